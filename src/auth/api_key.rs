@@ -0,0 +1,410 @@
+//! API keys and their quotas
+//!
+//! Keys are looked up by their SHA-256 digest, so neither the config nor a
+//! key file holds a usable key. Each key grants its configured roles and
+//! carries two optional quotas, counted over fixed windows: requests per
+//! minute, checked as a request arrives and as each subscription event is
+//! pushed, and entity rows returned per hour, charged as rows are sent (see
+//! `schema::RowCounter`). List fields are cut to the rows left in the window,
+//! and once it is used up entity fields fail and further requests are refused
+//! until the window ends.
+
+use crate::auth::Principal;
+use crate::config::{ApiKeyConfig, AuthConfig};
+use crate::error::{NouninatorError, Result};
+
+use ring::digest;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Hex SHA-256 digest of an API key, as configured in `sha256`
+pub fn hash_api_key(key: &str) -> String {
+    digest::digest(&digest::SHA256, key.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// The API keys requests may be made with
+#[derive(Debug, Clone, Default)]
+pub struct ApiKeys {
+    /// Keys by digest
+    keys: HashMap<String, ApiKey>,
+}
+
+/// The entries of an `api_key_file`
+#[derive(Deserialize)]
+struct ApiKeyFile {
+    #[serde(default)]
+    api_key: Vec<ApiKeyConfig>,
+}
+
+impl ApiKeys {
+    /// Load the keys of an `[auth]` section and its `api_key_file`
+    pub fn from_config(config: &AuthConfig) -> Result<Self> {
+        let mut keys = config.api_key.clone();
+        if let Some(path) = &config.api_key_file {
+            let contents = std::fs::read_to_string(path)
+                .map_err(|e| NouninatorError::Config(format!("Failed to read API key file '{}': {}", path, e)))?;
+            let file: ApiKeyFile = toml::from_str(&contents)
+                .map_err(|e| NouninatorError::Config(format!("Invalid API key file '{}': {}", path, e)))?;
+            keys.extend(file.api_key);
+        }
+        Self::new(keys)
+    }
+
+    pub fn new(configs: Vec<ApiKeyConfig>) -> Result<Self> {
+        let mut keys: HashMap<String, ApiKey> = HashMap::new();
+        for config in configs {
+            let digest = config.sha256.to_ascii_lowercase();
+            if digest.len() != 64 || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(NouninatorError::Config(format!(
+                    "API key '{}' needs a hex SHA-256 digest in 'sha256'",
+                    config.name
+                )));
+            }
+            if let Some(other) = keys.values().find(|key| key.name() == config.name) {
+                return Err(NouninatorError::Config(format!("API key '{}' is listed twice", other.name())));
+            }
+            if let Some(other) = keys.get(&digest) {
+                return Err(NouninatorError::Config(format!(
+                    "API keys '{}' and '{}' have the same digest",
+                    other.name(),
+                    config.name
+                )));
+            }
+            keys.insert(digest, ApiKey::new(config));
+        }
+        Ok(Self { keys })
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// The key a request presented, if it is known
+    pub fn find(&self, key: &str) -> Option<&ApiKey> {
+        self.keys.get(&hash_api_key(key))
+    }
+}
+
+/// One API key and what it has used of its quotas
+///
+/// Attached to the GraphQL request context next to the [`Principal`] it
+/// grants, so the rows sent can be charged to it.
+#[derive(Debug, Clone)]
+pub struct ApiKey(Arc<ApiKeyState>);
+
+#[derive(Debug)]
+struct ApiKeyState {
+    name: String,
+    roles: Vec<String>,
+    usage: Mutex<Usage>,
+}
+
+#[derive(Debug)]
+struct Usage {
+    requests: Option<Window>,
+    rows: Option<Window>,
+}
+
+/// A fixed window of a quota, starting at its first use
+#[derive(Debug)]
+struct Window {
+    limit: u64,
+    period: Duration,
+    start: Option<Instant>,
+    used: u64,
+}
+
+impl Window {
+    fn new(limit: u64, period: Duration) -> Self {
+        Self {
+            limit,
+            period,
+            start: None,
+            used: 0,
+        }
+    }
+
+    /// Start a new window if there is none or the current one has ended
+    fn roll(&mut self, now: Instant) {
+        if self.start.is_none_or(|start| now.saturating_duration_since(start) >= self.period) {
+            self.start = Some(now);
+            self.used = 0;
+        }
+    }
+
+    fn is_used_up(&self) -> bool {
+        self.used >= self.limit
+    }
+
+    fn remaining(&self, now: Instant) -> Duration {
+        let elapsed = self.start.map_or(Duration::ZERO, |start| now.saturating_duration_since(start));
+        self.period.saturating_sub(elapsed)
+    }
+}
+
+/// Which quota a refused request has used up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quota {
+    Requests,
+    Rows,
+}
+
+/// A request refused because its key has used up a quota
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotaExceeded {
+    pub key: String,
+    pub quota: Quota,
+    /// When the quota's window ends
+    pub retry_after: Duration,
+}
+
+impl fmt::Display for QuotaExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let quota = match self.quota {
+            Quota::Requests => "request rate",
+            Quota::Rows => "rows returned",
+        };
+        write!(f, "API key '{}' has exceeded its {} quota", self.key, quota)
+    }
+}
+
+impl ApiKey {
+    fn new(config: ApiKeyConfig) -> Self {
+        let usage = Usage {
+            requests: config
+                .requests_per_minute
+                .map(|limit| Window::new(limit, Duration::from_secs(60))),
+            rows: config.rows_per_hour.map(|limit| Window::new(limit, Duration::from_secs(3600))),
+        };
+        Self(Arc::new(ApiKeyState {
+            name: config.name,
+            roles: config.roles,
+            usage: Mutex::new(usage),
+        }))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    /// The caller a request made with the key acts as
+    pub fn principal(&self) -> Principal {
+        Principal {
+            subject: Some(self.0.name.clone()),
+            roles: self.0.roles.iter().cloned().collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Count a request against the key's quotas, unless one is used up
+    pub fn admit(&self) -> std::result::Result<(), QuotaExceeded> {
+        self.admit_at(Instant::now())
+    }
+
+    fn admit_at(&self, now: Instant) -> std::result::Result<(), QuotaExceeded> {
+        let mut usage = self.0.usage.lock().unwrap();
+        let exceeded = |quota, window: &Window| QuotaExceeded {
+            key: self.0.name.clone(),
+            quota,
+            retry_after: window.remaining(now),
+        };
+        if let Some(rows) = &mut usage.rows {
+            rows.roll(now);
+            if rows.is_used_up() {
+                return Err(exceeded(Quota::Rows, rows));
+            }
+        }
+        if let Some(requests) = &mut usage.requests {
+            requests.roll(now);
+            if requests.is_used_up() {
+                return Err(exceeded(Quota::Requests, requests));
+            }
+            requests.used += 1;
+        }
+        Ok(())
+    }
+
+    /// Fail once the key has been sent all the rows its quota allows
+    pub fn admit_rows(&self) -> std::result::Result<(), QuotaExceeded> {
+        self.admit_rows_at(Instant::now())
+    }
+
+    fn admit_rows_at(&self, now: Instant) -> std::result::Result<(), QuotaExceeded> {
+        if let Some(window) = &mut self.0.usage.lock().unwrap().rows {
+            window.roll(now);
+            if window.is_used_up() {
+                return Err(self.rows_exceeded(window, now));
+            }
+        }
+        Ok(())
+    }
+
+    /// Rows the key may still be sent in the current window (`None` without a row quota)
+    pub fn remaining_rows(&self) -> Option<u64> {
+        self.remaining_rows_at(Instant::now())
+    }
+
+    fn remaining_rows_at(&self, now: Instant) -> Option<u64> {
+        let mut usage = self.0.usage.lock().unwrap();
+        let window = usage.rows.as_mut()?;
+        window.roll(now);
+        Some(window.limit.saturating_sub(window.used))
+    }
+
+    /// Charge rows sent to the key's caller, as many as its row quota has left
+    ///
+    /// Returns the number of rows charged; the caller must not be sent more.
+    pub fn charge_rows(&self, rows: u64) -> std::result::Result<u64, QuotaExceeded> {
+        self.charge_rows_at(rows, Instant::now())
+    }
+
+    fn charge_rows_at(&self, rows: u64, now: Instant) -> std::result::Result<u64, QuotaExceeded> {
+        let Some(window) = &mut self.0.usage.lock().unwrap().rows else {
+            return Ok(rows);
+        };
+        window.roll(now);
+        if rows > 0 && window.is_used_up() {
+            return Err(self.rows_exceeded(window, now));
+        }
+        let charged = rows.min(window.limit.saturating_sub(window.used));
+        window.used += charged;
+        Ok(charged)
+    }
+
+    fn rows_exceeded(&self, window: &Window, now: Instant) -> QuotaExceeded {
+        QuotaExceeded {
+            key: self.0.name.clone(),
+            quota: Quota::Rows,
+            retry_after: window.remaining(now),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_config(name: &str, key: &str) -> ApiKeyConfig {
+        ApiKeyConfig {
+            name: name.to_string(),
+            sha256: hash_api_key(key),
+            roles: vec!["analyst".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_keys_are_found_by_digest() {
+        let keys = ApiKeys::new(vec![key_config("reporting", "secret-1"), key_config("etl", "secret-2")]).unwrap();
+
+        let key = keys.find("secret-2").unwrap();
+        assert_eq!(key.name(), "etl");
+        assert_eq!(key.principal().subject.as_deref(), Some("etl"));
+        assert!(key.principal().roles.contains("analyst"));
+        assert!(keys.find("secret-3").is_none());
+        // The digest itself is not a key
+        assert!(keys.find(&hash_api_key("secret-1")).is_none());
+
+        let err = ApiKeys::new(vec![key_config("a", "same"), key_config("b", "same")]).unwrap_err();
+        assert!(err.to_string().contains("same digest"), "{}", err);
+        let err = ApiKeys::new(vec![key_config("a", "one"), key_config("a", "two")]).unwrap_err();
+        assert!(err.to_string().contains("listed twice"), "{}", err);
+        let mut invalid = key_config("a", "one");
+        invalid.sha256 = "not-a-digest".to_string();
+        assert!(ApiKeys::new(vec![invalid]).is_err());
+    }
+
+    #[test]
+    fn test_keys_from_config_and_key_file() {
+        use std::io::Write;
+
+        let mut key_file = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            key_file,
+            "[[api_key]]\nname = \"etl\"\nsha256 = \"{}\"\nrows_per_hour = 1000\n",
+            hash_api_key("secret-2")
+        )
+        .unwrap();
+        key_file.flush().unwrap();
+
+        let config: AuthConfig = toml::from_str(&format!(
+            "api_key_file = {:?}\n\n[[api_key]]\nname = \"reporting\"\nsha256 = \"{}\"\nroles = [\"analyst\"]\n",
+            key_file.path().to_str().unwrap(),
+            hash_api_key("secret-1").to_uppercase()
+        ))
+        .unwrap();
+        assert!(config.validate().is_ok());
+
+        let keys = ApiKeys::from_config(&config).unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys.find("secret-1").unwrap().name(), "reporting");
+        assert_eq!(keys.find("secret-2").unwrap().name(), "etl");
+
+        let missing = AuthConfig {
+            api_key_file: Some("/nonexistent/api_keys.toml".to_string()),
+            ..Default::default()
+        };
+        assert!(ApiKeys::from_config(&missing).is_err());
+    }
+
+    #[test]
+    fn test_request_quota() {
+        let key = ApiKey::new(ApiKeyConfig {
+            requests_per_minute: Some(2),
+            ..key_config("reporting", "secret")
+        });
+        let start = Instant::now();
+        assert!(key.admit_at(start).is_ok());
+        assert!(key.admit_at(start + Duration::from_secs(1)).is_ok());
+
+        let exceeded = key.admit_at(start + Duration::from_secs(20)).unwrap_err();
+        assert_eq!(exceeded.quota, Quota::Requests);
+        assert_eq!(exceeded.retry_after, Duration::from_secs(40));
+        assert!(exceeded.to_string().contains("request rate"), "{}", exceeded);
+
+        // A new window starts once the minute is over
+        assert!(key.admit_at(start + Duration::from_secs(61)).is_ok());
+    }
+
+    #[test]
+    fn test_row_quota() {
+        let key = ApiKey::new(ApiKeyConfig {
+            rows_per_hour: Some(100),
+            ..key_config("reporting", "secret")
+        });
+        let start = Instant::now();
+        assert!(key.admit_at(start).is_ok());
+        assert_eq!(key.charge_rows_at(60, start), Ok(60));
+        assert_eq!(key.remaining_rows_at(start), Some(40));
+        assert!(key.admit_at(start).is_ok());
+        // Only what is left of the quota is charged, and nothing after that
+        assert_eq!(key.charge_rows_at(60, start), Ok(40));
+        assert_eq!(key.remaining_rows_at(start), Some(0));
+        let exceeded = key.charge_rows_at(1, start).unwrap_err();
+        assert_eq!(exceeded.quota, Quota::Rows);
+        assert_eq!(key.charge_rows_at(0, start), Ok(0));
+        assert!(key.admit_rows_at(start).is_err());
+        let exceeded = key.admit_at(start + Duration::from_secs(600)).unwrap_err();
+        assert_eq!(exceeded.quota, Quota::Rows);
+        assert_eq!(exceeded.retry_after, Duration::from_secs(3000));
+        assert!(exceeded.to_string().contains("rows returned"), "{}", exceeded);
+
+        assert!(key.admit_at(start + Duration::from_secs(3600)).is_ok());
+        assert_eq!(key.remaining_rows_at(start + Duration::from_secs(3600)), Some(100));
+
+        let unlimited = ApiKey::new(key_config("etl", "other"));
+        assert_eq!(unlimited.remaining_rows_at(start), None);
+        assert_eq!(unlimited.charge_rows_at(1000, start), Ok(1000));
+    }
+}
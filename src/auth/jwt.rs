@@ -0,0 +1,190 @@
+//! JWT decoding and claim validation
+//!
+//! Only signed tokens in compact serialization are accepted
+//! (`header.payload.signature`); unsigned tokens (`"alg": "none"`) are
+//! rejected like any other unsupported algorithm. Decoding, signature and
+//! standard claim checks are done by `jsonwebtoken`.
+
+use crate::auth::keys::{Algorithm, VerificationKey};
+use crate::error::{NouninatorError, Result};
+
+use jsonwebtoken::errors::ErrorKind;
+use serde_json::{Map, Value};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The verified claims of a request's token
+///
+/// Attached to the GraphQL request context; resolvers read them with
+/// `ctx.data_opt::<Claims>()`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Claims(Map<String, Value>);
+
+impl Claims {
+    pub fn new(claims: Map<String, Value>) -> Self {
+        Self(claims)
+    }
+
+    /// A top-level claim
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.0.get(name)
+    }
+
+    /// A claim addressed by a dotted path (`realm_access.roles`)
+    pub fn path(&self, path: &str) -> Option<&Value> {
+        let mut segments = path.split('.');
+        let first = self.0.get(segments.next()?)?;
+        segments.try_fold(first, |value, segment| value.get(segment))
+    }
+
+    /// The token's subject (`sub`)
+    pub fn subject(&self) -> Option<&str> {
+        self.get("sub").and_then(Value::as_str)
+    }
+
+    /// When the token expires (`exp`)
+    pub fn expires_at(&self) -> Option<SystemTime> {
+        let exp = self.get("exp").and_then(Value::as_f64)?;
+        Some(UNIX_EPOCH + Duration::try_from_secs_f64(exp).ok()?)
+    }
+
+    /// All claims
+    pub fn as_map(&self) -> &Map<String, Value> {
+        &self.0
+    }
+}
+
+/// What a token's claims are checked against
+#[derive(Debug, Clone, Default)]
+pub struct Validation {
+    pub issuer: Option<String>,
+    pub audience: Option<String>,
+    pub leeway: Duration,
+}
+
+impl Validation {
+    /// The `jsonwebtoken` validation for tokens signed with `alg`
+    ///
+    /// `exp` is required, and so are `iss` and `aud` when configured (a
+    /// token without them is rejected, not let through); `nbf` is checked
+    /// when present.
+    fn for_algorithm(&self, alg: Algorithm) -> jsonwebtoken::Validation {
+        let mut validation = jsonwebtoken::Validation::new(alg);
+        validation.leeway = self.leeway.as_secs();
+        validation.validate_nbf = true;
+
+        let mut required = vec!["exp"];
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+            required.push("iss");
+        }
+        match &self.audience {
+            Some(audience) => {
+                validation.set_audience(&[audience]);
+                required.push("aud");
+            }
+            None => validation.validate_aud = false,
+        }
+        validation.set_required_spec_claims(&required);
+        validation
+    }
+}
+
+/// Verify a token's signature with one of `keys` and check its claims
+pub fn verify(token: &str, keys: &[VerificationKey], validation: &Validation) -> Result<Claims> {
+    let header = jsonwebtoken::decode_header(token).map_err(|e| match e.kind() {
+        ErrorKind::Json(_) => unauthorized("Malformed token or unsupported token algorithm"),
+        _ => unauthorized("Malformed token"),
+    })?;
+
+    // A `kid` narrows the keys tried; keys published without one are always candidates
+    let candidates = keys.iter().filter(|key| {
+        let kid_matches = match (&header.kid, key.kid()) {
+            (Some(kid), Some(key_kid)) => kid == key_kid,
+            _ => true,
+        };
+        kid_matches && key.supports(header.alg)
+    });
+
+    let validation = validation.for_algorithm(header.alg);
+    let mut error = unauthorized("Invalid token signature");
+    for key in candidates {
+        match jsonwebtoken::decode::<Map<String, Value>>(token, key.decoding_key(), &validation) {
+            Ok(data) => return Ok(Claims(data.claims)),
+            // Another candidate key may have signed it
+            Err(e) if matches!(e.kind(), ErrorKind::InvalidSignature | ErrorKind::InvalidAlgorithm) => {}
+            Err(e) => error = rejection(e.kind()),
+        }
+    }
+    Err(error)
+}
+
+/// The error for a token whose signature verified but whose claims did not
+fn rejection(kind: &ErrorKind) -> NouninatorError {
+    unauthorized(match kind {
+        ErrorKind::MissingRequiredClaim(claim) if claim == "exp" => "Token has no expiry ('exp')",
+        ErrorKind::ExpiredSignature => "Token has expired",
+        ErrorKind::ImmatureSignature => "Token is not valid yet",
+        ErrorKind::InvalidIssuer => "Token was not issued by the expected issuer",
+        ErrorKind::MissingRequiredClaim(claim) if claim == "iss" => "Token was not issued by the expected issuer",
+        ErrorKind::InvalidAudience => "Token was not issued for this audience",
+        ErrorKind::MissingRequiredClaim(claim) if claim == "aud" => "Token was not issued for this audience",
+        _ => "Malformed token",
+    })
+}
+
+fn unauthorized(message: &str) -> NouninatorError {
+    NouninatorError::Unauthorized(message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde_json::json;
+
+    fn token(claims: Value) -> String {
+        encode(&Header::default(), &claims, &EncodingKey::from_secret(b"secret")).unwrap()
+    }
+
+    fn now() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+    }
+
+    #[test]
+    fn test_validate_claims() {
+        let keys = [VerificationKey::hmac(b"secret")];
+        let validation = Validation {
+            issuer: Some("https://issuer".to_string()),
+            audience: Some("api".to_string()),
+            leeway: Duration::from_secs(60),
+        };
+        let now = now();
+
+        let valid = json!({"exp": now + 100, "iss": "https://issuer", "aud": ["other", "api"]});
+        assert!(verify(&token(valid), &keys, &validation).is_ok());
+
+        // Within the leeway
+        let just_expired = json!({"exp": now - 30, "iss": "https://issuer", "aud": "api"});
+        assert!(verify(&token(just_expired), &keys, &validation).is_ok());
+
+        for (claims, expected) in [
+            (json!({"iss": "https://issuer", "aud": "api"}), "no expiry"),
+            (json!({"exp": now - 1000, "iss": "https://issuer", "aud": "api"}), "expired"),
+            (json!({"exp": now + 100, "nbf": now + 500, "iss": "https://issuer", "aud": "api"}), "not valid yet"),
+            (json!({"exp": now + 100, "iss": "https://other", "aud": "api"}), "issuer"),
+            (json!({"exp": now + 100, "aud": "api"}), "issuer"),
+            (json!({"exp": now + 100, "iss": "https://issuer", "aud": ["other"]}), "audience"),
+            (json!({"exp": now + 100, "iss": "https://issuer"}), "audience"),
+        ] {
+            let err = verify(&token(claims), &keys, &validation).unwrap_err().to_string();
+            assert!(err.contains(expected), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_expires_at() {
+        let claims = Claims::new(json!({"exp": 1_000_000}).as_object().unwrap().clone());
+        assert_eq!(claims.expires_at(), Some(UNIX_EPOCH + Duration::from_secs(1_000_000)));
+        assert_eq!(Claims::default().expires_at(), None);
+    }
+}
@@ -0,0 +1,140 @@
+//! Keys JWT signatures are verified with
+//!
+//! Keys come from a JSON Web Key Set (`kty` `RSA`, `EC` or `oct`), a PEM
+//! encoded RSA public key, or a shared HMAC secret. Each key only verifies
+//! the algorithm family it belongs to, so an RSA public key can never be
+//! used as an HMAC secret. Parsing and signature checks are done by
+//! `jsonwebtoken`; this module only decides which algorithms a key accepts.
+
+use crate::error::{NouninatorError, Result};
+
+use jsonwebtoken::jwk::{AlgorithmParameters, EllipticCurve, Jwk, PublicKeyUse};
+use jsonwebtoken::DecodingKey;
+use serde::Deserialize;
+use std::str::FromStr;
+
+pub use jsonwebtoken::Algorithm;
+
+const HMAC: &[Algorithm] = &[Algorithm::HS256, Algorithm::HS384, Algorithm::HS512];
+
+const RSA: &[Algorithm] = &[
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+];
+
+/// A key tokens can be verified with
+#[derive(Clone)]
+pub struct VerificationKey {
+    kid: Option<String>,
+    algorithms: Vec<Algorithm>,
+    key: DecodingKey,
+}
+
+impl std::fmt::Debug for VerificationKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print key material
+        f.debug_struct("VerificationKey")
+            .field("kid", &self.kid)
+            .field("algorithms", &self.algorithms)
+            .finish_non_exhaustive()
+    }
+}
+
+impl VerificationKey {
+    /// A shared secret for HS256, HS384 and HS512 tokens
+    pub fn hmac(secret: &[u8]) -> Self {
+        Self {
+            kid: None,
+            algorithms: HMAC.to_vec(),
+            key: DecodingKey::from_secret(secret),
+        }
+    }
+
+    /// An RSA public key in PEM (`BEGIN PUBLIC KEY` or `BEGIN RSA PUBLIC KEY`)
+    pub fn rsa_pem(pem: &str) -> Result<Self> {
+        let key = DecodingKey::from_rsa_pem(pem.as_bytes())
+            .map_err(|e| NouninatorError::Config(format!("Invalid RSA public key: {}", e)))?;
+        Ok(Self {
+            kid: None,
+            algorithms: RSA.to_vec(),
+            key,
+        })
+    }
+
+    /// Read the signing keys of a JSON Web Key Set
+    ///
+    /// Encryption keys (`"use": "enc"`) and unsupported key types are skipped.
+    pub fn jwks(json: &str) -> Result<Vec<Self>> {
+        let set: JwkSet =
+            serde_json::from_str(json).map_err(|e| NouninatorError::Config(format!("Invalid JWKS: {}", e)))?;
+
+        let mut keys = Vec::new();
+        for jwk in set.keys {
+            if !matches!(jwk.get("kty").and_then(|kty| kty.as_str()), Some("RSA" | "EC" | "oct")) {
+                continue;
+            }
+            let jwk: Jwk =
+                serde_json::from_value(jwk).map_err(|e| NouninatorError::Config(format!("Invalid JWK: {}", e)))?;
+            if jwk.common.public_key_use.as_ref().is_some_and(|key_use| *key_use != PublicKeyUse::Signature) {
+                continue;
+            }
+
+            let family = match &jwk.algorithm {
+                AlgorithmParameters::RSA(_) => RSA,
+                AlgorithmParameters::EllipticCurve(params) => match params.curve {
+                    EllipticCurve::P256 => &[Algorithm::ES256],
+                    EllipticCurve::P384 => &[Algorithm::ES384],
+                    _ => continue,
+                },
+                AlgorithmParameters::OctetKey(_) => HMAC,
+                AlgorithmParameters::OctetKeyPair(_) => continue,
+            };
+            // Keys may name algorithms this server does not implement; they cannot verify anything
+            let algorithms = match jwk.common.key_algorithm {
+                Some(alg) => match Algorithm::from_str(&alg.to_string()) {
+                    Ok(alg) if family.contains(&alg) => vec![alg],
+                    _ => continue,
+                },
+                None => family.to_vec(),
+            };
+
+            let key = DecodingKey::from_jwk(&jwk)
+                .map_err(|e| NouninatorError::Config(format!("Invalid JWK: {}", e)))?;
+            keys.push(Self {
+                kid: jwk.common.key_id,
+                algorithms,
+                key,
+            });
+        }
+
+        if keys.is_empty() {
+            return Err(NouninatorError::Config("JWKS contains no usable signing keys".to_string()));
+        }
+        Ok(keys)
+    }
+
+    /// Key id (`kid`) this key is published under
+    pub fn kid(&self) -> Option<&str> {
+        self.kid.as_deref()
+    }
+
+    /// Whether this key can verify tokens signed with `alg`
+    pub fn supports(&self, alg: Algorithm) -> bool {
+        self.algorithms.contains(&alg)
+    }
+
+    /// The key in the form `jsonwebtoken` verifies with
+    pub(crate) fn decoding_key(&self) -> &DecodingKey {
+        &self.key
+    }
+}
+
+/// A JWKS whose keys are parsed one by one, so unsupported key types can be skipped
+#[derive(Deserialize)]
+struct JwkSet {
+    keys: Vec<serde_json::Value>,
+}
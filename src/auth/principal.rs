@@ -0,0 +1,82 @@
+//! The caller a request is made on behalf of
+
+use crate::auth::Claims;
+
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+/// The roles and scopes of the caller, matched against `allow` rules
+///
+/// Attached to the GraphQL request context next to the [`Claims`] it was
+/// derived from; resolvers read it with `ctx.data_opt::<Principal>()`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Principal {
+    pub subject: Option<String>,
+    pub roles: BTreeSet<String>,
+    pub scopes: BTreeSet<String>,
+}
+
+impl Principal {
+    /// Read roles from the `roles_claim` claim (a string or a list of strings)
+    /// and scopes from `scope` (space-separated) or `scp`
+    pub fn from_claims(claims: &Claims, roles_claim: &str) -> Self {
+        let mut scopes = strings(claims.get("scope"));
+        scopes.extend(strings(claims.get("scp")));
+        Self {
+            subject: claims.subject().map(str::to_string),
+            roles: strings(claims.path(roles_claim)),
+            scopes,
+        }
+    }
+
+    /// Whether any role or scope of the caller is listed in `allow`
+    pub fn is_allowed(&self, allow: &[String]) -> bool {
+        allow
+            .iter()
+            .any(|entry| self.roles.contains(entry) || self.scopes.contains(entry))
+    }
+}
+
+fn strings(value: Option<&Value>) -> BTreeSet<String> {
+    match value {
+        Some(Value::String(s)) => s.split_whitespace().map(str::to_string).collect(),
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => BTreeSet::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_principal_from_claims() {
+        let claims = Claims::new(
+            json!({
+                "sub": "user-1",
+                "realm_access": {"roles": ["hr", "employee"]},
+                "scope": "read:orders write:orders",
+                "scp": ["admin:read"],
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        );
+
+        let principal = Principal::from_claims(&claims, "realm_access.roles");
+        assert_eq!(principal.subject.as_deref(), Some("user-1"));
+        assert!(principal.is_allowed(&["hr".to_string()]));
+        assert!(principal.is_allowed(&["finance".to_string(), "read:orders".to_string()]));
+        assert!(principal.is_allowed(&["admin:read".to_string()]));
+        assert!(!principal.is_allowed(&["finance".to_string()]));
+        assert!(!principal.is_allowed(&[]));
+
+        // A missing roles claim grants no roles
+        assert!(Principal::from_claims(&claims, "roles").roles.is_empty());
+    }
+}
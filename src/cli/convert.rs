@@ -71,7 +71,7 @@ async fn convert_single_file(
     
     // Read CSV into Arrow RecordBatch
     let file = File::open(csv_path)?;
-    let mut csv_reader = ReaderBuilder::new(Arc::clone(&schema))
+    let csv_reader = ReaderBuilder::new(Arc::clone(&schema))
         .with_header(true)
         .build(file)
         .map_err(|e| nouninator::error::NouninatorError::Config(format!("CSV read error: {}", e)))?;
@@ -79,7 +79,7 @@ async fn convert_single_file(
     let mut batches = Vec::new();
     let mut total_rows = 0;
     
-    while let Some(batch) = csv_reader.next() {
+    for batch in csv_reader {
         let batch = batch.map_err(|e| {
             nouninator::error::NouninatorError::Config(
                 format!("Failed to read CSV batch: {}", e)
@@ -93,10 +93,9 @@ async fn convert_single_file(
     let columns: Vec<deltalake::kernel::StructField> = schema
         .fields()
         .iter()
-        .cloned()
         .map(|f| {
             let delta_type: deltalake::kernel::DataType = f.data_type().try_into()
-                .expect(&format!("Failed to convert data type: {:?}", f.data_type()));
+                .unwrap_or_else(|_| panic!("Failed to convert data type: {:?}", f.data_type()));
             deltalake::kernel::StructField::new(
                 f.name().clone(),
                 delta_type,
@@ -36,9 +36,10 @@ pub fn create_example_entities() -> Vec<EntityConfig> {
                 entities.push(EntityConfig {
                     table: full_table_path,
                     graphql_name: table.graphql_name.to_string(),
-                    primary_key,
+                    primary_key: primary_key.into(),
                     description: Some(table.description.to_string()),
                     storage_location: Some(storage_location),
+                    ..Default::default()
                 });
                 
                 tracing::debug!(
@@ -63,9 +64,10 @@ pub fn create_example_entities() -> Vec<EntityConfig> {
                 entities.push(EntityConfig {
                     table: full_table_path,
                     graphql_name: table.graphql_name.to_string(),
-                    primary_key: infer_primary_key_from_name(table.csv_file),
+                    primary_key: infer_primary_key_from_name(table.csv_file).into(),
                     description: Some(table.description.to_string()),
                     storage_location: Some(storage_location),
+                    ..Default::default()
                 });
             }
         }
@@ -201,7 +203,7 @@ mod tests {
         // Check specific entities exist
         let noun = entities.iter().find(|e| e.graphql_name == "Noun");
         assert!(noun.is_some());
-        assert_eq!(noun.unwrap().table, "language.parts_of_speech.nouns");
+        assert_eq!(noun.unwrap().table, "nouns");
         assert_eq!(noun.unwrap().primary_key, "noun_id");
         
         let verb = entities.iter().find(|e| e.graphql_name == "Verb");
@@ -228,12 +230,13 @@ mod tests {
             assert!(entity.graphql_name.chars().next().unwrap().is_uppercase(),
                 "Entity {} not PascalCase", entity.graphql_name);
             
-            // Should have valid table format
-            assert_eq!(entity.table.split('.').count(), 3,
+            // Should have valid table format (local tables use simple names)
+            let parts = entity.table.split('.').count();
+            assert!(parts == 1 || parts == 3,
                 "Entity {} has invalid table format", entity.table);
             
             // Should have valid primary key
-            assert!(!entity.primary_key.is_empty(),
+            assert!(entity.primary_key.columns().iter().all(|c| !c.is_empty()),
                 "Entity {} has empty primary key", entity.graphql_name);
         }
     }
@@ -50,6 +50,7 @@ async fn run_example(output: Option<String>) -> Result<()> {
             port: 4000,
             bind: "0.0.0.0".to_string(),
         },
+        auth: None,
         entity: entities,
     };
     
@@ -131,6 +132,7 @@ async fn run_unity_catalog(
             port: 4000,
             bind: "0.0.0.0".to_string(),
         },
+        auth: None,
         entity: entities,
     };
     
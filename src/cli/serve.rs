@@ -1,10 +1,25 @@
-use nouninator::error::Result;
-use nouninator::schema::SchemaBuilder;
-use axum::{routing::get, routing::post, Router};
+use nouninator::auth::{ApiKey, ApiKeys, Authenticator, Claims, Principal, QuotaExceeded};
+use nouninator::config::EntityConfig;
+use nouninator::error::{NouninatorError, Result};
+use nouninator::schema::{SchemaBuilder, TableRegistry};
+use async_graphql::http::ALL_WEBSOCKET_PROTOCOLS;
+use async_graphql_axum::{GraphQLProtocol, GraphQLWebSocket};
+use axum::extract::ws::WebSocketUpgrade;
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::{routing::get, routing::post, Extension, Router};
+use futures_util::future::BoxFuture;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
 use tower_http::cors::CorsLayer;
 
+/// Header API keys are sent in
+const API_KEY_HEADER: &str = "x-api-key";
+
 /// Run the serve command to start the GraphQL server
 pub async fn run(config_path: String, port: u16) -> Result<()> {
     
@@ -16,6 +31,23 @@ pub async fn run(config_path: String, port: u16) -> Result<()> {
     // Use provided port or default from config
     let server_port = if port != 4000 { port } else { config.server.port };
     
+    // Load token verification keys and API keys before anything is served
+    let authenticator = match &config.auth {
+        Some(auth) if auth.uses_tokens() => {
+            tracing::info!("🔒 Accepting bearer tokens on /graphql and /ws");
+            Some(Arc::new(Authenticator::from_config(auth)?))
+        }
+        _ => None,
+    };
+    let api_keys = match &config.auth {
+        Some(auth) if auth.uses_api_keys() => {
+            let api_keys = ApiKeys::from_config(auth)?;
+            tracing::info!("🔑 Accepting {} API keys on /graphql and /ws", api_keys.len());
+            Some(Arc::new(api_keys))
+        }
+        _ => None,
+    };
+    
     tracing::info!("🔧 Building GraphQL schema for {} entities...", config.entity.len());
     
     // Create schema builder
@@ -30,6 +62,8 @@ pub async fn run(config_path: String, port: u16) -> Result<()> {
     }
     
     // Build the GraphQL schema
+    let registry = builder.registry();
+    spawn_refresh_tasks(&config.entity, &registry);
     let schema = builder.build_schema(config.entity).await?;
     
     tracing::info!("✅ Schema built successfully");
@@ -38,7 +72,44 @@ pub async fn run(config_path: String, port: u16) -> Result<()> {
     tracing::info!("💡 Press Ctrl+C to stop the server");
     
     // Start the HTTP server
-    start_http_server(schema, server_port).await
+    let state = AppState {
+        schema: Arc::new(schema),
+        registry,
+        authenticator,
+        api_keys,
+    };
+    start_http_server(state, server_port).await
+}
+
+/// Spawn a background task per Delta table that swaps in new commits
+fn spawn_refresh_tasks(entities: &[EntityConfig], registry: &TableRegistry) {
+    for entity in entities {
+        let (Some(table), Some(interval)) = (registry.served_table(&entity.table), entity.refresh_interval()) else {
+            continue;
+        };
+
+        tracing::info!(
+            "   Refreshing {} every {}s (serving version {})",
+            entity.graphql_name,
+            interval.as_secs(),
+            table.version()
+        );
+
+        let name = entity.table.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            // The first tick completes immediately; the table was just opened
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                match table.refresh().await {
+                    Ok(Some(version)) => tracing::info!("🔄 Now serving {} at version {}", name, version),
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("Failed to refresh {}: {}", name, e),
+                }
+            }
+        });
+    }
 }
 
 fn determine_table_path(entity: &nouninator::config::EntityConfig) -> String {
@@ -54,20 +125,114 @@ fn determine_table_path(entity: &nouninator::config::EntityConfig) -> String {
         })
 }
 
-async fn start_http_server(
-    schema: async_graphql::dynamic::Schema,
-    port: u16,
-) -> Result<()> {
-    
-    // Wrap schema in Arc for sharing across handlers
-    let schema = Arc::new(schema);
-    
-    // Create the router with GraphQL endpoints
-    let app = Router::new()
-        .route("/graphql", post(graphql_handler).get(graphql_playground))
+/// State shared across handlers
+#[derive(Clone)]
+struct AppState {
+    schema: Arc<async_graphql::dynamic::Schema>,
+    registry: Arc<TableRegistry>,
+    /// Verifies request tokens when the `[auth]` section has token keys
+    authenticator: Option<Arc<Authenticator>>,
+    /// API keys when the `[auth]` section lists any
+    api_keys: Option<Arc<ApiKeys>>,
+}
+
+impl AppState {
+    /// Whether requests must authenticate
+    fn requires_auth(&self) -> bool {
+        self.authenticator.is_some() || self.api_keys.is_some()
+    }
+}
+
+/// Create the router with GraphQL endpoints
+///
+/// The playground page and `/health` stay public; queries and subscriptions
+/// need a token or API key when auth is configured.
+fn router(state: AppState) -> Router {
+    let mut query = post(graphql_handler).route_layer(middleware::from_fn_with_state(state.clone(), require_token));
+    let mut subscribe = get(subscription_handler);
+    // Only queries and subscriptions count against an API key's quota
+    if let Some(api_keys) = &state.api_keys {
+        let layer = ApiKeyLayer {
+            api_keys: api_keys.clone(),
+        };
+        query = query.route_layer(layer.clone());
+        subscribe = subscribe.route_layer(layer);
+    }
+
+    Router::new()
+        .route("/graphql", query.get(graphql_playground))
+        .route("/ws", subscribe)
         .route("/health", get(health_check))
-        .with_state(schema)
-        .layer(CorsLayer::permissive());
+        .with_state(state)
+        .layer(CorsLayer::permissive())
+}
+
+/// Authenticates requests that carry an `X-API-Key` header and enforces the
+/// key's quotas
+///
+/// Unknown keys get a 401 and keys over quota a 429 with `Retry-After`. The
+/// key and the principal it grants are passed on in the request's
+/// extensions; requests without the header are left to `require_token`.
+#[derive(Clone)]
+struct ApiKeyLayer {
+    api_keys: Arc<ApiKeys>,
+}
+
+impl<S> Layer<S> for ApiKeyLayer {
+    type Service = ApiKeyService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ApiKeyService {
+            inner,
+            api_keys: self.api_keys.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct ApiKeyService<S> {
+    inner: S,
+    api_keys: Arc<ApiKeys>,
+}
+
+impl<S> Service<Request> for ApiKeyService<S>
+where
+    S: Service<Request, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, std::result::Result<Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<std::result::Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request) -> Self::Future {
+        // Use the service that was polled ready, leaving a fresh clone in its place
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let Some(presented) = request.headers().get(API_KEY_HEADER) else {
+            return Box::pin(inner.call(request));
+        };
+        let Some(key) = presented.to_str().ok().and_then(|presented| self.api_keys.find(presented)) else {
+            let error = NouninatorError::Unauthorized("Unknown API key".to_string());
+            return Box::pin(std::future::ready(Ok(unauthorized(error))));
+        };
+        if let Err(exceeded) = key.admit() {
+            return Box::pin(std::future::ready(Ok(too_many_requests(exceeded))));
+        }
+
+        request.extensions_mut().insert(key.principal());
+        request.extensions_mut().insert(key.clone());
+        Box::pin(inner.call(request))
+    }
+}
+
+async fn start_http_server(state: AppState, port: u16) -> Result<()> {
+    let app = router(state);
     
     let addr = SocketAddr::from(([0, 0, 0, 0], port));
     let listener = tokio::net::TcpListener::bind(addr)
@@ -85,22 +250,322 @@ async fn start_http_server(
     Ok(())
 }
 
+/// Reject requests without a valid bearer token and pass its claims (and the
+/// principal derived from them) on
+///
+/// Requests already authenticated by an API key pass through.
+async fn require_token(State(state): State<AppState>, mut request: Request, next: Next) -> Response {
+    if request.extensions().get::<ApiKey>().is_some() {
+        return next.run(request).await;
+    }
+    match &state.authenticator {
+        Some(authenticator) => {
+            let authorization = request.headers().get(header::AUTHORIZATION).and_then(|v| v.to_str().ok());
+            match authenticator.verify_bearer(authorization) {
+                Ok(claims) => {
+                    request.extensions_mut().insert(authenticator.principal(&claims));
+                    request.extensions_mut().insert(claims);
+                }
+                Err(e) => return unauthorized(e),
+            }
+        }
+        None if state.requires_auth() => return unauthorized(missing_api_key()),
+        None => {}
+    }
+    next.run(request).await
+}
+
+fn missing_api_key() -> NouninatorError {
+    NouninatorError::Unauthorized("Missing API key ('X-API-Key' header)".to_string())
+}
+
+/// 401 with a GraphQL error body
+fn unauthorized(error: NouninatorError) -> Response {
+    tracing::debug!("Rejected request: {}", error);
+    let body = async_graphql::Response::from_errors(vec![async_graphql::ServerError::new(error.to_string(), None)]);
+    (
+        StatusCode::UNAUTHORIZED,
+        [(header::WWW_AUTHENTICATE, "Bearer")],
+        axum::Json(body),
+    )
+        .into_response()
+}
+
+/// 429 with a GraphQL error body and the seconds until the quota's window ends
+fn too_many_requests(exceeded: QuotaExceeded) -> Response {
+    tracing::debug!("Refused request: {}", exceeded);
+    let retry_after = exceeded.retry_after.as_secs_f64().ceil().max(1.0) as u64;
+    let body = async_graphql::Response::from_errors(vec![async_graphql::ServerError::new(exceeded.to_string(), None)]);
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, retry_after.to_string())],
+        axum::Json(body),
+    )
+        .into_response()
+}
+
 async fn graphql_handler(
-    axum::extract::State(schema): axum::extract::State<std::sync::Arc<async_graphql::dynamic::Schema>>,
-    axum::Json(request): axum::Json<async_graphql::Request>,
+    State(state): State<AppState>,
+    claims: Option<Extension<Claims>>,
+    principal: Option<Extension<Principal>>,
+    api_key: Option<Extension<ApiKey>>,
+    axum::Json(mut request): axum::Json<async_graphql::Request>,
 ) -> axum::Json<async_graphql::Response> {
-    axum::Json(schema.execute(request).await)
+    if let Some(Extension(claims)) = claims {
+        request = request.data(claims);
+    }
+    if let Some(Extension(principal)) = principal {
+        request = request.data(principal);
+    }
+    // Rows sent are charged to the key's quota
+    if let Some(Extension(api_key)) = api_key {
+        request = request.data(api_key);
+    }
+    axum::Json(state.schema.execute(request).await)
+}
+
+/// Subscriptions over WebSocket (graphql-transport-ws and legacy graphql-ws)
+///
+/// Browsers cannot set headers on a WebSocket, so the token may be sent as
+/// `Authorization` in the `connection_init` payload instead of as a header.
+/// API keys must be sent in the `X-API-Key` header of the upgrade request,
+/// which counts as one request against the key's quota.
+async fn subscription_handler(
+    State(state): State<AppState>,
+    protocol: GraphQLProtocol,
+    headers: HeaderMap,
+    api_key: Option<Extension<ApiKey>>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    let api_key = api_key.map(|Extension(api_key)| api_key);
+    let header_claims = match (&api_key, &state.authenticator, headers.get(header::AUTHORIZATION)) {
+        (Some(_), _, _) => None,
+        (None, Some(authenticator), Some(authorization)) => {
+            match authenticator.verify_bearer(authorization.to_str().ok()) {
+                Ok(claims) => Some(claims),
+                Err(e) => return unauthorized(e),
+            }
+        }
+        (None, None, _) if state.requires_auth() => return unauthorized(missing_api_key()),
+        _ => None,
+    };
+
+    let schema = (*state.schema).clone();
+    let authenticator = state.authenticator.clone();
+    upgrade
+        .protocols(ALL_WEBSOCKET_PROTOCOLS)
+        .on_upgrade(move |stream| {
+            GraphQLWebSocket::new(stream, schema, protocol)
+                .on_connection_init(move |payload| async move {
+                    let mut data = async_graphql::Data::default();
+                    if let Some(api_key) = api_key {
+                        data.insert(api_key.principal());
+                        data.insert(api_key);
+                    } else if let Some(authenticator) = authenticator {
+                        let claims = match header_claims {
+                            Some(claims) => claims,
+                            None => authenticator
+                                .verify_bearer(payload_authorization(&payload))
+                                .map_err(|e| async_graphql::Error::new(e.to_string()))?,
+                        };
+                        data.insert(authenticator.principal(&claims));
+                        data.insert(claims);
+                    }
+                    Ok(data)
+                })
+                .serve()
+        })
+}
+
+/// The `Authorization` (or `authorization`) entry of a `connection_init` payload
+fn payload_authorization(payload: &serde_json::Value) -> Option<&str> {
+    payload
+        .get("Authorization")
+        .or_else(|| payload.get("authorization"))
+        .and_then(|value| value.as_str())
 }
 
 async fn graphql_playground() -> axum::response::Html<String> {
     axum::response::Html(
         async_graphql::http::playground_source(
-            async_graphql::http::GraphQLPlaygroundConfig::new("/graphql")
+            async_graphql::http::GraphQLPlaygroundConfig::new("/graphql").subscription_endpoint("/ws")
         )
     )
 }
 
-async fn health_check() -> &'static str {
-    "OK"
+/// Health check reporting the Delta table versions being served
+async fn health_check(
+    State(state): State<AppState>,
+) -> axum::Json<serde_json::Value> {
+    axum::Json(serde_json::json!({
+        "status": "OK",
+        "tables": state.registry.served_versions(),
+    }))
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::dynamic::{Field, FieldFuture, Object, Schema, TypeRef};
+    use async_graphql::Value;
+    use axum::body::Body;
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+    use nouninator::auth::{hash_api_key, VerificationKey};
+    use nouninator::config::{ApiKeyConfig, AuthConfig};
+    use ring::hmac;
+    use tower::ServiceExt;
+
+    /// A schema answering `whoami` with the subject of the request's claims
+    /// and `roles` with its principal's roles
+    fn test_state(authenticator: Option<Authenticator>) -> AppState {
+        let query = Object::new("Query")
+            .field(Field::new("whoami", TypeRef::named(TypeRef::STRING), |ctx| {
+                FieldFuture::new(async move {
+                    let subject = ctx.data_opt::<Claims>().and_then(|claims| claims.subject());
+                    Ok(subject.map(|sub| Value::from(sub.to_string())))
+                })
+            }))
+            .field(Field::new("roles", TypeRef::named_nn_list_nn(TypeRef::STRING), |ctx| {
+                FieldFuture::new(async move {
+                    let roles = ctx.data_opt::<Principal>().map(|p| p.roles.clone()).unwrap_or_default();
+                    Ok(Some(Value::List(roles.into_iter().map(Value::from).collect())))
+                })
+            }));
+        AppState {
+            schema: Arc::new(Schema::build("Query", None, None).register(query).finish().unwrap()),
+            registry: Arc::new(TableRegistry::new()),
+            authenticator: authenticator.map(Arc::new),
+            api_keys: None,
+        }
+    }
+
+    fn token(secret: &[u8]) -> String {
+        let exp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() + 300;
+        let signing_input = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(r#"{"alg":"HS256"}"#),
+            URL_SAFE_NO_PAD.encode(serde_json::json!({"sub": "user-1", "exp": exp, "roles": ["analyst"]}).to_string())
+        );
+        let sig = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, secret), signing_input.as_bytes());
+        format!("{}.{}", signing_input, URL_SAFE_NO_PAD.encode(sig.as_ref()))
+    }
+
+    async fn send(state: AppState, request: axum::http::Request<Body>) -> (StatusCode, HeaderMap, serde_json::Value) {
+        let response = router(state).oneshot(request).await.unwrap();
+        let (parts, body) = response.into_parts();
+        let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        let json = serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null);
+        (parts.status, parts.headers, json)
+    }
+
+    fn query(authorization: Option<String>) -> axum::http::Request<Body> {
+        let mut request = axum::http::Request::post("/graphql").header(header::CONTENT_TYPE, "application/json");
+        if let Some(authorization) = authorization {
+            request = request.header(header::AUTHORIZATION, authorization);
+        }
+        request.body(Body::from(r#"{"query": "{ whoami roles }"}"#)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_graphql_requires_a_valid_token() {
+        let authenticator = Authenticator::new(vec![VerificationKey::hmac(b"secret")], &AuthConfig::default());
+        let state = test_state(Some(authenticator));
+
+        let (status, headers, body) = send(state.clone(), query(None)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(headers[header::WWW_AUTHENTICATE], "Bearer");
+        assert!(body["errors"][0]["message"].as_str().unwrap().contains("Missing bearer token"));
+
+        let (status, _, _) = send(state.clone(), query(Some(format!("Bearer {}", token(b"wrong"))))).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, _, body) = send(state.clone(), query(Some(format!("Bearer {}", token(b"secret"))))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["whoami"], "user-1");
+        assert_eq!(body["data"]["roles"], serde_json::json!(["analyst"]));
+
+        // The playground and health check stay public
+        let playground = axum::http::Request::get("/graphql").body(Body::empty()).unwrap();
+        assert_eq!(send(state.clone(), playground).await.0, StatusCode::OK);
+        let health = axum::http::Request::get("/health").body(Body::empty()).unwrap();
+        assert_eq!(send(state, health).await.0, StatusCode::OK);
+    }
+
+    fn query_with_key(key: &str) -> axum::http::Request<Body> {
+        let mut request = query(None);
+        request.headers_mut().insert(API_KEY_HEADER, key.parse().unwrap());
+        request
+    }
+
+    #[tokio::test]
+    async fn test_api_keys_and_their_request_quota() {
+        let api_keys = ApiKeys::new(vec![ApiKeyConfig {
+            name: "reporting".to_string(),
+            sha256: hash_api_key("key-1"),
+            roles: vec!["analyst".to_string()],
+            requests_per_minute: Some(2),
+            ..Default::default()
+        }])
+        .unwrap();
+        let authenticator = Authenticator::new(vec![VerificationKey::hmac(b"secret")], &AuthConfig::default());
+        let state = AppState {
+            api_keys: Some(Arc::new(api_keys)),
+            ..test_state(Some(authenticator))
+        };
+
+        let (status, _, body) = send(state.clone(), query_with_key("key-2")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert!(body["errors"][0]["message"].as_str().unwrap().contains("Unknown API key"));
+
+        let (status, _, body) = send(state.clone(), query_with_key("key-1")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["roles"], serde_json::json!(["analyst"]));
+
+        // Bearer tokens are still accepted, and do not count against the key
+        let (status, _, _) = send(state.clone(), query(Some(format!("Bearer {}", token(b"secret"))))).await;
+        assert_eq!(status, StatusCode::OK);
+
+        assert_eq!(send(state.clone(), query_with_key("key-1")).await.0, StatusCode::OK);
+        let (status, headers, body) = send(state.clone(), query_with_key("key-1")).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: u64 = headers[header::RETRY_AFTER].to_str().unwrap().parse().unwrap();
+        assert!((1..=60).contains(&retry_after), "{}", retry_after);
+        assert!(body["errors"][0]["message"].as_str().unwrap().contains("request rate"));
+
+        // The playground and health check neither check keys nor count against them
+        for path in ["/health", "/graphql"] {
+            for key in ["key-1", "key-2"] {
+                let request = axum::http::Request::get(path).header(API_KEY_HEADER, key).body(Body::empty()).unwrap();
+                assert_eq!(send(state.clone(), request).await.0, StatusCode::OK, "{} with {}", path, key);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_api_keys_alone_require_a_key() {
+        let api_keys = ApiKeys::new(vec![ApiKeyConfig {
+            name: "reporting".to_string(),
+            sha256: hash_api_key("key-1"),
+            ..Default::default()
+        }])
+        .unwrap();
+        let state = AppState {
+            api_keys: Some(Arc::new(api_keys)),
+            ..test_state(None)
+        };
+
+        let (status, _, body) = send(state.clone(), query(None)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert!(body["errors"][0]["message"].as_str().unwrap().contains("Missing API key"));
+        assert_eq!(send(state, query_with_key("key-1")).await.0, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_graphql_is_open_without_auth() {
+        let (status, _, body) = send(test_state(None), query(None)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["whoami"], serde_json::Value::Null);
+    }
+}
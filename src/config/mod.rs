@@ -1,6 +1,10 @@
 mod types;
 
-pub use types::{Config, DatabricksConfig, EntityConfig, ServerConfig};
+pub use types::{
+    ApiKeyConfig, AuthConfig, ColumnConfig, Config, DatabricksConfig, EntityConfig, ForbiddenFields, Mask, PrimaryKey,
+    RelationConfig, RelationKind, ServerConfig, DEFAULT_AUTH_LEEWAY_SECS, DEFAULT_MAX_BULK_INSERT_ROWS,
+    DEFAULT_MAX_LOOKUP_IDS, DEFAULT_REFRESH_INTERVAL_SECS, DEFAULT_ROLES_CLAIM,
+};
 
 use crate::error::{NouninatorError, Result};
 use std::fs;
@@ -18,6 +22,45 @@ pub fn load_config(path: &str) -> Result<Config> {
             .map_err(NouninatorError::Config)?;
     }
     
+    // Validate relations point at configured entities
+    for entity in &config.entity {
+        for relation in &entity.relation {
+            if !config.entity.iter().any(|e| e.graphql_name == relation.target) {
+                return Err(NouninatorError::Config(format!(
+                    "Relation '{}' on '{}' targets unknown entity '{}'",
+                    relation.name, entity.graphql_name, relation.target
+                )));
+            }
+        }
+    }
+    
+    // Validate the auth section (if present)
+    if let Some(ref auth) = config.auth {
+        auth.validate().map_err(NouninatorError::Config)?;
+    }
+    
+    // Allow rules, role masks and claim-based row filters apply to authenticated callers
+    if config.auth.is_none() {
+        if let Some(entity) = config.entity.iter().find(|e| e.has_allow_rules()) {
+            return Err(NouninatorError::Config(format!(
+                "Entity '{}' has allow rules, which need an [auth] section",
+                entity.graphql_name
+            )));
+        }
+        if let Some(entity) = config.entity.iter().find(|e| e.has_role_masks()) {
+            return Err(NouninatorError::Config(format!(
+                "Entity '{}' has role masks, which need an [auth] section",
+                entity.graphql_name
+            )));
+        }
+        if let Some(entity) = config.entity.iter().find(|e| e.row_filter_uses_claims()) {
+            return Err(NouninatorError::Config(format!(
+                "Entity '{}' has a row filter reading claims, which needs an [auth] section",
+                entity.graphql_name
+            )));
+        }
+    }
+    
     // Validate Databricks host is a valid URL (if present)
     if let Some(ref databricks) = config.databricks {
         if !databricks.host.starts_with("http://") && !databricks.host.starts_with("https://") {
@@ -105,6 +148,165 @@ primary_key = "customer_id"
         assert!(config.is_err());
     }
 
+    #[test]
+    fn test_load_auth_section() {
+        let config_content = r#"
+[server]
+port = 4000
+bind = "0.0.0.0"
+
+[auth]
+jwks_file = "jwks.json"
+audience = "nouninator"
+
+[[entity]]
+table = "main.sales.customers"
+graphql_name = "Customer"
+primary_key = "customer_id"
+"#;
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(config_content.as_bytes()).unwrap();
+        temp_file.flush().unwrap();
+
+        let config = load_config(temp_file.path().to_str().unwrap()).unwrap();
+        let auth = config.auth.unwrap();
+        assert_eq!(auth.jwks_file.as_deref(), Some("jwks.json"));
+        assert_eq!(auth.audience.as_deref(), Some("nouninator"));
+        assert_eq!(auth.leeway().as_secs(), DEFAULT_AUTH_LEEWAY_SECS);
+
+        // An [auth] section without any key source is rejected
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file
+            .write_all(config_content.replace("jwks_file = \"jwks.json\"", "").as_bytes())
+            .unwrap();
+        temp_file.flush().unwrap();
+        let err = load_config(temp_file.path().to_str().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("at least one of"), "{}", err);
+    }
+
+    #[test]
+    fn test_load_allow_rules() {
+        let config_content = r#"
+[server]
+port = 4000
+bind = "0.0.0.0"
+
+[auth]
+jwks_file = "jwks.json"
+roles_claim = "realm_access.roles"
+
+[[entity]]
+table = "main.hr.employees"
+graphql_name = "Employee"
+primary_key = "employee_id"
+allow = ["hr", "read:employees"]
+forbidden_fields = "null"
+hide_forbidden = true
+
+[entity.columns.salary]
+allow = ["payroll"]
+"#;
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(config_content.as_bytes()).unwrap();
+        temp_file.flush().unwrap();
+
+        let config = load_config(temp_file.path().to_str().unwrap()).unwrap();
+        assert_eq!(config.auth.as_ref().unwrap().roles_claim(), "realm_access.roles");
+        let employee = &config.entity[0];
+        assert_eq!(employee.allow, Some(vec!["hr".to_string(), "read:employees".to_string()]));
+        assert_eq!(employee.forbidden_fields(), ForbiddenFields::Null);
+        assert!(employee.hide_forbidden);
+        assert_eq!(employee.column_allow("salary"), Some(&["payroll".to_string()][..]));
+        assert_eq!(employee.column_allow("name"), None);
+
+        // Allow rules need tokens to check them against
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file
+            .write_all(config_content.replacen("[auth]", "[unused]", 1).as_bytes())
+            .unwrap();
+        temp_file.flush().unwrap();
+        let err = load_config(temp_file.path().to_str().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("need an [auth] section"), "{}", err);
+    }
+
+    #[test]
+    fn test_load_masks() {
+        let config_content = r#"
+[server]
+port = 4000
+bind = "0.0.0.0"
+
+[auth]
+jwks_file = "jwks.json"
+
+[[entity]]
+table = "main.hr.employees"
+graphql_name = "Employee"
+primary_key = "employee_id"
+
+[entity.columns.ssn]
+mask = "partial"
+role_masks = { hr = "none", auditor = "hash" }
+"#;
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(config_content.as_bytes()).unwrap();
+        temp_file.flush().unwrap();
+
+        let config = load_config(temp_file.path().to_str().unwrap()).unwrap();
+        let ssn = &config.entity[0].columns["ssn"];
+        assert_eq!(ssn.mask, Some(Mask::Partial));
+        assert_eq!(ssn.role_masks["hr"], Mask::None);
+        assert_eq!(ssn.role_masks["auditor"], Mask::Hash);
+        assert!(ssn.is_masked() && ssn.masks_text());
+
+        // Role masks need tokens to pick them by
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file
+            .write_all(config_content.replacen("[auth]", "[unused]", 1).as_bytes())
+            .unwrap();
+        temp_file.flush().unwrap();
+        let err = load_config(temp_file.path().to_str().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("role masks, which need an [auth] section"), "{}", err);
+
+        // The primary key addresses rows, so it cannot be masked
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file
+            .write_all(config_content.replace("[entity.columns.ssn]", "[entity.columns.employee_id]").as_bytes())
+            .unwrap();
+        temp_file.flush().unwrap();
+        let err = load_config(temp_file.path().to_str().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("cannot be masked"), "{}", err);
+    }
+
+    #[test]
+    fn test_row_filter_claims_need_auth() {
+        let config_content = r#"
+[server]
+port = 4000
+bind = "0.0.0.0"
+
+[[entity]]
+table = "main.sales.orders"
+graphql_name = "Order"
+primary_key = "order_id"
+row_filter = "tenant_id = {claims.tenant}"
+"#;
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(config_content.as_bytes()).unwrap();
+        temp_file.flush().unwrap();
+        let err = load_config(temp_file.path().to_str().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("needs an [auth] section"), "{}", err);
+
+        // Filters that do not read claims apply to everyone
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file
+            .write_all(config_content.replace("{claims.tenant}", "'acme'").as_bytes())
+            .unwrap();
+        temp_file.flush().unwrap();
+        let config = load_config(temp_file.path().to_str().unwrap()).unwrap();
+        assert_eq!(config.entity[0].row_filter.as_deref(), Some("tenant_id = 'acme'"));
+    }
+
     #[test]
     fn test_save_and_load_config() {
         let config = Config {
@@ -115,13 +317,15 @@ primary_key = "customer_id"
                 port: 4000,
                 bind: "0.0.0.0".to_string(),
             },
+            auth: None,
             entity: vec![
                 EntityConfig {
                     table: "main.test.table1".to_string(),
                     graphql_name: "Table1".to_string(),
-                    primary_key: "id".to_string(),
+                    primary_key: "id".into(),
                     description: Some("Test table".to_string()),
                     storage_location: None,
+                    ..Default::default()
                 }
             ],
         };
@@ -163,5 +367,99 @@ description = "A local table"
         assert_eq!(config.entity.len(), 1);
         assert_eq!(config.entity[0].table, "local_table");
     }
+
+    #[test]
+    fn test_load_config_with_relations() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        let config_content = r#"
+[server]
+port = 4000
+
+[[entity]]
+table = "customers"
+graphql_name = "Customer"
+primary_key = "customer_id"
+
+[[entity.relation]]
+name = "orders"
+target = "Order"
+local_column = "customer_id"
+remote_column = "customer_id"
+kind = "many"
+
+[[entity]]
+table = "orders"
+graphql_name = "Order"
+primary_key = "order_id"
+
+[[entity.relation]]
+name = "customer"
+target = "Customer"
+local_column = "customer_id"
+remote_column = "customer_id"
+"#;
+        temp_file.write_all(config_content.as_bytes()).unwrap();
+        temp_file.flush().unwrap();
+        
+        let config = load_config(temp_file.path().to_str().unwrap()).unwrap();
+        assert_eq!(config.entity[0].relation.len(), 1);
+        assert_eq!(config.entity[0].relation[0].kind, RelationKind::Many);
+        assert_eq!(config.entity[1].relation[0].kind, RelationKind::One);
+    }
+
+    #[test]
+    fn test_load_config_with_composite_primary_key() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        let config_content = r#"
+[server]
+port = 4000
+
+[[entity]]
+table = "daily_sales"
+graphql_name = "DailySale"
+primary_key = ["date", "region", "sku"]
+
+[[entity]]
+table = "customers"
+graphql_name = "Customer"
+primary_key = "customer_id"
+"#;
+        temp_file.write_all(config_content.as_bytes()).unwrap();
+        temp_file.flush().unwrap();
+
+        let config = load_config(temp_file.path().to_str().unwrap()).unwrap();
+        assert_eq!(config.entity[0].primary_key.columns(), ["date", "region", "sku"]);
+        assert_eq!(config.entity[1].primary_key, "customer_id");
+
+        // Single-column keys keep serializing as a plain string
+        let saved = toml::to_string(&config).unwrap();
+        assert!(saved.contains(r#"primary_key = ["date", "region", "sku"]"#));
+        assert!(saved.contains(r#"primary_key = "customer_id""#));
+    }
+
+    #[test]
+    fn test_load_config_with_unknown_relation_target() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        let config_content = r#"
+[server]
+port = 4000
+
+[[entity]]
+table = "orders"
+graphql_name = "Order"
+primary_key = "order_id"
+
+[[entity.relation]]
+name = "customer"
+target = "Customer"
+local_column = "customer_id"
+remote_column = "customer_id"
+"#;
+        temp_file.write_all(config_content.as_bytes()).unwrap();
+        temp_file.flush().unwrap();
+        
+        let config = load_config(temp_file.path().to_str().unwrap());
+        assert!(config.is_err());
+    }
 }
 
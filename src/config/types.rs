@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Top-level configuration structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -7,6 +8,9 @@ pub struct Config {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub databricks: Option<DatabricksConfig>,
     pub server: ServerConfig,
+    /// Optional authentication of GraphQL requests (open when omitted)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<AuthConfig>,
     pub entity: Vec<EntityConfig>,
 }
 
@@ -30,6 +34,131 @@ pub struct ServerConfig {
     pub bind: String,
 }
 
+/// Request authentication (`[auth]`): JWT bearer tokens and/or API keys
+///
+/// Tokens are verified against a JWKS file and/or static keys. API keys are
+/// listed as `[[auth.api_key]]` entries or in an `api_key_file`. At least one
+/// key source must be configured.
+///
+/// ```toml
+/// [auth]
+/// jwks_file = "jwks.json"
+/// issuer = "https://login.example.com/"
+/// audience = "nouninator"
+/// api_key_file = "api_keys.toml"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthConfig {
+    /// JSON Web Key Set file with the keys tokens may be signed with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jwks_file: Option<String>,
+
+    /// Environment variable holding the shared secret for HS256/HS384/HS512 tokens
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hmac_secret_env: Option<String>,
+
+    /// PEM file with the RSA public key for RS256/RS384/RS512 and PS256/PS384/PS512 tokens
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rsa_public_key_file: Option<String>,
+
+    /// Required `iss` claim
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+
+    /// Audience tokens must be issued for (`aud` claim)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audience: Option<String>,
+
+    /// Clock skew tolerated when checking `exp` and `nbf`, in seconds
+    /// (defaults to 60)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub leeway_secs: Option<u64>,
+
+    /// Claim holding the caller's roles, as a dotted path (defaults to `roles`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub roles_claim: Option<String>,
+
+    /// TOML file of `[[api_key]]` entries, read when the server starts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key_file: Option<String>,
+
+    /// API keys (`[[auth.api_key]]`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub api_key: Vec<ApiKeyConfig>,
+}
+
+/// An API key (`[[auth.api_key]]`, or `[[api_key]]` in an `api_key_file`)
+///
+/// Only the key's SHA-256 digest is stored (`printf %s "$KEY" | sha256sum`).
+///
+/// ```toml
+/// [[auth.api_key]]
+/// name = "reporting"
+/// sha256 = "9c56cc51b374c3ba189210d5b6d4bf57790d351c96c47c02190ecf1e430635ab"
+/// roles = ["analyst"]
+/// requests_per_minute = 60
+/// rows_per_hour = 100000
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiKeyConfig {
+    /// Name of the key, used as the caller's subject and in logs
+    pub name: String,
+
+    /// SHA-256 digest of the key, in hex
+    pub sha256: String,
+
+    /// Roles granted to requests made with the key
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>,
+
+    /// Requests accepted per minute (unlimited when omitted)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requests_per_minute: Option<u64>,
+
+    /// Entity rows returned per hour (unlimited when omitted)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rows_per_hour: Option<u64>,
+}
+
+/// Default clock skew tolerated on token expiry, in seconds
+pub const DEFAULT_AUTH_LEEWAY_SECS: u64 = 60;
+
+/// Default claim holding the caller's roles
+pub const DEFAULT_ROLES_CLAIM: &str = "roles";
+
+impl AuthConfig {
+    /// Clock skew tolerated when checking `exp` and `nbf`
+    pub fn leeway(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.leeway_secs.unwrap_or(DEFAULT_AUTH_LEEWAY_SECS))
+    }
+
+    /// Claim holding the caller's roles
+    pub fn roles_claim(&self) -> &str {
+        self.roles_claim.as_deref().unwrap_or(DEFAULT_ROLES_CLAIM)
+    }
+
+    /// Whether bearer tokens are accepted (some token key source is configured)
+    pub fn uses_tokens(&self) -> bool {
+        self.jwks_file.is_some() || self.hmac_secret_env.is_some() || self.rsa_public_key_file.is_some()
+    }
+
+    /// Whether API keys are accepted
+    pub fn uses_api_keys(&self) -> bool {
+        self.api_key_file.is_some() || !self.api_key.is_empty()
+    }
+
+    /// Validate auth configuration
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.uses_tokens() && !self.uses_api_keys() {
+            return Err(
+                "[auth] needs at least one of jwks_file, hmac_secret_env, rsa_public_key_file, api_key or api_key_file"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
 fn default_port() -> u16 {
     4000
 }
@@ -39,7 +168,7 @@ fn default_bind() -> String {
 }
 
 /// Entity (table) configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct EntityConfig {
     /// Unity Catalog table path (format: "catalog.schema.table")
     pub table: String,
@@ -47,8 +176,8 @@ pub struct EntityConfig {
     /// GraphQL type name (PascalCase)
     pub graphql_name: String,
     
-    /// Primary key column name
-    pub primary_key: String,
+    /// Primary key column, or list of columns for a composite key
+    pub primary_key: PrimaryKey,
     
     /// Optional description for GraphQL schema
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -58,9 +187,284 @@ pub struct EntityConfig {
     /// If not provided, the system will attempt to determine it from the table name
     #[serde(skip_serializing_if = "Option::is_none")]
     pub storage_location: Option<String>,
+
+    /// Relationships to other entities (`[[entity.relation]]`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub relation: Vec<RelationConfig>,
+
+    /// How often `serve` checks the Delta log for new versions, in seconds
+    /// (defaults to 60; 0 disables refreshing)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_interval_secs: Option<u64>,
+
+    /// Generate create/update/delete/upsert mutations (Delta tables only)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub mutations: bool,
+
+    /// Maximum number of rows accepted by one `bulkInsertX` call
+    /// (defaults to 10000)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_bulk_insert_rows: Option<usize>,
+
+    /// Maximum number of keys accepted by one `X_by_ids` lookup
+    /// (defaults to 1000)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_lookup_ids: Option<usize>,
+
+    /// Roles or scopes allowed to query this entity (any caller when omitted)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow: Option<Vec<String>>,
+
+    /// What columns (and relations to this entity) the caller may not read
+    /// resolve to (defaults to an error)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forbidden_fields: Option<ForbiddenFields>,
+
+    /// Leave what the caller may not read out of introspection results
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hide_forbidden: bool,
+
+    /// SQL predicate every read of the entity is restricted to, e.g.
+    /// `tenant_id = {claims.tenant}`; `{claims.<path>}` takes the value of a
+    /// claim of the caller's token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub row_filter: Option<String>,
+
+    /// Per-column settings (`[entity.columns.<name>]`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub columns: BTreeMap<String, ColumnConfig>,
+}
+
+/// Settings for one column of an entity
+///
+/// ```toml
+/// [entity.columns.ssn]
+/// allow = ["hr", "support"]
+/// mask = "partial"
+/// role_masks = { hr = "none" }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnConfig {
+    /// Roles or scopes allowed to read this column (any caller when omitted)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow: Option<Vec<String>>,
+
+    /// How the column's values are masked (unmasked when omitted)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mask: Option<Mask>,
+
+    /// Masks for callers with a given role or scope, in place of `mask`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub role_masks: BTreeMap<String, Mask>,
+}
+
+impl ColumnConfig {
+    /// Whether any caller may see the column masked
+    pub fn is_masked(&self) -> bool {
+        self.mask.is_some_and(|mask| mask != Mask::None) || self.role_masks.values().any(|mask| *mask != Mask::None)
+    }
+
+    /// Whether the column has a mask other than `null`, which needs string values
+    pub fn masks_text(&self) -> bool {
+        self.mask
+            .iter()
+            .chain(self.role_masks.values())
+            .any(|mask| matches!(mask, Mask::Partial | Mask::Hash | Mask::Redact))
+    }
+}
+
+/// A masking function, from the most to the least revealing
+///
+/// A caller matching several `role_masks` gets the most revealing one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mask {
+    /// The value itself
+    None,
+    /// All but the last few characters replaced by `*`
+    Partial,
+    /// A keyed hash of the value, equal for equal values
+    Hash,
+    /// A fixed `[REDACTED]` placeholder
+    Redact,
+    /// `null` (masked columns become nullable in the schema)
+    Null,
+}
+
+/// How a field the caller may not read resolves
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForbiddenFields {
+    /// A GraphQL error naming the field
+    #[default]
+    Error,
+    /// `null` (restricted columns become nullable in the schema)
+    Null,
+}
+
+/// Primary key of an entity: one column or several
+///
+/// ```toml
+/// primary_key = "order_id"
+/// primary_key = ["date", "region", "sku"]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PrimaryKey {
+    Column(String),
+    Columns(Vec<String>),
+}
+
+impl PrimaryKey {
+    /// Key columns, in declaration order
+    pub fn columns(&self) -> &[String] {
+        match self {
+            PrimaryKey::Column(column) => std::slice::from_ref(column),
+            PrimaryKey::Columns(columns) => columns,
+        }
+    }
+
+    /// Whether the key spans more than one column
+    pub fn is_composite(&self) -> bool {
+        self.columns().len() > 1
+    }
+}
+
+impl Default for PrimaryKey {
+    fn default() -> Self {
+        PrimaryKey::Column(String::new())
+    }
+}
+
+impl From<&str> for PrimaryKey {
+    fn from(column: &str) -> Self {
+        PrimaryKey::Column(column.to_string())
+    }
+}
+
+impl From<String> for PrimaryKey {
+    fn from(column: String) -> Self {
+        PrimaryKey::Column(column)
+    }
+}
+
+impl From<Vec<String>> for PrimaryKey {
+    fn from(columns: Vec<String>) -> Self {
+        match <[String; 1]>::try_from(columns) {
+            Ok([column]) => PrimaryKey::Column(column),
+            Err(columns) => PrimaryKey::Columns(columns),
+        }
+    }
+}
+
+impl PartialEq<&str> for PrimaryKey {
+    fn eq(&self, other: &&str) -> bool {
+        matches!(self.columns(), [column] if column == other)
+    }
+}
+
+impl std::fmt::Display for PrimaryKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrimaryKey::Column(column) => f.write_str(column),
+            PrimaryKey::Columns(columns) => write!(f, "({})", columns.join(", ")),
+        }
+    }
+}
+
+/// Default interval between Delta table refreshes
+pub const DEFAULT_REFRESH_INTERVAL_SECS: u64 = 60;
+
+/// Default maximum number of rows per bulk insert
+pub const DEFAULT_MAX_BULK_INSERT_ROWS: usize = 10_000;
+
+/// Default maximum number of keys per batch lookup
+pub const DEFAULT_MAX_LOOKUP_IDS: usize = 1_000;
+
+/// Foreign-key relationship from one entity to another
+///
+/// ```toml
+/// [[entity.relation]]
+/// name = "customer"
+/// target = "Customer"
+/// local_column = "customer_id"
+/// remote_column = "customer_id"
+/// kind = "one"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelationConfig {
+    /// Field name on this entity's GraphQL type
+    pub name: String,
+
+    /// GraphQL name of the related entity
+    pub target: String,
+
+    /// Column on this entity holding the key
+    pub local_column: String,
+
+    /// Column on the target entity matched against `local_column`
+    pub remote_column: String,
+
+    /// Whether the field returns a single object or a list
+    #[serde(default)]
+    pub kind: RelationKind,
+}
+
+/// Cardinality of a relationship
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RelationKind {
+    /// At most one related row (e.g. `order { customer }`)
+    #[default]
+    One,
+    /// Any number of related rows (e.g. `customer { orders }`)
+    Many,
 }
 
 impl EntityConfig {
+    /// Interval between refreshes of the served Delta table, if enabled
+    pub fn refresh_interval(&self) -> Option<std::time::Duration> {
+        match self.refresh_interval_secs.unwrap_or(DEFAULT_REFRESH_INTERVAL_SECS) {
+            0 => None,
+            secs => Some(std::time::Duration::from_secs(secs)),
+        }
+    }
+
+    /// Maximum number of rows accepted by one bulk insert
+    pub fn max_bulk_insert_rows(&self) -> usize {
+        self.max_bulk_insert_rows.unwrap_or(DEFAULT_MAX_BULK_INSERT_ROWS)
+    }
+
+    /// Maximum number of keys accepted by one batch lookup
+    pub fn max_lookup_ids(&self) -> usize {
+        self.max_lookup_ids.unwrap_or(DEFAULT_MAX_LOOKUP_IDS)
+    }
+
+    /// Roles or scopes allowed to read a column, if it is restricted
+    pub fn column_allow(&self, column: &str) -> Option<&[String]> {
+        self.columns.get(column)?.allow.as_deref()
+    }
+
+    /// How fields the caller may not read resolve
+    pub fn forbidden_fields(&self) -> ForbiddenFields {
+        self.forbidden_fields.unwrap_or_default()
+    }
+
+    /// Whether the entity or any of its columns has an `allow` rule
+    pub fn has_allow_rules(&self) -> bool {
+        self.allow.is_some() || self.columns.values().any(|column| column.allow.is_some())
+    }
+
+    /// Whether any of the entity's columns is masked per role or scope
+    pub fn has_role_masks(&self) -> bool {
+        self.columns.values().any(|column| !column.role_masks.is_empty())
+    }
+
+    /// Whether the entity's row filter reads token claims
+    pub fn row_filter_uses_claims(&self) -> bool {
+        self.row_filter.as_deref().is_some_and(|filter| filter.contains("{claims."))
+    }
+
     /// Validate entity configuration
     pub fn validate(&self) -> Result<(), String> {
         // Validate table format - allow either:
@@ -88,6 +492,48 @@ impl EntityConfig {
                 self.graphql_name
             ));
         }
+
+        // Validate primary key columns
+        let key_columns = self.primary_key.columns();
+        if key_columns.is_empty() || key_columns.iter().any(|c| c.is_empty()) {
+            return Err(format!(
+                "Primary key of '{}' must name at least one column",
+                self.graphql_name
+            ));
+        }
+        for (idx, column) in key_columns.iter().enumerate() {
+            if key_columns[..idx].contains(column) {
+                return Err(format!(
+                    "Primary key of '{}' lists column '{}' more than once",
+                    self.graphql_name, column
+                ));
+            }
+            // Rows are addressed by their key, so it cannot be withheld
+            if self.column_allow(column).is_some() {
+                return Err(format!(
+                    "Primary key column '{}' of '{}' cannot have an allow rule",
+                    column, self.graphql_name
+                ));
+            }
+            if self.columns.get(column).is_some_and(ColumnConfig::is_masked) {
+                return Err(format!(
+                    "Primary key column '{}' of '{}' cannot be masked",
+                    column, self.graphql_name
+                ));
+            }
+        }
+
+        // Validate relation field names (GraphQL identifiers)
+        for relation in &self.relation {
+            let valid = relation.name.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_')
+                && relation.name.chars().all(|c| c.is_alphanumeric() || c == '_');
+            if !valid {
+                return Err(format!(
+                    "Relation name '{}' must be a valid GraphQL field name",
+                    relation.name
+                ));
+            }
+        }
         
         Ok(())
     }
@@ -102,9 +548,10 @@ mod tests {
         let entity = EntityConfig {
             table: "main.sales.customers".to_string(),
             graphql_name: "Customer".to_string(),
-            primary_key: "customer_id".to_string(),
+            primary_key: "customer_id".into(),
             description: None,
             storage_location: None,
+            ..Default::default()
         };
         
         assert!(entity.validate().is_ok());
@@ -116,9 +563,10 @@ mod tests {
         let entity = EntityConfig {
             table: "schema.table".to_string(),
             graphql_name: "Customer".to_string(),
-            primary_key: "id".to_string(),
+            primary_key: "id".into(),
             description: None,
             storage_location: None,
+            ..Default::default()
         };
         
         assert!(entity.validate().is_err());
@@ -130,9 +578,10 @@ mod tests {
         let entity = EntityConfig {
             table: "customers".to_string(),
             graphql_name: "Customer".to_string(),
-            primary_key: "id".to_string(),
+            primary_key: "id".into(),
             description: None,
             storage_location: None,
+            ..Default::default()
         };
         
         assert!(entity.validate().is_ok());
@@ -143,25 +592,115 @@ mod tests {
         let entity = EntityConfig {
             table: "main.sales.customers".to_string(),
             graphql_name: "customer".to_string(), // Should be PascalCase
-            primary_key: "id".to_string(),
+            primary_key: "id".into(),
             description: None,
             storage_location: None,
+            ..Default::default()
         };
         
         assert!(entity.validate().is_err());
     }
 
+    #[test]
+    fn test_entity_validation_invalid_relation_name() {
+        let entity = EntityConfig {
+            table: "main.sales.orders".to_string(),
+            graphql_name: "Order".to_string(),
+            primary_key: "order_id".into(),
+            relation: vec![RelationConfig {
+                name: "the customer".to_string(),
+                target: "Customer".to_string(),
+                local_column: "customer_id".to_string(),
+                remote_column: "customer_id".to_string(),
+                kind: RelationKind::One,
+            }],
+            ..Default::default()
+        };
+
+        assert!(entity.validate().is_err());
+    }
+
     #[test]
     fn test_entity_validation_non_alphanumeric_graphql_name() {
         let entity = EntityConfig {
             table: "main.sales.customers".to_string(),
             graphql_name: "Customer-Type".to_string(),
-            primary_key: "id".to_string(),
+            primary_key: "id".into(),
             description: None,
             storage_location: None,
+            ..Default::default()
         };
         
         assert!(entity.validate().is_err());
     }
+
+    #[test]
+    fn test_entity_validation_composite_primary_key() {
+        let mut entity = EntityConfig {
+            table: "main.sales.daily_sales".to_string(),
+            graphql_name: "DailySale".to_string(),
+            primary_key: PrimaryKey::Columns(vec!["date".to_string(), "region".to_string(), "sku".to_string()]),
+            ..Default::default()
+        };
+        assert!(entity.validate().is_ok());
+        assert!(entity.primary_key.is_composite());
+        assert_eq!(entity.primary_key.to_string(), "(date, region, sku)");
+
+        entity.primary_key = PrimaryKey::Columns(vec!["date".to_string(), "date".to_string()]);
+        assert!(entity.validate().is_err());
+
+        entity.primary_key = PrimaryKey::Columns(Vec::new());
+        assert!(entity.validate().is_err());
+    }
+
+    #[test]
+    fn test_primary_key_columns_cannot_be_restricted_or_masked() {
+        // Connection cursors carry primary key values, so nobody may be denied them
+        let key_column = |config: ColumnConfig| EntityConfig {
+            table: "customers".to_string(),
+            graphql_name: "Customer".to_string(),
+            primary_key: "customer_id".into(),
+            columns: [("customer_id".to_string(), config)].into(),
+            ..Default::default()
+        };
+
+        let err = key_column(ColumnConfig {
+            allow: Some(vec!["admin".to_string()]),
+            ..Default::default()
+        })
+        .validate()
+        .unwrap_err();
+        assert!(err.contains("cannot have an allow rule"), "{}", err);
+
+        let err = key_column(ColumnConfig {
+            role_masks: [("support".to_string(), Mask::Hash)].into(),
+            ..Default::default()
+        })
+        .validate()
+        .unwrap_err();
+        assert!(err.contains("cannot be masked"), "{}", err);
+
+        assert!(key_column(ColumnConfig {
+            mask: Some(Mask::None),
+            ..Default::default()
+        })
+        .validate()
+        .is_ok());
+    }
+
+    #[test]
+    fn test_refresh_interval() {
+        let mut entity = EntityConfig::default();
+        assert_eq!(
+            entity.refresh_interval(),
+            Some(std::time::Duration::from_secs(DEFAULT_REFRESH_INTERVAL_SECS))
+        );
+
+        entity.refresh_interval_secs = Some(5);
+        assert_eq!(entity.refresh_interval(), Some(std::time::Duration::from_secs(5)));
+
+        entity.refresh_interval_secs = Some(0);
+        assert_eq!(entity.refresh_interval(), None);
+    }
 }
 
@@ -0,0 +1,568 @@
+//! Entity- and column-level authorization
+//!
+//! `allow` rules on an entity (or on one of its `[entity.columns.<name>]`)
+//! list the roles and scopes of the request's [`Principal`] that may read it.
+//! Requests without a principal satisfy no rule.
+//!
+//! - Root fields of an entity the caller may not query fail, and relations to
+//!   it fail or resolve to `null`/`[]` (per `forbidden_fields`).
+//! - Restricted columns resolve to an error or to `null`. With
+//!   `forbidden_fields = "null"` their GraphQL types are made nullable.
+//! - Restricted columns may not appear in `filter`, `orderBy` or `groupBy`
+//!   arguments, where their values could be probed. Neither may columns the
+//!   caller sees masked (see [`masking`](super::masking)), nor may they be
+//!   part of the sort key that connection cursors encode. Relations joining
+//!   on them fail or resolve to `null`/`[]` like restricted columns.
+//! - Restricted columns may not be set by mutation inputs and patches.
+//!
+//! Entities with `hide_forbidden = true` are also removed from introspection
+//! by [`IntrospectionFilter`], along with the columns the caller may not read.
+
+use crate::auth::Principal;
+use crate::config::{EntityConfig, ForbiddenFields, Mask};
+use crate::schema::masking::{mask_for, masks_to_null, resolve_masked};
+use crate::schema::resolver::resolve_from_parent;
+
+use async_graphql::dynamic::{Field, FieldFuture, ResolverContext, TypeRef};
+use async_graphql::extensions::{
+    Extension, ExtensionContext, ExtensionFactory, NextExecute, NextParseQuery,
+};
+use async_graphql::parser::types::{ExecutableDocument, Selection, SelectionSet};
+use async_graphql::registry::{MetaTypeName, Registry};
+use async_graphql::{Context, Name, Response, ServerResult, Value, Variables};
+use async_trait::async_trait;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, Mutex};
+
+fn is_allowed(principal: Option<&Principal>, allow: Option<&[String]>) -> bool {
+    match allow {
+        None => true,
+        Some(allow) => principal.is_some_and(|principal| principal.is_allowed(allow)),
+    }
+}
+
+/// Whether the caller may query an entity
+pub fn can_query(ctx: &Context<'_>, entity: &EntityConfig) -> bool {
+    is_allowed(ctx.data_opt::<Principal>(), entity.allow.as_deref())
+}
+
+/// Whether the caller may read a column of an entity
+pub fn can_read_column(ctx: &Context<'_>, entity: &EntityConfig, column: &str) -> bool {
+    is_allowed(ctx.data_opt::<Principal>(), entity.column_allow(column))
+}
+
+/// Fail unless the caller may query an entity with the field's arguments
+///
+/// Checks the entity's `allow` rule and that `filter`, `orderBy` and
+/// `groupBy` only name columns the caller may read unmasked.
+pub fn authorize_query(ctx: &ResolverContext<'_>, entity: &EntityConfig) -> async_graphql::Result<()> {
+    if !can_query(ctx, entity) {
+        return Err(format!("Not authorized to query {}", entity.graphql_name).into());
+    }
+    if entity.columns.is_empty() {
+        return Ok(());
+    }
+
+    let mut columns = Vec::new();
+    if let Some(filter) = ctx.args.get("filter") {
+        filter_columns(filter.as_value(), &mut columns);
+    }
+    if let Some(order_by) = ctx.args.get("orderBy") {
+        if let Value::List(items) = order_by.as_value() {
+            columns.extend(items.iter().filter_map(|item| match item {
+                Value::Object(obj) => obj.get("field").and_then(column_name),
+                _ => None,
+            }));
+        }
+    }
+    if let Some(group_by) = ctx.args.get("groupBy") {
+        if let Value::List(items) = group_by.as_value() {
+            columns.extend(items.iter().filter_map(column_name));
+        }
+    }
+
+    authorize_columns(ctx, entity, columns)
+}
+
+/// Fail unless the caller may read every one of `columns` unmasked
+///
+/// Used for arguments that compare against column values, and for the sort
+/// key a connection's cursors carry.
+pub fn authorize_columns(
+    ctx: &ResolverContext<'_>,
+    entity: &EntityConfig,
+    columns: impl IntoIterator<Item = impl AsRef<str>>,
+) -> async_graphql::Result<()> {
+    for column in columns {
+        let column = column.as_ref();
+        if !can_read_column(ctx, entity, column) {
+            return Err(format!("Not authorized to use {}.{} in arguments", entity.graphql_name, column).into());
+        }
+        let masked = entity
+            .columns
+            .get(column)
+            .is_some_and(|config| mask_for(config, ctx.data_opt::<Principal>()) != Mask::None);
+        if masked {
+            return Err(format!("Masked column {}.{} cannot be used in arguments", entity.graphql_name, column).into());
+        }
+    }
+    Ok(())
+}
+
+/// Fail unless the caller may read every column a mutation sets
+///
+/// `columns` are the fields named by an `XInput` or `XPatch` value; a
+/// column's `allow` rule governs writing it as well as reading it.
+pub fn authorize_write<'a>(
+    ctx: &ResolverContext<'_>,
+    entity: &EntityConfig,
+    columns: impl IntoIterator<Item = &'a Name>,
+) -> async_graphql::Result<()> {
+    for column in columns {
+        if !can_read_column(ctx, entity, column) {
+            return Err(format!("Not authorized to write {}.{}", entity.graphql_name, column).into());
+        }
+    }
+    Ok(())
+}
+
+/// The columns set by mutation input objects
+pub fn input_columns(rows: &[Value]) -> impl Iterator<Item = &Name> {
+    rows.iter().flat_map(|row| match row {
+        Value::Object(obj) => Some(obj.keys()),
+        _ => None,
+    })
+    .flatten()
+}
+
+/// A column named by an `XColumn` enum value
+fn column_name(value: &Value) -> Option<String> {
+    match value {
+        Value::Enum(name) => Some(name.to_string()),
+        Value::String(name) => Some(name.clone()),
+        _ => None,
+    }
+}
+
+/// Collect the columns an `XFilter` value tests, through `and`/`or`/`not`
+fn filter_columns(filter: &Value, columns: &mut Vec<String>) {
+    let Value::Object(obj) = filter else {
+        return;
+    };
+    for (key, value) in obj {
+        match (key.as_str(), value) {
+            ("and" | "or", Value::List(items)) => {
+                for item in items {
+                    filter_columns(item, columns);
+                }
+            }
+            ("not", value) => filter_columns(value, columns),
+            (column, _) => columns.push(column.to_string()),
+        }
+    }
+}
+
+/// Whether a column's GraphQL type must be nullable
+///
+/// Restricted columns resolve to `null` for callers who may not read them
+/// when the entity sets `forbidden_fields = "null"`, and so do columns some
+/// callers see through a `null` mask.
+pub fn column_nullable(entity: &EntityConfig, column: &str, nullable: bool) -> bool {
+    nullable
+        || (entity.column_allow(column).is_some() && entity.forbidden_fields() == ForbiddenFields::Null)
+        || entity.columns.get(column).is_some_and(masks_to_null)
+}
+
+/// Build the field of a column on one of an entity's types
+///
+/// Plain columns resolve straight from the parent row; restricted ones check
+/// the caller first and masked ones are masked for it.
+pub fn column_field(entity: &EntityConfig, column: &str, ty: TypeRef) -> Field {
+    let name = column.to_string();
+    let Some(config) = entity
+        .columns
+        .get(column)
+        .filter(|config| config.allow.is_some() || config.is_masked())
+    else {
+        return Field::new(column, ty, move |ctx| {
+            let name = name.clone();
+            FieldFuture::new(async move { resolve_from_parent(&ctx, &name) })
+        });
+    };
+
+    let config = Arc::new(config.clone());
+    let forbidden = entity.forbidden_fields();
+    let message = format!("Not authorized to read {}.{}", entity.graphql_name, column);
+    Field::new(column, ty, move |ctx| {
+        let name = name.clone();
+        let config = config.clone();
+        let message = message.clone();
+        FieldFuture::new(async move {
+            if is_allowed(ctx.data_opt::<Principal>(), config.allow.as_deref()) {
+                return resolve_masked(&ctx, &name, &config);
+            }
+            match forbidden {
+                ForbiddenFields::Error => Err(message.into()),
+                ForbiddenFields::Null => Ok(None),
+            }
+        })
+    })
+}
+
+/// What an entity's hidden types and columns are, for [`IntrospectionFilter`]
+#[derive(Debug, Clone, Default)]
+pub struct HiddenEntity {
+    /// Entity `allow` rule
+    pub allow: Option<Vec<String>>,
+    /// Column `allow` rules
+    pub columns: BTreeMap<String, Vec<String>>,
+    /// Every type generated for the entity (object, input and enum types)
+    pub types: Vec<String>,
+}
+
+impl HiddenEntity {
+    pub fn new(entity: &EntityConfig, types: Vec<String>) -> Self {
+        Self {
+            allow: entity.allow.clone(),
+            columns: entity
+                .columns
+                .iter()
+                .filter_map(|(column, config)| Some((column.clone(), config.allow.clone()?)))
+                .collect(),
+            types,
+        }
+    }
+}
+
+/// What one caller may not see
+#[derive(Debug, Default)]
+struct Hidden {
+    types: BTreeSet<String>,
+    /// Member names (fields, input fields, enum values) hidden per type
+    members: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Hidden {
+    fn for_principal(entities: &[HiddenEntity], principal: Option<&Principal>) -> Self {
+        let mut hidden = Self::default();
+        for entity in entities {
+            if !is_allowed(principal, entity.allow.as_deref()) {
+                hidden.types.extend(entity.types.iter().cloned());
+                continue;
+            }
+            let columns: BTreeSet<String> = entity
+                .columns
+                .iter()
+                .filter(|(_, allow)| !is_allowed(principal, Some(allow)))
+                .map(|(column, _)| column.clone())
+                .collect();
+            if !columns.is_empty() {
+                for type_name in &entity.types {
+                    hidden.members.insert(type_name.clone(), columns.clone());
+                }
+            }
+        }
+        hidden
+    }
+
+    /// Also hide every field typed with a hidden type (root fields, relations)
+    ///
+    /// Read from the schema, so they are left out even when the query does
+    /// not select their `type`.
+    fn hide_fields_of_hidden_types(&mut self, registry: &Registry) {
+        if self.types.is_empty() {
+            return;
+        }
+        for (type_name, ty) in &registry.types {
+            for field in ty.fields().into_iter().flat_map(|fields| fields.values()) {
+                if self.types.contains(MetaTypeName::concrete_typename(&field.ty)) {
+                    self.members
+                        .entry(type_name.clone())
+                        .or_default()
+                        .insert(field.name.clone());
+                }
+            }
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.types.is_empty() && self.members.is_empty()
+    }
+
+    /// Filter the result of a root `__schema` or `__type` field
+    fn filter_root(&self, value: &mut Value, is_type: bool) {
+        if is_type {
+            // Without its name the type cannot be told apart from a hidden one
+            let hide = match &*value {
+                Value::Object(obj) => match string(obj.get("name")) {
+                    Some(name) => self.types.contains(name),
+                    None => !self.types.is_empty(),
+                },
+                _ => false,
+            };
+            if hide {
+                *value = Value::Null;
+            }
+        } else if let Value::Object(obj) = value {
+            if let Some(Value::List(types)) = obj.get_mut("types") {
+                types.retain(|ty| !self.hides_type(ty));
+            }
+        }
+        self.filter_members(value);
+    }
+
+    fn hides_type(&self, ty: &Value) -> bool {
+        match ty {
+            Value::Object(obj) => string(obj.get("name")).is_some_and(|name| self.types.contains(name)),
+            _ => false,
+        }
+    }
+
+    /// Remove hidden members from every `__Type` in an introspection result
+    fn filter_members(&self, value: &mut Value) {
+        match value {
+            Value::List(items) => items.iter_mut().for_each(|item| self.filter_members(item)),
+            Value::Object(obj) => {
+                let type_name = string(obj.get("name")).map(str::to_string);
+                for key in ["fields", "inputFields", "enumValues", "args"] {
+                    if let Some(Value::List(members)) = obj.get_mut(key) {
+                        members.retain(|member| !self.hides_member(type_name.as_deref(), member));
+                    }
+                }
+                obj.values_mut().for_each(|value| self.filter_members(value));
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether a field, input field, argument or enum value of `type_name` is hidden
+    ///
+    /// Members of an unnamed type (`name` not selected) are checked against
+    /// the hidden members of every type.
+    fn hides_member(&self, type_name: Option<&str>, member: &Value) -> bool {
+        let Value::Object(obj) = member else {
+            return false;
+        };
+        if let Some(name) = string(obj.get("name")) {
+            let hidden = match type_name {
+                Some(type_name) => self.members.get(type_name).is_some_and(|names| names.contains(name)),
+                None => self.members.values().any(|names| names.contains(name)),
+            };
+            if hidden {
+                return true;
+            }
+        }
+        // Fields and arguments typed with a hidden type (through lists and non-null wrappers)
+        let mut ty = obj.get("type");
+        while let Some(Value::Object(type_ref)) = ty {
+            if string(type_ref.get("name")).is_some_and(|name| self.types.contains(name)) {
+                return true;
+            }
+            ty = type_ref.get("ofType");
+        }
+        false
+    }
+}
+
+fn string(value: Option<&Value>) -> Option<&str> {
+    match value {
+        Some(Value::String(s)) => Some(s),
+        _ => None,
+    }
+}
+
+/// Removes what a caller may not read from introspection results
+///
+/// Registered by `SchemaBuilder` when an entity sets `hide_forbidden`. The
+/// root `__schema` and `__type` fields of each query are noted while it is
+/// parsed, and their results are rewritten once it has executed: hidden
+/// entities' types disappear (along with fields and arguments of those types)
+/// and restricted columns are dropped from the entity's types.
+pub struct IntrospectionFilter {
+    entities: Arc<[HiddenEntity]>,
+}
+
+impl IntrospectionFilter {
+    pub fn new(entities: Vec<HiddenEntity>) -> Self {
+        Self {
+            entities: entities.into(),
+        }
+    }
+}
+
+impl ExtensionFactory for IntrospectionFilter {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(IntrospectionFilterExtension {
+            entities: self.entities.clone(),
+            roots: Mutex::new(Vec::new()),
+        })
+    }
+}
+
+struct IntrospectionFilterExtension {
+    entities: Arc<[HiddenEntity]>,
+    /// Response keys of the query's root `__schema` (false) and `__type` (true) fields
+    roots: Mutex<Vec<(Name, bool)>>,
+}
+
+#[async_trait]
+impl Extension for IntrospectionFilterExtension {
+    async fn parse_query(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        query: &str,
+        variables: &Variables,
+        next: NextParseQuery<'_>,
+    ) -> ServerResult<ExecutableDocument> {
+        let document = next.run(ctx, query, variables).await?;
+        let mut roots = Vec::new();
+        for (_, operation) in document.operations.iter() {
+            introspection_roots(&document, &operation.node.selection_set.node, &mut roots);
+        }
+        *self.roots.lock().unwrap() = roots;
+        Ok(document)
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        operation_name: Option<&str>,
+        next: NextExecute<'_>,
+    ) -> Response {
+        let mut response = next.run(ctx, operation_name).await;
+        let roots = std::mem::take(&mut *self.roots.lock().unwrap());
+        if roots.is_empty() {
+            return response;
+        }
+
+        let mut hidden = Hidden::for_principal(&self.entities, ctx.data_opt::<Principal>());
+        if hidden.is_empty() {
+            return response;
+        }
+        hidden.hide_fields_of_hidden_types(&ctx.schema_env.registry);
+        if let Value::Object(data) = &mut response.data {
+            for (key, is_type) in roots {
+                if let Some(value) = data.get_mut(&key) {
+                    hidden.filter_root(value, is_type);
+                }
+            }
+        }
+        response
+    }
+}
+
+/// Collect the response keys of root introspection fields, through fragments
+fn introspection_roots(document: &ExecutableDocument, selection_set: &SelectionSet, roots: &mut Vec<(Name, bool)>) {
+    for selection in &selection_set.items {
+        match &selection.node {
+            Selection::Field(field) => {
+                let field = &field.node;
+                let is_type = match field.name.node.as_str() {
+                    "__schema" => false,
+                    "__type" => true,
+                    _ => continue,
+                };
+                roots.push((field.response_key().node.clone(), is_type));
+            }
+            Selection::FragmentSpread(spread) => {
+                if let Some(fragment) = document.fragments.get(&spread.node.fragment_name.node) {
+                    introspection_roots(document, &fragment.node.selection_set.node, roots);
+                }
+            }
+            Selection::InlineFragment(fragment) => {
+                introspection_roots(document, &fragment.node.selection_set.node, roots);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ColumnConfig;
+
+    fn value(json: serde_json::Value) -> Value {
+        Value::from_json(json).unwrap()
+    }
+
+    fn hidden_entities() -> Vec<HiddenEntity> {
+        let employee = EntityConfig {
+            graphql_name: "Employee".to_string(),
+            columns: [(
+                "salary".to_string(),
+                ColumnConfig {
+                    allow: Some(vec!["hr".to_string()]),
+                    ..Default::default()
+                },
+            )]
+            .into(),
+            ..Default::default()
+        };
+        let audit = EntityConfig {
+            graphql_name: "Audit".to_string(),
+            allow: Some(vec!["admin".to_string()]),
+            ..Default::default()
+        };
+        vec![
+            HiddenEntity::new(&employee, vec!["Employee".to_string(), "EmployeeColumn".to_string()]),
+            HiddenEntity::new(&audit, vec!["Audit".to_string(), "AuditFilter".to_string()]),
+        ]
+    }
+
+    #[test]
+    fn test_filter_columns() {
+        let filter = value(serde_json::json!({
+            "name": {"eq": "a"},
+            "or": [{"salary": {"gt": 1}}, {"not": {"ssn": {"eq": "x"}}}],
+        }));
+        let mut columns = Vec::new();
+        filter_columns(&filter, &mut columns);
+        assert_eq!(columns, vec!["name", "salary", "ssn"]);
+    }
+
+    #[test]
+    fn test_introspection_is_filtered_per_principal() {
+        let schema = value(serde_json::json!({
+            "types": [
+                {"name": "Employee", "fields": [{"name": "id"}, {"name": "salary"}]},
+                {"name": "EmployeeColumn", "enumValues": [{"name": "id"}, {"name": "salary"}]},
+                {"name": "Audit", "fields": [{"name": "id"}]},
+                {"name": "Query", "fields": [
+                    {"name": "employee", "type": {"name": "Employee", "ofType": null}},
+                    {"name": "list_audit", "type": {"name": null, "ofType": {"name": null, "ofType": {"name": "Audit"}}}},
+                ]},
+            ]
+        }));
+
+        let mut anonymous = schema.clone();
+        Hidden::for_principal(&hidden_entities(), None).filter_root(&mut anonymous, false);
+        assert_eq!(
+            anonymous,
+            value(serde_json::json!({
+                "types": [
+                    {"name": "Employee", "fields": [{"name": "id"}]},
+                    {"name": "EmployeeColumn", "enumValues": [{"name": "id"}]},
+                    {"name": "Query", "fields": [
+                        {"name": "employee", "type": {"name": "Employee", "ofType": null}},
+                    ]},
+                ]
+            }))
+        );
+
+        let admin = Principal {
+            roles: ["admin".to_string(), "hr".to_string()].into(),
+            ..Default::default()
+        };
+        let hidden = Hidden::for_principal(&hidden_entities(), Some(&admin));
+        assert!(hidden.is_empty());
+
+        let mut audit = value(serde_json::json!({"name": "Audit", "fields": [{"name": "id"}]}));
+        Hidden::for_principal(&hidden_entities(), None).filter_root(&mut audit, true);
+        assert_eq!(audit, Value::Null);
+
+        // A type whose name was not selected cannot be shown
+        let mut unnamed = value(serde_json::json!({"fields": [{"name": "id"}]}));
+        Hidden::for_principal(&hidden_entities(), None).filter_root(&mut unnamed, true);
+        assert_eq!(unnamed, Value::Null);
+    }
+}
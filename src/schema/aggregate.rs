@@ -0,0 +1,351 @@
+//! Aggregation queries
+//!
+//! This module generates the `XAggregate` result type for an entity's
+//! `X_aggregate(filter, groupBy)` field: a `count` plus `sum`/`avg` over numeric
+//! columns and `min`/`max` over numeric and temporal columns, optionally split
+//! into groups. Aggregates are computed by DataFusion; only the aggregate
+//! functions selected in the query are evaluated.
+
+use crate::config::EntityConfig;
+use crate::error::{NouninatorError, Result};
+use crate::schema::access::column_field;
+use crate::schema::resolver::parent_field;
+use crate::schema::scalars::{BIG_INT, DECIMAL, LOCAL_DATE_TIME, TIME};
+use crate::schema::type_mapping::arrow_to_graphql_type;
+
+use async_graphql::dynamic::{Object, TypeRef};
+use async_graphql::{Name, Value};
+use datafusion::arrow::datatypes::Schema as ArrowSchema;
+use datafusion::functions_aggregate::expr_fn::{avg, count, max, min, sum};
+use datafusion::prelude::{ident, lit, Expr};
+use indexmap::IndexMap;
+
+/// Aggregate functions applied per column
+pub const COLUMN_FUNCTIONS: [AggregateFunction; 4] = [
+    AggregateFunction::Sum,
+    AggregateFunction::Avg,
+    AggregateFunction::Min,
+    AggregateFunction::Max,
+];
+
+/// An aggregate function over a single column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregateFunction {
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl AggregateFunction {
+    /// Field name on the aggregate result type
+    pub fn field_name(self) -> &'static str {
+        match self {
+            AggregateFunction::Sum => "sum",
+            AggregateFunction::Avg => "avg",
+            AggregateFunction::Min => "min",
+            AggregateFunction::Max => "max",
+        }
+    }
+
+    fn type_suffix(self) -> &'static str {
+        match self {
+            AggregateFunction::Sum => "SumFields",
+            AggregateFunction::Avg => "AvgFields",
+            AggregateFunction::Min => "MinFields",
+            AggregateFunction::Max => "MaxFields",
+        }
+    }
+
+    /// Build the DataFusion aggregate expression for a column
+    pub fn to_expr(self, column: &str) -> Expr {
+        let column_expr = ident(column);
+        match self {
+            AggregateFunction::Sum => sum(column_expr),
+            AggregateFunction::Avg => avg(column_expr),
+            AggregateFunction::Min => min(column_expr),
+            AggregateFunction::Max => max(column_expr),
+        }
+        .alias(self.alias(column))
+    }
+
+    /// Output column name of the aggregate for `column`
+    pub fn alias(self, column: &str) -> String {
+        format!("{}__{}", self.field_name(), column)
+    }
+
+    /// GraphQL type of the aggregate for a column of the given scalar type
+    ///
+    /// Returns `None` if the function does not apply to the column.
+    fn output_type(self, scalar: &str) -> Option<&'static str> {
+        const ORDERED: [&str; 8] = [
+            TypeRef::INT,
+            BIG_INT,
+            TypeRef::FLOAT,
+            DECIMAL,
+            "Date",
+            "DateTime",
+            LOCAL_DATE_TIME,
+            TIME,
+        ];
+        let numeric = matches!(scalar, TypeRef::INT | BIG_INT | TypeRef::FLOAT | DECIMAL);
+
+        match self {
+            // Integer sums are 64-bit, decimal sums and averages stay decimal
+            AggregateFunction::Sum if matches!(scalar, TypeRef::INT | BIG_INT) => Some(BIG_INT),
+            AggregateFunction::Sum | AggregateFunction::Avg if scalar == DECIMAL => Some(DECIMAL),
+            AggregateFunction::Sum | AggregateFunction::Avg if numeric => Some(TypeRef::FLOAT),
+            AggregateFunction::Min | AggregateFunction::Max => {
+                ORDERED.into_iter().find(|ordered| *ordered == scalar)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Name of the generated aggregate result type for an entity (e.g. `CustomerAggregate`)
+pub fn aggregate_type_name(graphql_name: &str) -> String {
+    format!("{}Aggregate", graphql_name)
+}
+
+/// Name of the generated group key type for an entity (e.g. `CustomerAggregateGroup`)
+pub fn aggregate_group_type_name(graphql_name: &str) -> String {
+    format!("{}AggregateGroup", graphql_name)
+}
+
+fn function_type_name(graphql_name: &str, function: AggregateFunction) -> String {
+    format!("{}Aggregate{}", graphql_name, function.type_suffix())
+}
+
+/// Columns a function applies to, with the GraphQL type of the result
+pub fn aggregate_columns(
+    function: AggregateFunction,
+    arrow_schema: &ArrowSchema,
+) -> Vec<(String, &'static str)> {
+    arrow_schema
+        .fields()
+        .iter()
+        .filter_map(|field| {
+            let type_ref = arrow_to_graphql_type(field.name(), field.data_type(), true)?;
+            let output = function.output_type(type_ref.type_name())?;
+            Some((field.name().to_string(), output))
+        })
+        .collect()
+}
+
+/// Build the aggregate result types for an entity
+///
+/// Produces `XAggregate` (with `group`, `count` and one field per applicable
+/// function), `XAggregateGroup`, and `XAggregateSumFields` etc. for functions
+/// that apply to at least one column. Column fields honor the entity's column
+/// `allow` rules.
+pub fn build_aggregate_types(entity: &EntityConfig, arrow_schema: &ArrowSchema) -> Vec<Object> {
+    let graphql_name = entity.graphql_name.as_str();
+    let mut objects = Vec::new();
+
+    // Group key: every groupable column, populated only for the grouped ones
+    let mut group = Object::new(aggregate_group_type_name(graphql_name))
+        .description(format!("Group key of a {} aggregate", graphql_name));
+    for field in arrow_schema.fields() {
+        if let Some(type_ref) = arrow_to_graphql_type(field.name(), field.data_type(), true) {
+            group = group.field(column_field(entity, field.name(), type_ref));
+        }
+    }
+    objects.push(group);
+
+    let mut aggregate = Object::new(aggregate_type_name(graphql_name))
+        .description(format!("Aggregates over {}", graphql_name))
+        .field(parent_field(
+            "group",
+            TypeRef::named(aggregate_group_type_name(graphql_name)),
+        ))
+        .field(parent_field("count", TypeRef::named_nn(BIG_INT)));
+
+    for function in COLUMN_FUNCTIONS {
+        let columns = aggregate_columns(function, arrow_schema);
+        if columns.is_empty() {
+            continue;
+        }
+
+        let type_name = function_type_name(graphql_name, function);
+        let mut object = Object::new(&type_name);
+        for (column, output) in columns {
+            object = object.field(column_field(entity, &column, TypeRef::named(output)));
+        }
+        objects.push(object);
+
+        aggregate = aggregate.field(parent_field(
+            function.field_name(),
+            TypeRef::named_nn(type_name),
+        ));
+    }
+
+    objects.push(aggregate);
+    objects
+}
+
+/// The `count(*)` aggregate expression
+pub fn count_expr() -> Expr {
+    count(lit(1)).alias("count")
+}
+
+/// Resolve the `groupBy` argument into column names
+pub fn parse_group_by(group_by: Option<&Value>, schema: &ArrowSchema) -> Result<Vec<String>> {
+    let Some(Value::List(items)) = group_by else {
+        return Ok(Vec::new());
+    };
+
+    let mut columns = Vec::new();
+    for item in items {
+        let column = match item {
+            Value::Enum(name) => name.to_string(),
+            Value::String(name) => name.clone(),
+            _ => {
+                return Err(NouninatorError::SchemaGeneration(
+                    "groupBy entries must be column names".to_string(),
+                ))
+            }
+        };
+
+        if schema.field_with_name(&column).is_err() {
+            return Err(NouninatorError::SchemaGeneration(format!(
+                "Unknown group column '{}'",
+                column
+            )));
+        }
+
+        if !columns.contains(&column) {
+            columns.push(column);
+        }
+    }
+
+    Ok(columns)
+}
+
+/// Reshape a flat aggregate output row into the `XAggregate` value
+///
+/// # Arguments
+///
+/// * `row` - Converted output row (group columns plus aliased aggregates)
+/// * `group_by` - Grouped column names
+/// * `functions` - Column aggregates that were computed
+pub fn aggregate_row_to_value(
+    row: Value,
+    group_by: &[String],
+    functions: &[(AggregateFunction, String)],
+) -> Value {
+    let Value::Object(mut flat) = row else {
+        return Value::Null;
+    };
+
+    let mut result = IndexMap::new();
+
+    let group = if group_by.is_empty() {
+        Value::Null
+    } else {
+        Value::Object(
+            group_by
+                .iter()
+                .map(|column| {
+                    let value = flat.shift_remove(column.as_str()).unwrap_or(Value::Null);
+                    (Name::new(column), value)
+                })
+                .collect(),
+        )
+    };
+    result.insert(Name::new("group"), group);
+    result.insert(
+        Name::new("count"),
+        flat.shift_remove("count").unwrap_or(Value::Number(0.into())),
+    );
+
+    for function in COLUMN_FUNCTIONS {
+        let mut values = IndexMap::new();
+        for (f, column) in functions.iter().filter(|(f, _)| *f == function) {
+            let value = flat.shift_remove(f.alias(column).as_str()).unwrap_or(Value::Null);
+            values.insert(Name::new(column), value);
+        }
+        result.insert(Name::new(function.field_name()), Value::Object(values));
+    }
+
+    Value::Object(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::datatypes::{DataType, Field as ArrowField, TimeUnit};
+
+    fn test_schema() -> ArrowSchema {
+        ArrowSchema::new(vec![
+            ArrowField::new("id", DataType::Int64, false),
+            ArrowField::new("word", DataType::Utf8, false),
+            ArrowField::new("rank", DataType::Int64, true),
+            ArrowField::new("score", DataType::Float64, true),
+            ArrowField::new(
+                "created_at",
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                true,
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_aggregate_columns_by_function() {
+        let schema = test_schema();
+        assert_eq!(
+            aggregate_columns(AggregateFunction::Sum, &schema),
+            vec![("rank".to_string(), "BigInt"), ("score".to_string(), "Float")]
+        );
+        assert_eq!(
+            aggregate_columns(AggregateFunction::Avg, &schema),
+            vec![("rank".to_string(), "Float"), ("score".to_string(), "Float")]
+        );
+        assert_eq!(
+            aggregate_columns(AggregateFunction::Max, &schema),
+            vec![
+                ("rank".to_string(), "BigInt"),
+                ("score".to_string(), "Float"),
+                ("created_at".to_string(), "LocalDateTime"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_group_by() {
+        let schema = test_schema();
+        let group_by = Value::List(vec![
+            Value::Enum(Name::new("word")),
+            Value::Enum(Name::new("word")),
+        ]);
+        assert_eq!(parse_group_by(Some(&group_by), &schema).unwrap(), vec!["word"]);
+        assert!(parse_group_by(None, &schema).unwrap().is_empty());
+
+        let unknown = Value::List(vec![Value::Enum(Name::new("missing"))]);
+        assert!(parse_group_by(Some(&unknown), &schema).is_err());
+    }
+
+    #[test]
+    fn test_aggregate_row_to_value() {
+        let mut flat = IndexMap::new();
+        flat.insert(Name::new("word"), Value::String("cat".to_string()));
+        flat.insert(Name::new("count"), Value::Number(3.into()));
+        flat.insert(Name::new("sum__rank"), Value::Number(12.into()));
+
+        let value = aggregate_row_to_value(
+            Value::Object(flat),
+            &["word".to_string()],
+            &[(AggregateFunction::Sum, "rank".to_string())],
+        );
+
+        let expected = serde_json::json!({
+            "group": { "word": "cat" },
+            "count": 3,
+            "sum": { "rank": 12 },
+            "avg": {},
+            "min": {},
+            "max": {},
+        });
+        assert_eq!(value.into_json().unwrap(), expected);
+    }
+}
@@ -1,31 +1,59 @@
-/// GraphQL schema builder
-///
-/// This module provides the `SchemaBuilder` which generates a complete GraphQL schema
-/// from Delta tables (or CSV files for testing).
+//! GraphQL schema builder
+//!
+//! This module provides the `SchemaBuilder` which generates a complete GraphQL schema
+//! from Delta tables (or CSV files for testing).
 
 use crate::config::EntityConfig;
 use crate::error::{NouninatorError, Result};
+use crate::schema::access::{column_field, column_nullable, HiddenEntity, IntrospectionFilter};
+use crate::schema::loader::RowLoader;
+use crate::schema::masking::{check_masks, MaskKey};
+use crate::schema::refresh::RefreshableTable;
+use crate::schema::registry::TableRegistry;
+use crate::schema::row_count::RowCounter;
+use crate::schema::row_filter::{RowFilter, RowFilters};
+use crate::schema::filter::{build_filter_input, register_filter_inputs};
+use crate::schema::ordering::{build_column_enum, build_order_by_input, register_ordering_enums};
 use crate::schema::scalars::register_custom_scalars;
-use crate::schema::type_mapping::arrow_to_graphql_type;
-use crate::schema::resolver::{create_get_resolver, create_list_resolver};
-
-use datafusion::arrow::datatypes::Schema as ArrowSchema;
-use async_graphql::dynamic::{Field, FieldFuture, FieldValue, Object, Schema};
-use async_graphql::Value;
+use crate::schema::nested::{build_nested_types, column_graphql_type};
+use crate::schema::aggregate::build_aggregate_types;
+use crate::schema::changes::{build_change_page_type, build_change_type, change_type_name};
+use crate::schema::connection::{build_connection_types, build_page_info_type};
+use crate::schema::empty_scan::new_session_context;
+use crate::schema::key::{build_key_input, build_lookup_type};
+use crate::schema::resolver::{
+    create_aggregate_resolver, create_by_ids_resolver, create_changes_resolver, create_connection_resolver, create_get_resolver,
+    create_list_resolver, create_relation_field,
+};
+use crate::schema::mutation::{
+    build_bulk_insert_result_type, build_mutation_inputs, check_mutation_columns, create_bulk_insert_mutation,
+    create_create_mutation, create_delete_mutation, create_update_mutation, create_upsert_mutation,
+};
+use crate::schema::subscription::{create_added_subscription, create_changed_subscription};
+
+use datafusion::arrow::datatypes::{DataType as ArrowDataType, Schema as ArrowSchema};
+use async_graphql::dataloader::DataLoader;
+use async_graphql::dynamic::{Object, Schema, Subscription};
+use datafusion::datasource::TableProvider;
 use datafusion::prelude::*;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 /// Schema builder for generating GraphQL schemas from Delta tables
 pub struct SchemaBuilder {
     /// DataFusion session context for query execution
     datafusion_ctx: SessionContext,
+
+    /// Delta table locations and historical snapshots (for time travel)
+    registry: Arc<TableRegistry>,
 }
 
 impl SchemaBuilder {
     /// Create a new schema builder
     pub fn new() -> Self {
         Self {
-            datafusion_ctx: SessionContext::new(),
+            datafusion_ctx: new_session_context(),
+            registry: Arc::new(TableRegistry::new()),
         }
     }
 
@@ -45,8 +73,21 @@ impl SchemaBuilder {
             ));
         }
 
-        // Build Query type
+        // Build Query type, Mutation type for writable entities, and Subscription
+        // type for Delta-backed entities
         let mut query = Object::new("Query");
+        let mut mutation = Object::new("Mutation");
+        let mut has_mutations = false;
+        let mut subscription = Subscription::new("Subscription");
+        let mut has_subscriptions = false;
+        let mut object_types = Vec::new();
+        let mut input_types = Vec::new();
+        let mut enum_types = Vec::new();
+        let mut hidden_entities = Vec::new();
+        let mut row_filters = RowFilters::default();
+
+        // Resolve every entity's Arrow schema up front so relations can be checked
+        let mut arrow_schemas = HashMap::new();
 
         for entity in &entities {
             // Validate entity config
@@ -54,20 +95,6 @@ impl SchemaBuilder {
                 NouninatorError::Config(format!("Invalid entity '{}': {}", entity.graphql_name, e))
             })?;
 
-            tracing::info!("Building schema for entity: {}", entity.graphql_name);
-
-            // Get the table from DataFusion context
-            let _table = self
-                .datafusion_ctx
-                .table(&entity.table)
-                .await
-                .map_err(|e| {
-                    NouninatorError::SchemaGeneration(format!(
-                        "Failed to get table '{}': {}",
-                        entity.table, e
-                    ))
-                })?;
-
             // Get the Arrow schema from the table provider
             let table_provider = self
                 .datafusion_ctx
@@ -80,56 +107,208 @@ impl SchemaBuilder {
                     ))
                 })?;
 
-            let arrow_schema = table_provider.schema().as_ref().clone();
+            let arrow_schema = table_provider.schema();
+            if let Some(missing) = entity
+                .primary_key
+                .columns()
+                .iter()
+                .find(|column| arrow_schema.field_with_name(column).is_err())
+            {
+                return Err(NouninatorError::Config(format!(
+                    "Primary key column '{}' of '{}' not found in table '{}'",
+                    missing, entity.graphql_name, entity.table
+                )));
+            }
+
+            if let Some(column) = entity
+                .columns
+                .keys()
+                .find(|column| arrow_schema.field_with_name(column).is_err())
+            {
+                return Err(NouninatorError::Config(format!(
+                    "Column '{}' configured on '{}' not found in table '{}'",
+                    column, entity.graphql_name, entity.table
+                )));
+            }
+            check_masks(entity, &arrow_schema)?;
+
+            if let Some(row_filter) = &entity.row_filter {
+                row_filters.insert(
+                    &entity.graphql_name,
+                    RowFilter::parse(&self.datafusion_ctx, row_filter, &arrow_schema)?,
+                );
+            }
+
+            arrow_schemas.insert(entity.graphql_name.clone(), arrow_schema.as_ref().clone());
+        }
+
+        for entity in &entities {
+            tracing::info!("Building schema for entity: {}", entity.graphql_name);
 
-            // Build GraphQL object type from Arrow schema
-            let _object_type = self.build_entity_type(entity, arrow_schema)?;
+            let arrow_schema = arrow_schemas[&entity.graphql_name].clone();
+            let (first_object, first_input, first_enum) = (object_types.len(), input_types.len(), enum_types.len());
+
+            // Build GraphQL filter and sort inputs from Arrow schema
+            input_types.push(build_filter_input(entity, &arrow_schema));
+            input_types.push(build_order_by_input(entity));
+            enum_types.push(build_column_enum(entity, &arrow_schema));
+
+            // Build GraphQL object type (and nested struct types) from Arrow schema
+            for nested in build_nested_types(&entity.graphql_name, arrow_schema.fields()) {
+                if let Some(other) = entities.iter().find(|e| e.graphql_name == nested.type_name()) {
+                    return Err(NouninatorError::Config(format!(
+                        "Struct column type '{}' on '{}' clashes with entity '{}'",
+                        nested.type_name(),
+                        entity.graphql_name,
+                        other.graphql_name
+                    )));
+                }
+                object_types.push(nested);
+            }
+            object_types.extend(build_connection_types(&entity.graphql_name));
+            object_types.extend(build_aggregate_types(entity, &arrow_schema));
+            object_types.push(self.build_entity_type(entity, arrow_schema, &entities, &arrow_schemas)?);
 
             // Add get_X resolver (by primary key)
-            let get_field = create_get_resolver(entity);
+            let get_field = create_get_resolver(entity, &arrow_schemas[&entity.graphql_name]);
             query = query.field(get_field);
 
-            // Add list_X resolver (with pagination)
+            // Add X_by_ids resolver (batch lookup by primary key)
+            input_types.extend(build_key_input(entity, &arrow_schemas[&entity.graphql_name]));
+            object_types.push(build_lookup_type(&entity.graphql_name));
+            query = query.field(create_by_ids_resolver(entity));
+
+            // Add list_X resolver (with filtering, sorting and pagination)
             let list_field = create_list_resolver(entity);
             query = query.field(list_field);
 
-            // Store the object type to register later
-            // Note: We'll register it after building the Query type
+            // Add X_connection resolver (with cursor pagination)
+            let connection_field = create_connection_resolver(entity);
+            query = query.field(connection_field);
+
+            // Add X_aggregate resolver (count/sum/avg/min/max, optionally grouped)
+            let aggregate_field = create_aggregate_resolver(entity);
+            query = query.field(aggregate_field);
+
+            // Add X_changes (Change Data Feed) and xAdded/xChanged subscriptions
+            // (tailing the Delta log)
+            if self.registry.served_table(&entity.table).is_some() {
+                object_types.push(build_change_type(entity, &arrow_schemas[&entity.graphql_name]));
+                object_types.push(build_change_page_type(&entity.graphql_name));
+                query = query.field(create_changes_resolver(entity));
+                subscription = subscription
+                    .field(create_added_subscription(entity))
+                    .field(create_changed_subscription(entity));
+                has_subscriptions = true;
+            }
+
+            // Add createX/updateX/deleteX/upsertX/bulkInsertX mutations (opt-in, Delta only)
+            if entity.mutations {
+                if self.registry.served_table(&entity.table).is_none() {
+                    return Err(NouninatorError::Config(format!(
+                        "Entity '{}' enables mutations but '{}' is not a Delta table",
+                        entity.graphql_name, entity.table
+                    )));
+                }
+
+                check_mutation_columns(entity, &arrow_schemas[&entity.graphql_name])?;
+
+                input_types.extend(build_mutation_inputs(entity, &arrow_schemas[&entity.graphql_name]));
+                mutation = mutation
+                    .field(create_create_mutation(entity))
+                    .field(create_update_mutation(entity, &arrow_schemas[&entity.graphql_name]))
+                    .field(create_delete_mutation(entity, &arrow_schemas[&entity.graphql_name]))
+                    .field(create_upsert_mutation(entity))
+                    .field(create_bulk_insert_mutation(entity));
+                has_mutations = true;
+            }
+
+            // Remember the entity's types so introspection can hide them
+            if entity.hide_forbidden {
+                let types = object_types[first_object..]
+                    .iter()
+                    .map(|object| object.type_name().to_string())
+                    .chain(input_types[first_input..].iter().map(|input| input.type_name().to_string()))
+                    .chain(enum_types[first_enum..].iter().map(|e| e.type_name().to_string()))
+                    .collect();
+                hidden_entities.push(HiddenEntity::new(entity, types));
+            }
         }
 
         // Build the schema with custom scalars and entity types
-        let mut schema_builder = Schema::build(query.type_name(), None, None);
+        let mutation_name = has_mutations.then(|| mutation.type_name().to_string());
+        let subscription_name = has_subscriptions.then(|| subscription.type_name().to_string());
+        let mut schema_builder = Schema::build(
+            query.type_name(),
+            mutation_name.as_deref(),
+            subscription_name.as_deref(),
+        );
 
         // Add custom scalars
         for scalar in register_custom_scalars() {
             schema_builder = schema_builder.register(scalar);
         }
 
-        // Register all entity types
-        for entity in &entities {
-            // Re-build the object type to register it
-            let table_provider = self
-                .datafusion_ctx
-                .table_provider(&entity.table)
-                .await
-                .map_err(|e| {
-                    NouninatorError::SchemaGeneration(format!(
-                        "Failed to get table provider for '{}': {}",
-                        entity.table, e
-                    ))
-                })?;
+        // Add shared filter operator inputs and sort enums
+        for input in register_filter_inputs() {
+            schema_builder = schema_builder.register(input);
+        }
+
+        for sort_enum in register_ordering_enums() {
+            schema_builder = schema_builder.register(sort_enum);
+        }
 
-            let arrow_schema = table_provider.schema().as_ref().clone();
-            let object_type = self.build_entity_type(entity, arrow_schema)?;
+        // Register all entity types and their filter/sort/connection types
+        schema_builder = schema_builder.register(build_page_info_type());
+
+        for object_type in object_types {
             schema_builder = schema_builder.register(object_type);
         }
 
-        // Add the Query object
+        for input_type in input_types {
+            schema_builder = schema_builder.register(input_type);
+        }
+
+        for enum_type in enum_types {
+            schema_builder = schema_builder.register(enum_type);
+        }
+
+        // Add the Query, Mutation and Subscription objects
         schema_builder = schema_builder.register(query);
+        if has_mutations {
+            schema_builder = schema_builder
+                .register(mutation)
+                .register(build_bulk_insert_result_type());
+        }
+        if has_subscriptions {
+            schema_builder = schema_builder.register(subscription);
+        }
+
+        // Remove hidden entities and columns from introspection for callers who may not read them
+        if !hidden_entities.is_empty() {
+            schema_builder = schema_builder.extension(IntrospectionFilter::new(hidden_entities));
+        }
+
+        // Charge the rows sent to API key callers to their quotas
+        let row_types = entities
+            .iter()
+            .flat_map(|entity| [entity.graphql_name.clone(), change_type_name(&entity.graphql_name)])
+            .collect();
+        schema_builder = schema_builder.extension(RowCounter::new(row_types));
+
+        // Store DataFusion context, table registry, row filters, the masking key and the batched row loader in schema data
+        let datafusion_ctx = Arc::new(self.datafusion_ctx.clone());
+        let row_loader = DataLoader::new(
+            RowLoader::new(datafusion_ctx.clone(), self.registry.clone()),
+            tokio::spawn,
+        );
 
-        // Store DataFusion context in schema data
         let schema = schema_builder
-            .data(Arc::new(self.datafusion_ctx.clone()))
+            .data(datafusion_ctx)
+            .data(self.registry.clone())
+            .data(row_filters)
+            .data(MaskKey::generate()?)
+            .data(row_loader)
             .finish()
             .map_err(|e| {
                 NouninatorError::SchemaGeneration(format!("Failed to build schema: {}", e))
@@ -138,6 +317,32 @@ impl SchemaBuilder {
         Ok(schema)
     }
 
+    /// Registry of the Delta tables registered so far
+    ///
+    /// Shared with the built schema; used to refresh tables while serving.
+    pub fn registry(&self) -> Arc<TableRegistry> {
+        self.registry.clone()
+    }
+
+    /// Register an existing table provider under the given name
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name to register the table as
+    /// * `provider` - Table provider to query
+    pub fn register_table(&mut self, name: &str, provider: Arc<dyn TableProvider>) -> Result<()> {
+        self.datafusion_ctx
+            .register_table(name, provider)
+            .map_err(|e| {
+                NouninatorError::SchemaGeneration(format!(
+                    "Failed to register table '{}': {}",
+                    name, e
+                ))
+            })?;
+
+        Ok(())
+    }
+
     /// Register a table from a file path (supports CSV for testing, Delta for production)
     ///
     /// # Arguments
@@ -146,16 +351,36 @@ impl SchemaBuilder {
     /// * `path` - Path to the file (CSV or Delta table)
     pub async fn register_table_from_path(&mut self, name: &str, path: &str) -> Result<()> {
         if path.ends_with(".csv") {
+            let csv_error = |e: datafusion::error::DataFusionError| {
+                NouninatorError::SchemaGeneration(format!("Failed to register CSV '{}': {}", path, e))
+            };
+
+            // CSV timestamps carry no zone type; read them as UTC instants
+            let inferred = self
+                .datafusion_ctx
+                .read_csv(path, CsvReadOptions::default())
+                .await
+                .map_err(csv_error)?;
+            let schema = ArrowSchema::new(
+                inferred
+                    .schema()
+                    .fields()
+                    .iter()
+                    .map(|field| match field.data_type() {
+                        ArrowDataType::Timestamp(unit, None) => field
+                            .as_ref()
+                            .clone()
+                            .with_data_type(ArrowDataType::Timestamp(*unit, Some("UTC".into()))),
+                        _ => field.as_ref().clone(),
+                    })
+                    .collect::<Vec<_>>(),
+            );
+
             // Register CSV file
             self.datafusion_ctx
-                .register_csv(name, path, CsvReadOptions::default())
+                .register_csv(name, path, CsvReadOptions::new().schema(&schema))
                 .await
-                .map_err(|e| {
-                    NouninatorError::SchemaGeneration(format!(
-                        "Failed to register CSV '{}': {}",
-                        path, e
-                    ))
-                })?;
+                .map_err(csv_error)?;
         } else {
             // Register Delta table
             let delta_table = deltalake::open_table(path).await.map_err(|e| {
@@ -165,24 +390,35 @@ impl SchemaBuilder {
                 ))
             })?;
 
+            // Served through a refreshable provider so new commits can be swapped in
+            let provider = Arc::new(RefreshableTable::new(name, delta_table));
             self.datafusion_ctx
-                .register_table(name, Arc::new(delta_table))
+                .register_table(name, provider.clone())
                 .map_err(|e| {
                     NouninatorError::SchemaGeneration(format!(
                         "Failed to register Delta table '{}': {}",
                         name, e
                     ))
                 })?;
+
+            // Remember the location so historical snapshots can be loaded
+            self.registry.register_delta(name, path);
+            self.registry.register_served(name, provider);
         }
 
         Ok(())
     }
 
     /// Build GraphQL object type from Arrow schema
+    ///
+    /// Columns become scalar fields and `[[entity.relation]]` entries become
+    /// nested fields resolving against the related entity's table.
     fn build_entity_type(
         &self,
         entity: &EntityConfig,
         arrow_schema: ArrowSchema,
+        entities: &[EntityConfig],
+        arrow_schemas: &HashMap<String, ArrowSchema>,
     ) -> Result<Object> {
         let mut object = Object::new(&entity.graphql_name);
 
@@ -192,32 +428,54 @@ impl SchemaBuilder {
 
         // Map each Arrow field to a GraphQL field
         for field in arrow_schema.fields() {
-            if let Some(type_ref) =
-                arrow_to_graphql_type(field.name(), field.data_type(), field.is_nullable())
-            {
-                let field_name = field.name().to_string();
-                let field_name_for_closure = field_name.clone();
-                
-                let graphql_field = Field::new(field_name, type_ref, move |ctx| {
-                    let field_name = field_name_for_closure.clone();
-                    FieldFuture::new(async move {
-                        // Extract the field value from the parent object
-                        let parent = ctx.parent_value.try_downcast_ref::<Value>()?;
-                        
-                        if let Value::Object(obj) = parent {
-                            if let Some(value) = obj.get(field_name.as_str()) {
-                                return Ok(Some(FieldValue::value(value.clone())));
-                            }
-                        }
-                        
-                        Ok(Some(FieldValue::NULL))
-                    })
-                });
+            let nullable = column_nullable(entity, field.name(), field.is_nullable());
+            if let Some(type_ref) = column_graphql_type(&entity.graphql_name, field, nullable) {
+                // Extract the field value from the parent object (if the caller may read it)
+                let graphql_field = column_field(entity, field.name(), type_ref);
 
                 object = object.field(graphql_field);
             }
         }
 
+        // Add nested fields for relations
+        for relation in &entity.relation {
+            let target = entities
+                .iter()
+                .find(|e| e.graphql_name == relation.target)
+                .ok_or_else(|| {
+                    NouninatorError::Config(format!(
+                        "Relation '{}' on '{}' targets unknown entity '{}'",
+                        relation.name, entity.graphql_name, relation.target
+                    ))
+                })?;
+
+            if arrow_schema.field_with_name(&relation.local_column).is_err() {
+                return Err(NouninatorError::Config(format!(
+                    "Relation '{}' on '{}': unknown local column '{}'",
+                    relation.name, entity.graphql_name, relation.local_column
+                )));
+            }
+
+            if arrow_schemas[&target.graphql_name]
+                .field_with_name(&relation.remote_column)
+                .is_err()
+            {
+                return Err(NouninatorError::Config(format!(
+                    "Relation '{}' on '{}': unknown remote column '{}' on '{}'",
+                    relation.name, entity.graphql_name, relation.remote_column, target.graphql_name
+                )));
+            }
+
+            if arrow_schema.field_with_name(&relation.name).is_ok() {
+                return Err(NouninatorError::Config(format!(
+                    "Relation '{}' on '{}' clashes with a column of the same name",
+                    relation.name, entity.graphql_name
+                )));
+            }
+
+            object = object.field(create_relation_field(entity, relation, target));
+        }
+
         Ok(object)
     }
 }
@@ -0,0 +1,305 @@
+//! Row-level changes from the Delta transaction log
+//!
+//! Changes between two versions are read with deltalake's change data feed
+//! reader. For tables with `delta.enableChangeDataFeed = true` this yields the
+//! recorded inserts, deletes and update pre/post images; for other tables it
+//! falls back to the data files each commit added (`insert`) and removed
+//! (`delete`). Every change row carries the table's columns plus
+//! `_change_type`, `_commit_version` and `_commit_timestamp`, exposed as the
+//! `XChange` object type returned by `X_changes` queries and `xChanged`
+//! subscriptions.
+//!
+//! `X_changes` reads at most [`MAX_CHANGE_VERSIONS`] commits and `limit` rows
+//! per request, returned in an `XChangePage` whose `nextVersion`/`nextOffset`
+//! say where the next request (or the next sync) should continue.
+
+use crate::config::EntityConfig;
+use crate::error::{NouninatorError, Result};
+use crate::schema::access::column_field;
+use crate::schema::empty_scan::new_session_context;
+use crate::schema::filter::filter_to_expr;
+use crate::schema::resolver::{parent_field, record_batch_to_graphql_value};
+use crate::schema::nested::column_graphql_type;
+use crate::schema::scalars::{big_int_to_i64, big_int_value, BIG_INT};
+
+use async_graphql::dynamic::{Object, TypeRef};
+use async_graphql::{Name, Value};
+use chrono::{DateTime, Utc};
+use datafusion::arrow::array::timezone::Tz;
+use datafusion::arrow::datatypes::{DataType, Schema as ArrowSchema, TimeUnit};
+use datafusion::physical_plan::collect;
+use datafusion::prelude::{cast, ident, Expr};
+use deltalake::{DeltaOps, DeltaTable};
+use indexmap::IndexMap;
+use std::sync::Arc;
+
+/// Change type column (`insert`, `delete`, `update_preimage`, `update_postimage`)
+pub const CHANGE_TYPE_COLUMN: &str = "_change_type";
+
+/// Version of the commit that made the change
+pub const COMMIT_VERSION_COLUMN: &str = "_commit_version";
+
+/// Timestamp of the commit that made the change
+pub const COMMIT_TIMESTAMP_COLUMN: &str = "_commit_timestamp";
+
+/// Change type of newly inserted rows
+pub const INSERT: &str = "insert";
+
+/// Most commits a single `X_changes` request reads
+pub const MAX_CHANGE_VERSIONS: i64 = 100;
+
+/// Commits to read changes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeRange {
+    /// First version to read (inclusive)
+    pub from_version: i64,
+    /// Last version to read (inclusive)
+    pub to_version: i64,
+    /// Skip commits made before this time
+    pub from_timestamp: Option<DateTime<Utc>>,
+    /// Skip commits made after this time
+    pub to_timestamp: Option<DateTime<Utc>>,
+}
+
+impl ChangeRange {
+    /// All commits between two versions (inclusive)
+    pub fn versions(from_version: i64, to_version: i64) -> Self {
+        Self {
+            from_version,
+            to_version,
+            from_timestamp: None,
+            to_timestamp: None,
+        }
+    }
+}
+
+/// Whether a table records its Change Data Feed
+pub fn change_data_feed_enabled(table: &DeltaTable) -> bool {
+    table
+        .metadata()
+        .ok()
+        .and_then(|metadata| metadata.configuration.get("delta.enableChangeDataFeed"))
+        .and_then(|value| value.as_deref())
+        .is_some_and(|value| value.eq_ignore_ascii_case("true"))
+}
+
+/// Name of the generated change type for an entity (e.g. `CustomerChange`)
+pub fn change_type_name(graphql_name: &str) -> String {
+    format!("{}Change", graphql_name)
+}
+
+/// Name of the generated change page type for an entity (e.g. `CustomerChangePage`)
+pub fn change_page_type_name(graphql_name: &str) -> String {
+    format!("{}ChangePage", graphql_name)
+}
+
+/// Build the `XChangePage` object type returned by `X_changes`
+pub fn build_change_page_type(graphql_name: &str) -> Object {
+    Object::new(change_page_type_name(graphql_name))
+        .description(format!("A page of changes to {} rows", graphql_name))
+        .field(parent_field(
+            "changes",
+            TypeRef::named_nn_list_nn(change_type_name(graphql_name)),
+        ))
+        .field(
+            parent_field("hasMore", TypeRef::named_nn(TypeRef::BOOLEAN))
+                .description("Whether the requested range has changes beyond this page"),
+        )
+        .field(
+            parent_field("nextVersion", TypeRef::named_nn(BIG_INT))
+                .description("`fromVersion` to continue from (past `toVersion` once the range is read)"),
+        )
+        .field(
+            parent_field("nextOffset", TypeRef::named_nn(TypeRef::INT))
+                .description("`offset` to continue from: changes of `nextVersion` already returned"),
+        )
+}
+
+/// Build the `XChange` object type: the entity's columns plus change metadata
+pub fn build_change_type(entity: &EntityConfig, arrow_schema: &ArrowSchema) -> Object {
+    let graphql_name = entity.graphql_name.as_str();
+    let mut object = Object::new(change_type_name(graphql_name))
+        .description(format!("A change to a {} row", graphql_name));
+
+    for field in arrow_schema.fields() {
+        if let Some(type_ref) = column_graphql_type(graphql_name, field, true) {
+            object = object.field(column_field(entity, field.name(), type_ref));
+        }
+    }
+
+    object
+        .field(parent_field(CHANGE_TYPE_COLUMN, TypeRef::named_nn(TypeRef::STRING)))
+        .field(parent_field(COMMIT_VERSION_COLUMN, TypeRef::named_nn(BIG_INT)))
+        .field(parent_field(COMMIT_TIMESTAMP_COLUMN, TypeRef::named("DateTime")))
+}
+
+/// Read the changes committed in a range of versions
+///
+/// # Arguments
+///
+/// * `table` - Snapshot at the end of the range or later
+/// * `range` - Commits to read
+/// * `filter` - Optional `XFilter` value applied to the changed rows
+/// * `row_filter` - The entity's bound row filter, if it has one
+/// * `timezone` - Zone to render `DateTime` values in (defaults to each column's zone)
+/// * `primary_key` - Columns ordering the changes within a commit
+/// * `page` - Rows to return as `(offset, limit)` (`None` for every row)
+///
+/// Rows are returned in commit order (then primary key and change type
+/// order, so pages are stable), converted like query results.
+pub async fn read_changes(
+    table: &DeltaTable,
+    range: &ChangeRange,
+    filter: Option<&Value>,
+    row_filter: Option<&Expr>,
+    timezone: Option<&Tz>,
+    primary_key: &[String],
+    page: Option<(usize, usize)>,
+) -> Result<Vec<Value>> {
+    let ctx = new_session_context();
+    let arrow_schema = datafusion::datasource::TableProvider::schema(table);
+
+    let mut builder = DeltaOps(table.clone())
+        .load_cdf()
+        .with_session_ctx(ctx.clone())
+        .with_starting_version(range.from_version)
+        .with_ending_version(range.to_version);
+    if let Some(from) = range.from_timestamp {
+        builder = builder.with_starting_timestamp(from);
+    }
+    if let Some(to) = range.to_timestamp {
+        builder = builder.with_ending_timestamp(to);
+    }
+
+    let scan = builder.build().await.map_err(|e| {
+        NouninatorError::SchemaGeneration(format!(
+            "Failed to read changes between versions {} and {}: {}",
+            range.from_version, range.to_version, e
+        ))
+    })?;
+
+    let batches = collect(Arc::new(scan), ctx.task_ctx())
+        .await
+        .map_err(|e| NouninatorError::SchemaGeneration(format!("Failed to read changes: {}", e)))?;
+    if batches.iter().all(|batch| batch.num_rows() == 0) {
+        return Ok(Vec::new());
+    }
+
+    // Partition and metadata columns come back dictionary-encoded; cast
+    // everything back to the table's types so filters and conversion apply
+    let mut columns: Vec<Expr> = arrow_schema
+        .fields()
+        .iter()
+        .map(|field| cast(ident(field.name()), field.data_type().clone()).alias(field.name()))
+        .collect();
+    columns.push(cast(ident(CHANGE_TYPE_COLUMN), DataType::Utf8).alias(CHANGE_TYPE_COLUMN));
+    columns.push(cast(ident(COMMIT_VERSION_COLUMN), DataType::Int64).alias(COMMIT_VERSION_COLUMN));
+    columns.push(
+        cast(
+            ident(COMMIT_TIMESTAMP_COLUMN),
+            DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+        )
+        .alias(COMMIT_TIMESTAMP_COLUMN),
+    );
+
+    let mut df = ctx
+        .read_batches(batches)
+        .and_then(|df| df.select(columns))
+        .map_err(|e| NouninatorError::SchemaGeneration(format!("Failed to read changes: {}", e)))?;
+
+    if let Some(row_filter) = row_filter {
+        df = df
+            .filter(row_filter.clone())
+            .map_err(|e| NouninatorError::SchemaGeneration(format!("Failed to read changes: {}", e)))?;
+    }
+
+    if let Some(filter) = filter {
+        if let Some(predicate) = filter_to_expr(filter, &arrow_schema)? {
+            df = df
+                .filter(predicate)
+                .map_err(|e| NouninatorError::SchemaGeneration(format!("Invalid filter: {}", e)))?;
+        }
+    }
+
+    let mut sort = vec![ident(COMMIT_VERSION_COLUMN).sort(true, false)];
+    sort.extend(primary_key.iter().map(|column| ident(column).sort(true, false)));
+    sort.push(ident(CHANGE_TYPE_COLUMN).sort(true, false));
+    let mut df = df
+        .sort(sort)
+        .map_err(|e| NouninatorError::SchemaGeneration(format!("Failed to read changes: {}", e)))?;
+
+    if let Some((offset, limit)) = page {
+        df = df
+            .limit(offset, Some(limit))
+            .map_err(|e| NouninatorError::SchemaGeneration(format!("Failed to read changes: {}", e)))?;
+    }
+
+    let batches = df
+        .collect()
+        .await
+        .map_err(|e| NouninatorError::SchemaGeneration(format!("Failed to read changes: {}", e)))?;
+
+    let mut rows = Vec::new();
+    for batch in &batches {
+        for row_idx in 0..batch.num_rows() {
+            rows.push(record_batch_to_graphql_value(batch, row_idx, timezone)?);
+        }
+    }
+
+    Ok(rows)
+}
+
+/// The `_commit_version` of a converted change row
+pub fn commit_version(row: &Value) -> Option<i64> {
+    match row {
+        Value::Object(obj) => match obj.get(COMMIT_VERSION_COLUMN) {
+            Some(version) => big_int_to_i64(version),
+            None => None,
+        },
+        _ => None,
+    }
+}
+
+/// Build the `XChangePage` value for one page of changes
+///
+/// `rows` are the changes read from `range` with `offset` skipped and a
+/// limit of `limit + 1`: the extra row only tells that the page is full.
+/// `requested_to` is the `toVersion` asked for, which `range` may stop short
+/// of (see [`MAX_CHANGE_VERSIONS`]).
+pub fn change_page(
+    mut rows: Vec<Value>,
+    range: &ChangeRange,
+    requested_to: i64,
+    offset: usize,
+    limit: usize,
+) -> Value {
+    let (next_version, next_offset) = if rows.len() > limit {
+        // Continue within the commit of the last row returned
+        rows.truncate(limit);
+        let last = rows.last().and_then(commit_version).unwrap_or(range.from_version);
+        let returned = rows.iter().filter(|row| commit_version(row) == Some(last)).count();
+        let skipped = if last == range.from_version { offset } else { 0 };
+        (last, skipped + returned)
+    } else {
+        (range.to_version + 1, 0)
+    };
+    let has_more = next_version <= requested_to;
+
+    let mut page = IndexMap::new();
+    page.insert(Name::new("changes"), Value::List(rows));
+    page.insert(Name::new("hasMore"), Value::Boolean(has_more));
+    page.insert(Name::new("nextVersion"), big_int_value(next_version));
+    page.insert(Name::new("nextOffset"), Value::Number(next_offset.into()));
+    Value::Object(page)
+}
+
+/// The `_change_type` of a converted change row
+pub fn change_type(row: &Value) -> Option<&str> {
+    match row {
+        Value::Object(obj) => match obj.get(CHANGE_TYPE_COLUMN) {
+            Some(Value::String(change_type)) => Some(change_type),
+            _ => None,
+        },
+        _ => None,
+    }
+}
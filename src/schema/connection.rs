@@ -0,0 +1,271 @@
+//! Relay-style cursor pagination
+//!
+//! This module generates the `XConnection`/`XEdge` object types and the shared
+//! `PageInfo` type, and implements opaque keyset cursors over the sort key.
+//! A cursor encodes the sort columns and the row's values for them, so the next
+//! page is fetched with a `WHERE (sort key) > (cursor)` predicate instead of an
+//! offset - deep pages stay cheap and stable while the table is appended to.
+
+use crate::error::{NouninatorError, Result};
+use crate::schema::filter::value_to_literal;
+use crate::schema::ordering::SortKey;
+use crate::schema::resolver::parent_field;
+use crate::schema::scalars::BIG_INT;
+
+use async_graphql::dynamic::{Object, TypeRef};
+use async_graphql::Value;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use datafusion::arrow::datatypes::Schema as ArrowSchema;
+use datafusion::prelude::{ident, lit, Expr};
+use serde::{Deserialize, Serialize};
+
+/// Shared page info type name
+pub const PAGE_INFO: &str = "PageInfo";
+
+/// Name of the generated connection type for an entity (e.g. `CustomerConnection`)
+pub fn connection_type_name(graphql_name: &str) -> String {
+    format!("{}Connection", graphql_name)
+}
+
+/// Name of the generated edge type for an entity (e.g. `CustomerEdge`)
+pub fn edge_type_name(graphql_name: &str) -> String {
+    format!("{}Edge", graphql_name)
+}
+
+/// Build the shared `PageInfo` object type
+pub fn build_page_info_type() -> Object {
+    Object::new(PAGE_INFO)
+        .description("Information about pagination in a connection")
+        .field(parent_field("hasNextPage", TypeRef::named_nn(TypeRef::BOOLEAN)))
+        .field(parent_field("hasPreviousPage", TypeRef::named_nn(TypeRef::BOOLEAN)))
+        .field(parent_field("startCursor", TypeRef::named(TypeRef::STRING)))
+        .field(parent_field("endCursor", TypeRef::named(TypeRef::STRING)))
+}
+
+/// Build the `XConnection` and `XEdge` object types for an entity
+pub fn build_connection_types(graphql_name: &str) -> Vec<Object> {
+    let edge = Object::new(edge_type_name(graphql_name))
+        .description(format!("An edge in a {} connection", graphql_name))
+        .field(parent_field("cursor", TypeRef::named_nn(TypeRef::STRING)))
+        .field(parent_field("node", TypeRef::named_nn(graphql_name)));
+
+    let connection = Object::new(connection_type_name(graphql_name))
+        .description(format!("A paginated list of {}", graphql_name))
+        .field(parent_field(
+            "edges",
+            TypeRef::named_nn_list_nn(edge_type_name(graphql_name)),
+        ))
+        .field(parent_field("pageInfo", TypeRef::named_nn(PAGE_INFO)))
+        .field(parent_field("totalCount", TypeRef::named_nn(BIG_INT)));
+
+    vec![connection, edge]
+}
+
+/// Decoded keyset cursor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cursor {
+    /// Sort columns the cursor was produced for
+    #[serde(rename = "c")]
+    pub columns: Vec<String>,
+    /// The row's values for those columns
+    #[serde(rename = "v")]
+    pub values: Vec<serde_json::Value>,
+}
+
+impl Cursor {
+    /// Build the cursor for a converted row
+    pub fn for_row(row: &Value, sort_keys: &[SortKey]) -> Result<Self> {
+        let Value::Object(obj) = row else {
+            return Err(NouninatorError::SchemaGeneration(
+                "Cannot build cursor for non-object row".to_string(),
+            ));
+        };
+
+        let mut values = Vec::with_capacity(sort_keys.len());
+        for key in sort_keys {
+            let value = obj.get(key.column.as_str()).cloned().unwrap_or(Value::Null);
+            values.push(value.into_json().map_err(|e| {
+                NouninatorError::Serialization(format!("Failed to encode cursor: {}", e))
+            })?);
+        }
+
+        Ok(Self {
+            columns: sort_keys.iter().map(|k| k.column.clone()).collect(),
+            values,
+        })
+    }
+
+    /// Encode as an opaque string
+    pub fn encode(&self) -> String {
+        // Serializing plain strings and JSON values cannot fail
+        let json = serde_json::to_vec(self).unwrap_or_default();
+        URL_SAFE_NO_PAD.encode(json)
+    }
+
+    /// Decode an opaque cursor string
+    pub fn decode(cursor: &str) -> Result<Self> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(cursor)
+            .map_err(|_| NouninatorError::SchemaGeneration("Invalid cursor".to_string()))?;
+        serde_json::from_slice(&bytes)
+            .map_err(|_| NouninatorError::SchemaGeneration("Invalid cursor".to_string()))
+    }
+
+    /// Predicate selecting rows strictly after this cursor in `sort_keys` order
+    ///
+    /// For keys `(a, b)` this is `a > x OR (a = x AND b > y)`, with the
+    /// comparison direction and null placement taken from each key.
+    pub fn after_predicate(&self, sort_keys: &[SortKey], schema: &ArrowSchema) -> Result<Expr> {
+        let columns: Vec<&str> = sort_keys.iter().map(|k| k.column.as_str()).collect();
+        if self.columns != columns {
+            return Err(NouninatorError::SchemaGeneration(
+                "Cursor does not match the requested orderBy".to_string(),
+            ));
+        }
+
+        let mut disjuncts = Vec::new();
+        let mut equal_prefix: Vec<Expr> = Vec::new();
+
+        for (key, json) in sort_keys.iter().zip(&self.values) {
+            let value = Value::from_json(json.clone()).map_err(|e| {
+                NouninatorError::SchemaGeneration(format!("Invalid cursor value: {}", e))
+            })?;
+            let column = ident(&key.column);
+
+            let (after, equal) = if matches!(value, Value::Null) {
+                // Only non-null values can follow a null that sorts first
+                let after = if key.nulls_first {
+                    column.clone().is_not_null()
+                } else {
+                    lit(false)
+                };
+                (after, column.is_null())
+            } else {
+                let data_type = schema
+                    .field_with_name(&key.column)
+                    .map_err(|_| {
+                        NouninatorError::SchemaGeneration(format!(
+                            "Unknown cursor column '{}'",
+                            key.column
+                        ))
+                    })?
+                    .data_type();
+                let literal = value_to_literal(&value, data_type)?;
+                let beyond = if key.ascending {
+                    column.clone().gt(literal.clone())
+                } else {
+                    column.clone().lt(literal.clone())
+                };
+                // Nulls sorting last come after every non-null value
+                let after = if key.nulls_first {
+                    beyond
+                } else {
+                    beyond.or(column.clone().is_null())
+                };
+                (after, column.eq(literal))
+            };
+
+            let clause = equal_prefix
+                .iter()
+                .cloned()
+                .chain(std::iter::once(after))
+                .reduce(Expr::and)
+                .expect("clause has at least one term");
+            disjuncts.push(clause);
+            equal_prefix.push(equal);
+        }
+
+        Ok(disjuncts.into_iter().reduce(Expr::or).unwrap_or(lit(true)))
+    }
+}
+
+/// Reverse sort keys (for paginating backwards with `last`/`before`)
+pub fn reverse_sort_keys(sort_keys: &[SortKey]) -> Vec<SortKey> {
+    sort_keys
+        .iter()
+        .map(|key| SortKey {
+            column: key.column.clone(),
+            ascending: !key.ascending,
+            nulls_first: !key.nulls_first,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::Name;
+    use datafusion::arrow::datatypes::{DataType, Field as ArrowField};
+    use indexmap::IndexMap;
+
+    fn test_schema() -> ArrowSchema {
+        ArrowSchema::new(vec![
+            ArrowField::new("id", DataType::Int64, false),
+            ArrowField::new("rank", DataType::Int64, true),
+        ])
+    }
+
+    fn row(id: &str, rank: Option<i64>) -> Value {
+        let mut map = IndexMap::new();
+        map.insert(Name::new("id"), Value::String(id.to_string()));
+        map.insert(
+            Name::new("rank"),
+            rank.map(|r| Value::Number(r.into())).unwrap_or(Value::Null),
+        );
+        Value::Object(map)
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let keys = vec![SortKey::asc("rank"), SortKey::asc("id")];
+        let cursor = Cursor::for_row(&row("7", Some(3)), &keys).unwrap();
+        let decoded = Cursor::decode(&cursor.encode()).unwrap();
+        assert_eq!(decoded, cursor);
+    }
+
+    #[test]
+    fn test_invalid_cursor_is_rejected() {
+        assert!(Cursor::decode("not a cursor!").is_err());
+    }
+
+    #[test]
+    fn test_after_predicate_single_key() {
+        let keys = vec![SortKey::asc("id")];
+        let cursor = Cursor::for_row(&row("7", None), &keys).unwrap();
+        let expr = cursor.after_predicate(&keys, &test_schema()).unwrap();
+        assert_eq!(expr, ident("id").gt(lit(7i64)).or(ident("id").is_null()));
+    }
+
+    #[test]
+    fn test_after_predicate_compound_key() {
+        let keys = vec![
+            SortKey {
+                column: "rank".to_string(),
+                ascending: false,
+                nulls_first: true,
+            },
+            SortKey::asc("id"),
+        ];
+        let cursor = Cursor::for_row(&row("7", Some(3)), &keys).unwrap();
+        let expr = cursor.after_predicate(&keys, &test_schema()).unwrap();
+        let expected = ident("rank").lt(lit(3i64)).or(ident("rank")
+            .eq(lit(3i64))
+            .and(ident("id").gt(lit(7i64)).or(ident("id").is_null())));
+        assert_eq!(expr, expected);
+    }
+
+    #[test]
+    fn test_cursor_must_match_order_by() {
+        let cursor = Cursor::for_row(&row("7", Some(3)), &[SortKey::asc("id")]).unwrap();
+        let result = cursor.after_predicate(&[SortKey::asc("rank"), SortKey::asc("id")], &test_schema());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_reverse_sort_keys() {
+        let reversed = reverse_sort_keys(&[SortKey::asc("id")]);
+        assert!(!reversed[0].ascending);
+        assert!(reversed[0].nulls_first);
+    }
+}
@@ -0,0 +1,83 @@
+//! Planning fix for scans that prune every file
+//!
+//! When a filter on a Delta table rules out every data file, the scan has no
+//! file groups and reports zero output partitions. DataFusion 41 then refuses
+//! to run the plan: operators needing a single partition (sorts, limits) are
+//! not given a `CoalescePartitionsExec` for a zero-partition input, and
+//! `SanityCheckPlan` rejects the result. A query for keys outside every file's
+//! statistics would fail instead of returning no rows.
+//!
+//! [`EmptyPartitionsRule`] runs before the built-in physical optimizer rules
+//! and replaces any subtree with zero output partitions (which can produce no
+//! rows) by an `EmptyExec` of the same schema.
+
+use datafusion::common::tree_node::{Transformed, TreeNode, TreeNodeRecursion};
+use datafusion::config::ConfigOptions;
+use datafusion::error::Result;
+use datafusion::execution::session_state::SessionStateBuilder;
+use datafusion::physical_optimizer::optimizer::PhysicalOptimizer;
+use datafusion::physical_optimizer::PhysicalOptimizerRule;
+use datafusion::physical_plan::empty::EmptyExec;
+use datafusion::physical_plan::{ExecutionPlan, ExecutionPlanProperties};
+use datafusion::prelude::SessionContext;
+use std::sync::Arc;
+
+/// Replaces zero-partition subtrees with an `EmptyExec`
+#[derive(Debug, Default)]
+pub struct EmptyPartitionsRule;
+
+impl PhysicalOptimizerRule for EmptyPartitionsRule {
+    fn optimize(&self, plan: Arc<dyn ExecutionPlan>, _config: &ConfigOptions) -> Result<Arc<dyn ExecutionPlan>> {
+        plan.transform_down(|node| {
+            if node.output_partitioning().partition_count() == 0 {
+                let empty: Arc<dyn ExecutionPlan> = Arc::new(EmptyExec::new(node.schema()));
+                Ok(Transformed::new(empty, true, TreeNodeRecursion::Jump))
+            } else {
+                Ok(Transformed::no(node))
+            }
+        })
+        .map(|transformed| transformed.data)
+    }
+
+    fn name(&self) -> &str {
+        "empty_partitions"
+    }
+
+    fn schema_check(&self) -> bool {
+        true
+    }
+}
+
+/// Create a DataFusion context with [`EmptyPartitionsRule`] installed
+pub fn new_session_context() -> SessionContext {
+    let mut rules = PhysicalOptimizer::new().rules;
+    rules.insert(0, Arc::new(EmptyPartitionsRule));
+
+    let state = SessionStateBuilder::new()
+        .with_default_features()
+        .with_physical_optimizer_rules(rules)
+        .build();
+    SessionContext::new_with_state(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::datatypes::{DataType, Field, Schema};
+    use datafusion::physical_plan::memory::MemoryExec;
+    use datafusion::physical_plan::sorts::sort::SortExec;
+
+    #[test]
+    fn test_zero_partition_subtrees_become_empty() {
+        let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)]));
+        // No partitions at all, like a scan whose files were all pruned
+        let pruned = Arc::new(MemoryExec::try_new(&[], schema.clone(), None).unwrap());
+        let sort = Arc::new(SortExec::new(vec![], pruned));
+
+        let optimized = EmptyPartitionsRule.optimize(sort, &ConfigOptions::default()).unwrap();
+        let child = &optimized.children()[0];
+        assert!(child.as_any().is::<EmptyExec>());
+        assert_eq!(child.output_partitioning().partition_count(), 1);
+        assert_eq!(child.schema(), schema);
+    }
+}
@@ -0,0 +1,550 @@
+//! Typed filter input objects for list queries
+//!
+//! This module generates a `XFilter` input type per entity from its Arrow schema
+//! and translates filter arguments into DataFusion predicates, so they can be
+//! pushed down into the Delta (or CSV) scan.
+
+use crate::config::EntityConfig;
+use crate::error::{NouninatorError, Result};
+use crate::schema::scalars::{BIG_INT, BYTES, DECIMAL, DURATION, LOCAL_DATE_TIME, TIME};
+use crate::schema::temporal::parse_duration;
+use crate::schema::type_mapping::arrow_to_graphql_type;
+
+use async_graphql::dynamic::{InputObject, InputValue, TypeRef};
+use async_graphql::Value;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use datafusion::arrow::datatypes::{
+    DataType as ArrowDataType, IntervalDayTime, IntervalMonthDayNano, IntervalUnit, Schema as ArrowSchema,
+    TimeUnit,
+};
+use datafusion::common::ScalarValue;
+use datafusion::functions::expr_fn::starts_with;
+use datafusion::functions::string::contains;
+use datafusion::prelude::{ident, lit, Expr};
+
+/// Scalar types that get a shared `<Scalar>Filter` operator input
+const FILTERABLE_SCALARS: &[&str] = &[
+    TypeRef::ID,
+    TypeRef::STRING,
+    TypeRef::INT,
+    BIG_INT,
+    TypeRef::FLOAT,
+    DECIMAL,
+    TypeRef::BOOLEAN,
+    BYTES,
+    "Date",
+    "DateTime",
+    LOCAL_DATE_TIME,
+    TIME,
+    DURATION,
+];
+
+/// Name of the generated filter input type for an entity (e.g. `CustomerFilter`)
+pub fn filter_type_name(graphql_name: &str) -> String {
+    format!("{}Filter", graphql_name)
+}
+
+/// Name of the shared operator input type for a scalar (e.g. `StringFilter`)
+fn scalar_filter_type_name(scalar: &str) -> String {
+    format!("{}Filter", scalar)
+}
+
+/// Register the shared per-scalar operator inputs (`StringFilter`, `IntFilter`, ...)
+pub fn register_filter_inputs() -> Vec<InputObject> {
+    FILTERABLE_SCALARS
+        .iter()
+        .map(|scalar| scalar_filter_input(scalar))
+        .collect()
+}
+
+/// Build the operator input object for a single scalar type
+///
+/// # Operators
+///
+/// - `eq`, `ne`, `in`, `isNull` - all scalars
+/// - `gt`, `gte`, `lt`, `lte` - ordered scalars (everything except `ID`, `Boolean` and `Bytes`)
+/// - `contains`, `startsWith` - `String` only
+fn scalar_filter_input(scalar: &str) -> InputObject {
+    let mut input = InputObject::new(scalar_filter_type_name(scalar))
+        .description(format!("Comparison operators for {} columns", scalar))
+        .field(InputValue::new("eq", TypeRef::named(scalar)))
+        .field(InputValue::new("ne", TypeRef::named(scalar)));
+
+    if scalar != TypeRef::BOOLEAN {
+        input = input.field(InputValue::new("in", TypeRef::named_nn_list(scalar)));
+    }
+
+    if ![TypeRef::BOOLEAN, TypeRef::ID, BYTES].contains(&scalar) {
+        for op in ["gt", "gte", "lt", "lte"] {
+            input = input.field(InputValue::new(op, TypeRef::named(scalar)));
+        }
+    }
+
+    if scalar == TypeRef::STRING {
+        input = input
+            .field(InputValue::new("contains", TypeRef::named(TypeRef::STRING)))
+            .field(InputValue::new("startsWith", TypeRef::named(TypeRef::STRING)));
+    }
+
+    input.field(InputValue::new("isNull", TypeRef::named(TypeRef::BOOLEAN)))
+}
+
+/// Build the `XFilter` input object for an entity
+///
+/// Every column with a filterable GraphQL type gets a field referencing the
+/// shared operator input for its scalar, plus `and`/`or`/`not` composition.
+pub fn build_filter_input(entity: &EntityConfig, arrow_schema: &ArrowSchema) -> InputObject {
+    let type_name = filter_type_name(&entity.graphql_name);
+    let mut input = InputObject::new(&type_name)
+        .description(format!("Filter conditions for {}", entity.graphql_name));
+
+    for field in arrow_schema.fields() {
+        let Some(type_ref) = arrow_to_graphql_type(field.name(), field.data_type(), true) else {
+            continue;
+        };
+
+        let scalar = type_ref.type_name();
+        if FILTERABLE_SCALARS.contains(&scalar) {
+            input = input.field(InputValue::new(
+                field.name(),
+                TypeRef::named(scalar_filter_type_name(scalar)),
+            ));
+        }
+    }
+
+    input
+        .field(InputValue::new("and", TypeRef::named_nn_list(&type_name)))
+        .field(InputValue::new("or", TypeRef::named_nn_list(&type_name)))
+        .field(InputValue::new("not", TypeRef::named(&type_name)))
+}
+
+/// Translate a filter argument into a DataFusion predicate
+///
+/// # Arguments
+///
+/// * `filter` - The `XFilter` argument value
+/// * `schema` - Arrow schema of the table being filtered (used to type literals)
+///
+/// # Returns
+///
+/// `Some(Expr)` with the combined predicate, or `None` if the filter has no conditions
+pub fn filter_to_expr(filter: &Value, schema: &ArrowSchema) -> Result<Option<Expr>> {
+    let Value::Object(conditions) = filter else {
+        if matches!(filter, Value::Null) {
+            return Ok(None);
+        }
+        return Err(NouninatorError::SchemaGeneration(
+            "Filter must be an object".to_string(),
+        ));
+    };
+
+    let mut predicates = Vec::new();
+
+    for (key, value) in conditions {
+        if matches!(value, Value::Null) {
+            continue;
+        }
+
+        let predicate = match key.as_str() {
+            "and" => list_to_exprs(value, schema)?.into_iter().reduce(Expr::and),
+            "or" => list_to_exprs(value, schema)?.into_iter().reduce(Expr::or),
+            "not" => filter_to_expr(value, schema)?.map(|expr| !expr),
+            column => column_filter_to_expr(column, value, schema)?,
+        };
+
+        if let Some(predicate) = predicate {
+            predicates.push(predicate);
+        }
+    }
+
+    Ok(predicates.into_iter().reduce(Expr::and))
+}
+
+/// Translate a list of nested filters (for `and`/`or`)
+fn list_to_exprs(value: &Value, schema: &ArrowSchema) -> Result<Vec<Expr>> {
+    let Value::List(filters) = value else {
+        return Err(NouninatorError::SchemaGeneration(
+            "'and'/'or' must be a list of filters".to_string(),
+        ));
+    };
+
+    let mut exprs = Vec::new();
+    for filter in filters {
+        if let Some(expr) = filter_to_expr(filter, schema)? {
+            exprs.push(expr);
+        }
+    }
+    Ok(exprs)
+}
+
+/// Translate the operators for a single column
+fn column_filter_to_expr(column: &str, operators: &Value, schema: &ArrowSchema) -> Result<Option<Expr>> {
+    let field = schema.field_with_name(column).map_err(|_| {
+        NouninatorError::SchemaGeneration(format!("Unknown filter column '{}'", column))
+    })?;
+    let data_type = field.data_type();
+
+    let Value::Object(operators) = operators else {
+        return Err(NouninatorError::SchemaGeneration(format!(
+            "Filter for column '{}' must be an object",
+            column
+        )));
+    };
+
+    let mut predicates = Vec::new();
+
+    for (op, value) in operators {
+        if matches!(value, Value::Null) {
+            continue;
+        }
+
+        let column_expr = ident(column);
+        let predicate = match op.as_str() {
+            "eq" => column_expr.eq(value_to_literal(value, data_type)?),
+            "ne" => column_expr.not_eq(value_to_literal(value, data_type)?),
+            "gt" => column_expr.gt(value_to_literal(value, data_type)?),
+            "gte" => column_expr.gt_eq(value_to_literal(value, data_type)?),
+            "lt" => column_expr.lt(value_to_literal(value, data_type)?),
+            "lte" => column_expr.lt_eq(value_to_literal(value, data_type)?),
+            "in" => {
+                let Value::List(values) = value else {
+                    return Err(NouninatorError::SchemaGeneration(format!(
+                        "'in' for column '{}' must be a list",
+                        column
+                    )));
+                };
+                if values.is_empty() {
+                    lit(false)
+                } else {
+                    let literals = values
+                        .iter()
+                        .map(|v| value_to_literal(v, data_type))
+                        .collect::<Result<Vec<_>>>()?;
+                    column_expr.in_list(literals, false)
+                }
+            }
+            "isNull" => match value {
+                Value::Boolean(true) => column_expr.is_null(),
+                Value::Boolean(false) => column_expr.is_not_null(),
+                _ => {
+                    return Err(NouninatorError::SchemaGeneration(format!(
+                        "'isNull' for column '{}' must be a boolean",
+                        column
+                    )))
+                }
+            },
+            "contains" => contains().call(vec![column_expr, lit(string_operand(op, value)?)]),
+            "startsWith" => starts_with(column_expr, lit(string_operand(op, value)?)),
+            other => {
+                return Err(NouninatorError::SchemaGeneration(format!(
+                    "Unknown filter operator '{}' for column '{}'",
+                    other, column
+                )))
+            }
+        };
+
+        predicates.push(predicate);
+    }
+
+    Ok(predicates.into_iter().reduce(Expr::and))
+}
+
+/// Build an equality predicate for a key lookup
+///
+/// The value is parsed into the column's Arrow type rather than spliced into
+/// SQL, so user-supplied IDs can never alter the query.
+///
+/// # Arguments
+///
+/// * `column` - Key column name
+/// * `value` - The lookup argument (an `ID`, so usually a string)
+/// * `schema` - Arrow schema of the table (used to type the literal)
+pub fn key_predicate(column: &str, value: &Value, schema: &ArrowSchema) -> Result<Expr> {
+    let field = schema.field_with_name(column).map_err(|_| {
+        NouninatorError::SchemaGeneration(format!("Unknown key column '{}'", column))
+    })?;
+    Ok(ident(column).eq(value_to_literal(value, field.data_type())?))
+}
+
+fn string_operand<'a>(op: &str, value: &'a Value) -> Result<&'a str> {
+    match value {
+        Value::String(s) => Ok(s.as_str()),
+        _ => Err(NouninatorError::SchemaGeneration(format!(
+            "'{}' requires a string value",
+            op
+        ))),
+    }
+}
+
+/// Convert a GraphQL argument value into a literal of the column's Arrow type
+pub fn value_to_literal(value: &Value, data_type: &ArrowDataType) -> Result<Expr> {
+    Ok(lit(value_to_scalar(value, data_type)?))
+}
+
+/// Convert a GraphQL value into a `ScalarValue` of the given Arrow type
+///
+/// Strings are parsed with Arrow's cast kernels, so `"42"` becomes an `Int64`
+/// for an integer column and RFC 3339 strings become timestamps. Binary
+/// columns take base64 strings, and numbers for decimal columns are parsed
+/// from their decimal representation rather than through a float. Values that
+/// cannot be represented in the target type are rejected. Duration and
+/// interval columns take ISO 8601 durations (`PT1H30M`).
+pub fn value_to_scalar(value: &Value, data_type: &ArrowDataType) -> Result<ScalarValue> {
+    let scalar = match value {
+        Value::Null => return ScalarValue::try_from(data_type).map_err(Into::into),
+        Value::String(s) if matches!(data_type, ArrowDataType::Duration(_) | ArrowDataType::Interval(_)) => {
+            return duration_to_scalar(s, data_type)
+                .ok_or_else(|| NouninatorError::SchemaGeneration(format!(
+                    "Invalid value {} for type {:?}: expected an ISO 8601 duration",
+                    value, data_type
+                )));
+        }
+        Value::String(s) if is_binary(data_type) => {
+            let bytes = STANDARD.decode(s).map_err(|e| {
+                NouninatorError::SchemaGeneration(format!("Invalid base64 value {}: {}", value, e))
+            })?;
+            match data_type {
+                ArrowDataType::FixedSizeBinary(size) => {
+                    if bytes.len() != *size as usize {
+                        return Err(NouninatorError::SchemaGeneration(format!(
+                            "Invalid value {} for type {:?}: expected {} bytes",
+                            value, data_type, size
+                        )));
+                    }
+                    ScalarValue::FixedSizeBinary(*size, Some(bytes))
+                }
+                _ => ScalarValue::Binary(Some(bytes)),
+            }
+        }
+        Value::Number(n) if matches!(data_type, ArrowDataType::Decimal128(_, _) | ArrowDataType::Decimal256(_, _)) => {
+            return ScalarValue::try_from_string(n.to_string(), data_type)
+                .map_err(|e| invalid_literal(value, data_type, e));
+        }
+        Value::String(s) => return ScalarValue::try_from_string(s.clone(), data_type)
+            .map_err(|e| invalid_literal(value, data_type, e)),
+        Value::Enum(name) => ScalarValue::Utf8(Some(name.to_string())),
+        Value::Boolean(b) => ScalarValue::Boolean(Some(*b)),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                ScalarValue::Int64(Some(i))
+            } else if let Some(u) = n.as_u64() {
+                ScalarValue::UInt64(Some(u))
+            } else {
+                ScalarValue::Float64(n.as_f64())
+            }
+        }
+        _ => {
+            return Err(NouninatorError::SchemaGeneration(format!(
+                "Unsupported literal value {} for type {:?}",
+                value, data_type
+            )))
+        }
+    };
+
+    scalar
+        .cast_to(data_type)
+        .map_err(|e| invalid_literal(value, data_type, e))
+}
+
+/// Convert an ISO 8601 duration to a duration or interval of the given type
+///
+/// Returns `None` if the value is malformed or does not fit the type (e.g.
+/// months for a duration column, or hours for a year-month interval).
+fn duration_to_scalar(value: &str, data_type: &ArrowDataType) -> Option<ScalarValue> {
+    let duration = parse_duration(value)?;
+    let scalar = match data_type {
+        ArrowDataType::Duration(unit) => {
+            let nanos = duration.total_nanos()?;
+            match unit {
+                TimeUnit::Second if nanos % 1_000_000_000 == 0 => {
+                    ScalarValue::DurationSecond(Some(nanos / 1_000_000_000))
+                }
+                TimeUnit::Millisecond if nanos % 1_000_000 == 0 => {
+                    ScalarValue::DurationMillisecond(Some(nanos / 1_000_000))
+                }
+                TimeUnit::Microsecond if nanos % 1_000 == 0 => {
+                    ScalarValue::DurationMicrosecond(Some(nanos / 1_000))
+                }
+                TimeUnit::Nanosecond => ScalarValue::DurationNanosecond(Some(nanos)),
+                _ => return None,
+            }
+        }
+        ArrowDataType::Interval(IntervalUnit::YearMonth) if duration.days == 0 && duration.nanos == 0 => {
+            ScalarValue::IntervalYearMonth(Some(duration.months.try_into().ok()?))
+        }
+        ArrowDataType::Interval(IntervalUnit::DayTime)
+            if duration.months == 0 && duration.nanos % 1_000_000 == 0 =>
+        {
+            ScalarValue::IntervalDayTime(Some(IntervalDayTime::new(
+                duration.days.try_into().ok()?,
+                (duration.nanos / 1_000_000).try_into().ok()?,
+            )))
+        }
+        ArrowDataType::Interval(IntervalUnit::MonthDayNano) => {
+            ScalarValue::IntervalMonthDayNano(Some(IntervalMonthDayNano::new(
+                duration.months.try_into().ok()?,
+                duration.days.try_into().ok()?,
+                duration.nanos,
+            )))
+        }
+        _ => return None,
+    };
+    Some(scalar)
+}
+
+fn is_binary(data_type: &ArrowDataType) -> bool {
+    matches!(
+        data_type,
+        ArrowDataType::Binary
+            | ArrowDataType::LargeBinary
+            | ArrowDataType::FixedSizeBinary(_)
+            | ArrowDataType::BinaryView
+    )
+}
+
+fn invalid_literal(
+    value: &Value,
+    data_type: &ArrowDataType,
+    err: datafusion::error::DataFusionError,
+) -> NouninatorError {
+    NouninatorError::SchemaGeneration(format!(
+        "Invalid value {} for type {:?}: {}",
+        value, data_type, err
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::Name;
+    use datafusion::arrow::datatypes::{Field as ArrowField, TimeUnit};
+    use indexmap::IndexMap;
+
+    fn test_schema() -> ArrowSchema {
+        ArrowSchema::new(vec![
+            ArrowField::new("id", ArrowDataType::Int64, false),
+            ArrowField::new("word", ArrowDataType::Utf8, false),
+            ArrowField::new("score", ArrowDataType::Float64, true),
+            ArrowField::new(
+                "created_at",
+                ArrowDataType::Timestamp(TimeUnit::Nanosecond, None),
+                true,
+            ),
+        ])
+    }
+
+    fn object(entries: Vec<(&str, Value)>) -> Value {
+        let mut map = IndexMap::new();
+        for (key, value) in entries {
+            map.insert(Name::new(key), value);
+        }
+        Value::Object(map)
+    }
+
+    #[test]
+    fn test_empty_filter_is_none() {
+        let expr = filter_to_expr(&object(vec![]), &test_schema()).unwrap();
+        assert!(expr.is_none());
+    }
+
+    #[test]
+    fn test_eq_casts_to_column_type() {
+        let filter = object(vec![(
+            "id",
+            object(vec![("eq", Value::String("42".to_string()))]),
+        )]);
+        let expr = filter_to_expr(&filter, &test_schema()).unwrap().unwrap();
+        assert_eq!(expr, ident("id").eq(lit(42i64)));
+    }
+
+    #[test]
+    fn test_operators_are_combined_with_and() {
+        let filter = object(vec![(
+            "score",
+            object(vec![
+                ("gte", Value::Number(1.into())),
+                ("lt", Value::Number(5.into())),
+            ]),
+        )]);
+        let expr = filter_to_expr(&filter, &test_schema()).unwrap().unwrap();
+        assert_eq!(
+            expr,
+            ident("score").gt_eq(lit(1.0f64)).and(ident("score").lt(lit(5.0f64)))
+        );
+    }
+
+    #[test]
+    fn test_or_and_not_composition() {
+        let filter = object(vec![
+            (
+                "or",
+                Value::List(vec![
+                    object(vec![("word", object(vec![("eq", Value::String("cat".into()))]))]),
+                    object(vec![("word", object(vec![("startsWith", Value::String("do".into()))]))]),
+                ]),
+            ),
+            ("not", object(vec![("score", object(vec![("isNull", Value::Boolean(true))]))])),
+        ]);
+        let expr = filter_to_expr(&filter, &test_schema()).unwrap().unwrap();
+        let expected = ident("word")
+            .eq(lit("cat"))
+            .or(starts_with(ident("word"), lit("do")))
+            .and(!ident("score").is_null());
+        assert_eq!(expr, expected);
+    }
+
+    #[test]
+    fn test_in_list() {
+        let filter = object(vec![(
+            "id",
+            object(vec![(
+                "in",
+                Value::List(vec![Value::String("1".into()), Value::String("2".into())]),
+            )]),
+        )]);
+        let expr = filter_to_expr(&filter, &test_schema()).unwrap().unwrap();
+        assert_eq!(expr, ident("id").in_list(vec![lit(1i64), lit(2i64)], false));
+    }
+
+    #[test]
+    fn test_unknown_column_is_rejected() {
+        let filter = object(vec![("missing", object(vec![("eq", Value::Number(1.into()))]))]);
+        assert!(filter_to_expr(&filter, &test_schema()).is_err());
+    }
+
+    #[test]
+    fn test_key_predicate_uses_column_type() {
+        let expr = key_predicate("id", &Value::String("7".to_string()), &test_schema()).unwrap();
+        assert_eq!(expr, ident("id").eq(lit(7i64)));
+
+        let injected = "x' OR '1'='1";
+        let expr = key_predicate("word", &Value::String(injected.to_string()), &test_schema()).unwrap();
+        assert_eq!(expr, ident("word").eq(lit(injected)));
+    }
+
+    #[test]
+    fn test_key_predicate_rejects_uncastable_value() {
+        let value = Value::String("1 OR 1=1".to_string());
+        assert!(key_predicate("id", &value, &test_schema()).is_err());
+    }
+
+    #[test]
+    fn test_value_to_scalar_timestamp() {
+        let scalar = value_to_scalar(
+            &Value::String("2024-01-15T10:00:00Z".to_string()),
+            &ArrowDataType::Timestamp(TimeUnit::Nanosecond, None),
+        )
+        .unwrap();
+        assert_eq!(
+            scalar,
+            ScalarValue::TimestampNanosecond(Some(1_705_312_800_000_000_000), None)
+        );
+    }
+
+    #[test]
+    fn test_value_to_scalar_rejects_invalid_integer() {
+        let result = value_to_scalar(&Value::String("abc".to_string()), &ArrowDataType::Int64);
+        assert!(result.is_err());
+    }
+}
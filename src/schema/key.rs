@@ -0,0 +1,206 @@
+//! Primary key arguments and predicates
+//!
+//! A single-column key is passed as one `ID!` argument named after the column.
+//! A composite key (`primary_key = ["date", "region", "sku"]`) gets one
+//! argument per column, typed from the Arrow schema (`date: Date!, region:
+//! String!, sku: String!`), and rows match only when every column matches.
+//! Where a list of keys is accepted (`X_by_ids`), composite keys are passed as
+//! generated `XKey` input objects.
+
+use crate::config::EntityConfig;
+use crate::error::Result;
+use crate::schema::filter::key_predicate;
+use crate::schema::resolver::parent_field;
+use crate::schema::type_mapping::arrow_to_graphql_type;
+
+use async_graphql::dynamic::{Field, InputObject, InputValue, Object, ResolverContext, TypeRef};
+use async_graphql::Value;
+use datafusion::arrow::datatypes::Schema as ArrowSchema;
+use datafusion::logical_expr::utils::conjunction;
+use datafusion::prelude::Expr;
+
+/// A primary key value: one `(column, value)` pair per key column, in key order
+pub type KeyValues = Vec<(String, Value)>;
+
+/// Name of the generated key input type for an entity (e.g. `DailySaleKey`)
+pub fn key_type_name(graphql_name: &str) -> String {
+    format!("{}Key", graphql_name)
+}
+
+/// Name of the type of each `X_by_ids` result (e.g. `WordLookup`)
+pub fn lookup_type_name(graphql_name: &str) -> String {
+    format!("{}Lookup", graphql_name)
+}
+
+/// Build the `XLookup` type: one position of an `X_by_ids` result
+///
+/// async-graphql's dynamic schema cannot put `null` entries in a list of
+/// objects, so each position wraps its row in a nullable `node`.
+pub fn build_lookup_type(graphql_name: &str) -> Object {
+    Object::new(lookup_type_name(graphql_name))
+        .description(format!("The {} found for one requested key", graphql_name))
+        .field(parent_field("node", TypeRef::named(graphql_name)))
+}
+
+/// Add an entity's primary key arguments to a field
+pub fn with_key_arguments(field: Field, entity: &EntityConfig, schema: &ArrowSchema) -> Field {
+    entity.primary_key.columns().iter().fold(field, |field, column| {
+        field.argument(InputValue::new(column, key_column_type(entity, column, schema)))
+    })
+}
+
+/// Type name of one element of a list of keys (`ID` or `XKey`)
+pub fn key_list_item_type_name(entity: &EntityConfig) -> String {
+    if entity.primary_key.is_composite() {
+        key_type_name(&entity.graphql_name)
+    } else {
+        TypeRef::ID.to_string()
+    }
+}
+
+/// Build the `XKey` input type of an entity with a composite primary key
+pub fn build_key_input(entity: &EntityConfig, schema: &ArrowSchema) -> Option<InputObject> {
+    if !entity.primary_key.is_composite() {
+        return None;
+    }
+    let input = InputObject::new(key_type_name(&entity.graphql_name))
+        .description(format!("The primary key of a {} row", entity.graphql_name));
+    Some(entity.primary_key.columns().iter().fold(input, |input, column| {
+        input.field(InputValue::new(column, key_column_type(entity, column, schema)))
+    }))
+}
+
+/// Argument type of a key column: `ID!` for single-column keys, the column's
+/// own type for composite keys
+fn key_column_type(entity: &EntityConfig, column: &str, schema: &ArrowSchema) -> TypeRef {
+    match schema.field_with_name(column) {
+        Ok(arrow_field) if entity.primary_key.is_composite() => {
+            arrow_to_graphql_type(column, arrow_field.data_type(), false)
+                .unwrap_or_else(|| TypeRef::named_nn(TypeRef::ID))
+        }
+        _ => TypeRef::named_nn(TypeRef::ID),
+    }
+}
+
+/// Read the primary key arguments of the field being resolved
+pub fn key_from_args(
+    ctx: &ResolverContext<'_>,
+    entity: &EntityConfig,
+) -> std::result::Result<KeyValues, async_graphql::Error> {
+    entity
+        .primary_key
+        .columns()
+        .iter()
+        .map(|column| {
+            let value = ctx
+                .args
+                .try_get(column)
+                .map_err(|_| format!("Primary key '{}' argument missing", column))?;
+            Ok((column.clone(), value.as_value().clone()))
+        })
+        .collect()
+}
+
+/// Read one element of a list of keys (an `ID` or an `XKey` object)
+pub fn key_from_list_item(
+    item: &Value,
+    entity: &EntityConfig,
+    argument: &str,
+) -> std::result::Result<KeyValues, async_graphql::Error> {
+    match entity.primary_key.columns() {
+        [column] => Ok(vec![(column.clone(), item.clone())]),
+        _ => key_from_row(item, entity, argument),
+    }
+}
+
+/// Read the primary key columns of an input object passed as `argument`
+pub fn key_from_row(
+    input: &Value,
+    entity: &EntityConfig,
+    argument: &str,
+) -> std::result::Result<KeyValues, async_graphql::Error> {
+    let Value::Object(obj) = input else {
+        return Err(format!("'{}' must be an object", argument).into());
+    };
+    entity
+        .primary_key
+        .columns()
+        .iter()
+        .map(|column| match obj.get(column.as_str()) {
+            Some(value) if !matches!(value, Value::Null) => Ok((column.clone(), value.clone())),
+            _ => Err(format!("'{}' must set the primary key '{}'", argument, column).into()),
+        })
+        .collect()
+}
+
+/// Build a predicate matching the row with the given key
+///
+/// Each value is converted to its column's Arrow type, as in [`key_predicate`].
+pub fn key_values_predicate(key: &[(String, Value)], schema: &ArrowSchema) -> Result<Expr> {
+    let predicates = key
+        .iter()
+        .map(|(column, value)| key_predicate(column, value, schema))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(conjunction(predicates).unwrap_or(Expr::Literal(true.into())))
+}
+
+/// Describe a key for messages (`id "5"`, or `date "2024-01-01", region "EU"`)
+pub fn describe_key(key: &[(String, Value)]) -> String {
+    key.iter()
+        .map(|(column, value)| format!("{} {}", column, value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::datatypes::{DataType, Field as ArrowField};
+    use indexmap::IndexMap;
+
+    fn test_schema() -> ArrowSchema {
+        ArrowSchema::new(vec![
+            ArrowField::new("date", DataType::Date32, false),
+            ArrowField::new("region", DataType::Utf8, false),
+            ArrowField::new("units", DataType::Int64, true),
+        ])
+    }
+
+    fn composite_entity() -> EntityConfig {
+        EntityConfig {
+            table: "daily_sales".to_string(),
+            graphql_name: "DailySale".to_string(),
+            primary_key: vec!["date".to_string(), "region".to_string()].into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_key_from_row_requires_every_column() {
+        let mut obj = IndexMap::new();
+        obj.insert(async_graphql::Name::new("date"), Value::String("2024-01-01".to_string()));
+        obj.insert(async_graphql::Name::new("units"), Value::Number(3.into()));
+        let entity = composite_entity();
+        assert!(key_from_row(&Value::Object(obj.clone()), &entity, "input").is_err());
+
+        obj.insert(async_graphql::Name::new("region"), Value::String("EU".to_string()));
+        let key = key_from_row(&Value::Object(obj), &entity, "input").unwrap();
+        assert_eq!(key.len(), 2);
+        assert_eq!(describe_key(&key), r#"date "2024-01-01", region "EU""#);
+    }
+
+    #[test]
+    fn test_key_values_predicate_matches_all_columns() {
+        let key = vec![
+            ("date".to_string(), Value::String("2024-01-01".to_string())),
+            ("region".to_string(), Value::String("EU".to_string())),
+        ];
+        let expr = key_values_predicate(&key, &test_schema()).unwrap();
+        let rendered = expr.to_string();
+        assert!(rendered.contains("date = Date32"), "{}", rendered);
+        assert!(rendered.contains(" AND "), "{}", rendered);
+
+        let bad = vec![("date".to_string(), Value::String("yesterday".to_string()))];
+        assert!(key_values_predicate(&bad, &test_schema()).is_err());
+    }
+}
@@ -0,0 +1,432 @@
+//! Batched row lookups for nested and repeated fields
+//!
+//! Resolving `customer` for each of 100 orders would otherwise run 100 separate
+//! `WHERE customer_id = ...` queries. Instead, lookup resolvers go through an
+//! async-graphql `DataLoader`: keys requested while the same query is being
+//! resolved are grouped by (table, columns, projected columns) and each group is
+//! fetched with a single `WHERE column IN (...)` DataFusion query, then fanned
+//! back out to the waiting resolvers. Keys spanning several columns (composite
+//! primary keys) are fetched with `WHERE (a = ... AND b = ...) OR ...` instead.
+//! Paged lookups (`many` relations with `limit`/`offset`) number each key's
+//! rows with `ROW_NUMBER() OVER (PARTITION BY ...)` so only the requested page
+//! of every key is read back.
+
+use crate::schema::filter::value_to_scalar;
+use crate::schema::registry::{TableRegistry, TableVersion};
+use crate::schema::resolver::record_batch_to_graphql_value;
+use crate::schema::temporal::parse_timezone;
+
+use async_graphql::dataloader::Loader;
+use async_graphql::Value;
+use datafusion::arrow::datatypes::Schema as ArrowSchema;
+use datafusion::common::ScalarValue;
+use datafusion::logical_expr::utils::{conjunction, disjunction};
+use datafusion::logical_expr::window_function::row_number;
+use datafusion::logical_expr::ExprFunctionExt;
+use datafusion::prelude::{ident, lit, Expr, SessionContext};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Which rows and columns a lookup reads
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LookupGroup {
+    /// Registered table name
+    pub table: String,
+    /// Columns matched against the key
+    pub columns: Vec<String>,
+    /// Primary key columns of the table (rows are returned in primary key order)
+    pub primary_key: Vec<String>,
+    /// Snapshot version to read (`None` for the currently served version)
+    pub version: Option<i64>,
+    /// Columns to read
+    pub projection: Vec<String>,
+    /// Zone to render `DateTime` values in (`None` for each column's zone)
+    pub timezone: Option<String>,
+    /// The entity's bound row filter, applied to every lookup
+    pub row_filter: Option<Expr>,
+    /// Rows to return per key as `(offset, limit)` (`None` for every row)
+    pub page: Option<(usize, usize)>,
+}
+
+/// A single key to look up
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LookupKey {
+    /// Table/columns/projection shared by keys that can be batched together
+    pub group: Arc<LookupGroup>,
+    /// Key values (one per group column), normalized to the column types' string form
+    pub values: Vec<String>,
+}
+
+impl LookupKey {
+    /// Build a key, validating each value against its column's Arrow type
+    ///
+    /// `values` are given in the order of the group's columns. Normalizing
+    /// through the column type means `"007"` and `7` share a key for an
+    /// integer column, and values that cannot be represented in the column
+    /// type are rejected before any query runs.
+    pub fn new(group: Arc<LookupGroup>, values: &[&Value], schema: &ArrowSchema) -> crate::error::Result<Self> {
+        if values.len() != group.columns.len() {
+            return Err(crate::error::NouninatorError::SchemaGeneration(format!(
+                "Expected {} key values, got {}",
+                group.columns.len(),
+                values.len()
+            )));
+        }
+        let values = group
+            .columns
+            .iter()
+            .zip(values)
+            .map(|(column, value)| {
+                let field = schema.field_with_name(column).map_err(|_| {
+                    crate::error::NouninatorError::SchemaGeneration(format!("Unknown key column '{}'", column))
+                })?;
+                Ok(value_to_scalar(value, field.data_type())?.to_string())
+            })
+            .collect::<crate::error::Result<Vec<_>>>()?;
+        Ok(Self { group, values })
+    }
+}
+
+/// Column holding each row's position within its key (paged lookups only)
+const ROW_NUMBER_COLUMN: &str = "__row_number";
+
+/// Loads all rows matching a batch of keys
+///
+/// Values are every matching row (in primary key order), or the group's page
+/// of them, so the same loader serves single-object and list relations.
+pub struct RowLoader {
+    ctx: Arc<SessionContext>,
+    registry: Arc<TableRegistry>,
+    queries: AtomicUsize,
+}
+
+impl RowLoader {
+    /// Create a loader running queries against the given DataFusion context
+    ///
+    /// Historical snapshots (for time travel lookups) come from `registry`.
+    pub fn new(ctx: Arc<SessionContext>, registry: Arc<TableRegistry>) -> Self {
+        Self {
+            ctx,
+            registry,
+            queries: AtomicUsize::new(0),
+        }
+    }
+
+    /// Number of queries executed so far
+    pub fn query_count(&self) -> usize {
+        self.queries.load(Ordering::Relaxed)
+    }
+
+    async fn load_group(
+        &self,
+        group: &LookupGroup,
+        keys: Vec<&Vec<String>>,
+    ) -> std::result::Result<HashMap<Vec<String>, Vec<Value>>, String> {
+        let df = match group.version {
+            Some(version) => {
+                let snapshot = self
+                    .registry
+                    .snapshot(&group.table, &TableVersion::Version(version))
+                    .await
+                    .map_err(|e| e.to_string())?;
+                self.ctx.read_table(snapshot)
+            }
+            None => self.ctx.table(group.table.as_str()).await,
+        }
+        .map_err(|e| format!("Query execution failed: {}", e))?;
+
+        let data_types = group
+            .columns
+            .iter()
+            .map(|column| {
+                df.schema()
+                    .as_arrow()
+                    .field_with_name(column)
+                    .map(|field| field.data_type().clone())
+                    .map_err(|_| format!("Unknown key column '{}'", column))
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        // Literals per key, one per group column
+        let literals = keys
+            .into_iter()
+            .map(|values| {
+                values
+                    .iter()
+                    .zip(&data_types)
+                    .map(|(value, data_type)| {
+                        ScalarValue::try_from_string(value.clone(), data_type)
+                            .map(lit)
+                            .map_err(|e| format!("Invalid key '{}': {}", value, e))
+                    })
+                    .collect::<std::result::Result<Vec<_>, _>>()
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        // The key columns are always read so rows can be matched back to keys
+        let mut columns: Vec<&str> = group.projection.iter().map(String::as_str).collect();
+        for column in &group.columns {
+            if !columns.contains(&column.as_str()) {
+                columns.push(column.as_str());
+            }
+        }
+
+        let df = match &group.row_filter {
+            Some(row_filter) => df.filter(row_filter.clone()),
+            None => Ok(df),
+        }
+        .map_err(|e| format!("Query execution failed: {}", e))?;
+
+        let sort: Vec<_> = group.primary_key.iter().map(|column| ident(column).sort(true, false)).collect();
+        let df = df
+            .filter(keys_predicate(&group.columns, literals))
+            .map_err(|e| format!("Query execution failed: {}", e))?;
+
+        // Number each key's rows in primary key order and keep only the page
+        let df = match group.page {
+            Some((offset, limit)) => row_number()
+                .partition_by(group.columns.iter().map(ident).collect())
+                .order_by(sort.clone())
+                .build()
+                .and_then(|row_number| df.window(vec![row_number.alias(ROW_NUMBER_COLUMN)]))
+                .and_then(|df| {
+                    df.filter(
+                        ident(ROW_NUMBER_COLUMN)
+                            .gt(lit(offset as u64))
+                            .and(ident(ROW_NUMBER_COLUMN).lt_eq(lit((offset + limit) as u64))),
+                    )
+                }),
+            None => Ok(df),
+        }
+        .map_err(|e| format!("Query execution failed: {}", e))?;
+
+        let df = df
+            .sort(sort)
+            .and_then(|df| df.select_columns(&columns))
+            .map_err(|e| format!("Query execution failed: {}", e))?;
+
+        tracing::debug!("Executing batched lookup: {}", df.logical_plan().display_indent());
+
+        self.queries.fetch_add(1, Ordering::Relaxed);
+        let batches = df
+            .collect()
+            .await
+            .map_err(|e| format!("Data collection failed: {}", e))?;
+
+        let timezone = group
+            .timezone
+            .as_deref()
+            .map(parse_timezone)
+            .transpose()
+            .map_err(|e| e.to_string())?;
+
+        let mut rows: HashMap<Vec<String>, Vec<Value>> = HashMap::new();
+        for batch in &batches {
+            let key_columns = group
+                .columns
+                .iter()
+                .map(|column| {
+                    batch
+                        .column_by_name(column)
+                        .ok_or_else(|| format!("Missing key column '{}'", column))
+                })
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+
+            for row_idx in 0..batch.num_rows() {
+                let key = key_columns
+                    .iter()
+                    .map(|array| {
+                        ScalarValue::try_from_array(array, row_idx)
+                            .map(|scalar| scalar.to_string())
+                            .map_err(|e| format!("Failed to read key: {}", e))
+                    })
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+                let row = record_batch_to_graphql_value(batch, row_idx, timezone.as_ref())
+                    .map_err(|e| format!("Failed to convert row: {}", e))?;
+                rows.entry(key).or_default().push(row);
+            }
+        }
+
+        Ok(rows)
+    }
+}
+
+/// Predicate matching any of the given keys
+///
+/// Single-column keys use `IN`, which DataFusion can prune files with;
+/// composite keys become a disjunction of per-key conjunctions.
+fn keys_predicate(columns: &[String], keys: Vec<Vec<Expr>>) -> Expr {
+    if let [column] = columns {
+        let literals = keys.into_iter().flatten().collect();
+        return ident(column).in_list(literals, false);
+    }
+
+    let matches = keys.into_iter().map(|literals| {
+        let equalities = columns.iter().zip(literals).map(|(column, literal)| ident(column).eq(literal));
+        conjunction(equalities).unwrap_or(lit(false))
+    });
+    disjunction(matches).unwrap_or(lit(false))
+}
+
+impl Loader<LookupKey> for RowLoader {
+    type Value = Vec<Value>;
+    type Error = String;
+
+    async fn load(
+        &self,
+        keys: &[LookupKey],
+    ) -> std::result::Result<HashMap<LookupKey, Self::Value>, Self::Error> {
+        // One query per (table, columns, projection)
+        let mut groups: HashMap<&Arc<LookupGroup>, Vec<&Vec<String>>> = HashMap::new();
+        for key in keys {
+            groups.entry(&key.group).or_default().push(&key.values);
+        }
+
+        let mut results = HashMap::new();
+        for (group, values) in groups {
+            let mut rows = self.load_group(group, values).await?;
+            for key in keys.iter().filter(|key| &key.group == group) {
+                if let Some(matches) = rows.remove(&key.values) {
+                    results.insert(key.clone(), matches);
+                }
+            }
+        }
+
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::dataloader::DataLoader;
+    use datafusion::arrow::array::{Int64Array, StringArray};
+    use datafusion::arrow::datatypes::{DataType, Field, Schema};
+    use datafusion::arrow::record_batch::RecordBatch;
+    use datafusion::datasource::MemTable;
+
+    fn test_schema() -> Schema {
+        Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("owner_id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+        ])
+    }
+
+    fn test_context() -> Arc<SessionContext> {
+        let schema = Arc::new(test_schema());
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2, 3, 4])),
+                Arc::new(Int64Array::from(vec![10, 10, 20, 30])),
+                Arc::new(StringArray::from(vec!["a", "b", "c", "d"])),
+            ],
+        )
+        .unwrap();
+
+        let ctx = SessionContext::new();
+        ctx.register_table("items", Arc::new(MemTable::try_new(schema, vec![vec![batch]]).unwrap()))
+            .unwrap();
+        Arc::new(ctx)
+    }
+
+    fn group(columns: &[&str]) -> Arc<LookupGroup> {
+        Arc::new(LookupGroup {
+            table: "items".to_string(),
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            primary_key: vec!["id".to_string()],
+            version: None,
+            projection: vec!["id".to_string(), "name".to_string()],
+            timezone: None,
+            row_filter: None,
+            page: None,
+        })
+    }
+
+    fn key(group: &Arc<LookupGroup>, values: &[&str]) -> LookupKey {
+        let values: Vec<Value> = values.iter().map(|v| Value::String(v.to_string())).collect();
+        LookupKey::new(group.clone(), &values.iter().collect::<Vec<_>>(), &test_schema()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_keys_are_batched_per_group() {
+        let loader = DataLoader::new(
+            RowLoader::new(test_context(), Arc::new(TableRegistry::new())),
+            tokio::spawn,
+        );
+        let by_id = group(&["id"]);
+        let by_owner = group(&["owner_id"]);
+
+        let keys = vec![
+            key(&by_id, &["1"]),
+            key(&by_id, &["003"]),
+            key(&by_id, &["99"]),
+            key(&by_owner, &["10"]),
+            key(&by_owner, &["30"]),
+        ];
+        let results = loader.load_many(keys.clone()).await.unwrap();
+
+        // One IN query per group, regardless of the number of keys
+        assert_eq!(loader.loader().query_count(), 2);
+
+        assert_eq!(results[&keys[0]].len(), 1);
+        assert_eq!(results[&keys[1]].len(), 1);
+        assert!(!results.contains_key(&keys[2]));
+        assert_eq!(results[&keys[3]].len(), 2);
+        assert_eq!(results[&keys[4]].len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_composite_keys_match_every_column() {
+        let loader = DataLoader::new(
+            RowLoader::new(test_context(), Arc::new(TableRegistry::new())),
+            tokio::spawn,
+        );
+        let by_owner_and_name = group(&["owner_id", "name"]);
+
+        let keys = vec![
+            key(&by_owner_and_name, &["10", "b"]),
+            key(&by_owner_and_name, &["20", "c"]),
+            key(&by_owner_and_name, &["20", "a"]),
+        ];
+        let results = loader.load_many(keys.clone()).await.unwrap();
+
+        assert_eq!(loader.loader().query_count(), 1);
+        assert_eq!(results[&keys[0]].len(), 1);
+        assert_eq!(results[&keys[1]].len(), 1);
+        assert!(!results.contains_key(&keys[2]));
+    }
+
+    #[tokio::test]
+    async fn test_paged_keys_read_only_their_page() {
+        let loader = DataLoader::new(
+            RowLoader::new(test_context(), Arc::new(TableRegistry::new())),
+            tokio::spawn,
+        );
+        let mut paged = (*group(&["owner_id"])).clone();
+        paged.page = Some((1, 1));
+        let by_owner = Arc::new(paged);
+
+        let keys = vec![key(&by_owner, &["10"]), key(&by_owner, &["20"])];
+        let results = loader.load_many(keys.clone()).await.unwrap();
+
+        // Owner 10 has rows 1 and 2; only the second is in the page
+        assert_eq!(loader.loader().query_count(), 1);
+        let rows = &results[&keys[0]];
+        assert_eq!(rows.len(), 1);
+        match &rows[0] {
+            Value::Object(row) => assert_eq!(row.get("id"), Some(&Value::from("2"))),
+            other => panic!("Expected an object, got {:?}", other),
+        }
+        assert!(!results.contains_key(&keys[1]));
+    }
+
+    #[test]
+    fn test_key_rejects_values_of_the_wrong_type() {
+        let value = Value::String("1 OR 1=1".to_string());
+        assert!(LookupKey::new(group(&["id"]), &[&value], &test_schema()).is_err());
+        assert!(LookupKey::new(group(&["id", "name"]), &[&value], &test_schema()).is_err());
+    }
+}
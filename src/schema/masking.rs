@@ -0,0 +1,191 @@
+//! Column masking
+//!
+//! A column's `mask` is applied to every value of it a caller is sent (entity
+//! fields, changes, aggregate results), unless one of the caller's roles or
+//! scopes has an entry in `role_masks`, in which case the most revealing of
+//! those applies. Values are masked after the row has been read and
+//! converted, as the column's field resolves.
+//!
+//! Masked columns may not appear in `filter`, `orderBy` or `groupBy`
+//! arguments (see `access::authorize_query`): `eq` predicates or a sort order
+//! would reveal what the mask hides.
+//!
+//! `hash` is HMAC-SHA256 under a key generated when the schema is built, so
+//! equal values hash alike for the lifetime of the server but hashes cannot be
+//! matched against hashed guesses elsewhere.
+
+use crate::auth::Principal;
+use crate::config::{ColumnConfig, EntityConfig, Mask};
+use crate::error::{NouninatorError, Result};
+use crate::schema::resolver::{parent_row, resolve_from_parent};
+use crate::schema::type_mapping::arrow_to_graphql_type;
+
+use async_graphql::dynamic::{FieldValue, ResolverContext, TypeRef};
+use async_graphql::Value;
+use datafusion::arrow::datatypes::Schema as ArrowSchema;
+use ring::hmac;
+use ring::rand::SystemRandom;
+
+/// What `redact` replaces values with
+const REDACTED: &str = "[REDACTED]";
+
+/// Characters `partial` leaves visible at the end of longer values
+const PARTIAL_VISIBLE: usize = 4;
+
+/// The mask a caller sees a column through
+pub fn mask_for(column: &ColumnConfig, principal: Option<&Principal>) -> Mask {
+    let role_mask = principal.and_then(|principal| {
+        column
+            .role_masks
+            .iter()
+            .filter(|(role, _)| principal.is_allowed(std::slice::from_ref(*role)))
+            .map(|(_, mask)| *mask)
+            .min()
+    });
+    role_mask.or(column.mask).unwrap_or(Mask::None)
+}
+
+/// Whether a column can be masked to `null` for some caller
+pub fn masks_to_null(column: &ColumnConfig) -> bool {
+    column.mask.iter().chain(column.role_masks.values()).any(|mask| *mask == Mask::Null)
+}
+
+/// Check that every column masked with a text mask holds strings or IDs
+pub fn check_masks(entity: &EntityConfig, schema: &ArrowSchema) -> Result<()> {
+    for (column, config) in &entity.columns {
+        if !config.masks_text() {
+            continue;
+        }
+        let Ok(field) = schema.field_with_name(column) else {
+            continue;
+        };
+        let is_text = arrow_to_graphql_type(column, field.data_type(), true)
+            .is_some_and(|ty| ty.type_name() == TypeRef::STRING || ty.type_name() == TypeRef::ID);
+        if !is_text {
+            return Err(NouninatorError::Config(format!(
+                "Column '{}' of '{}' is not a string or ID; it can only be masked with \"null\"",
+                column, entity.graphql_name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Key of the `hash` mask, kept in the schema's data
+#[derive(Clone)]
+pub struct MaskKey(hmac::Key);
+
+impl MaskKey {
+    pub fn generate() -> Result<Self> {
+        let key = hmac::Key::generate(hmac::HMAC_SHA256, &SystemRandom::new())
+            .map_err(|_| NouninatorError::SchemaGeneration("Failed to generate the masking key".to_string()))?;
+        Ok(Self(key))
+    }
+}
+
+/// Mask one value (`null` stays `null`)
+pub fn apply_mask(mask: Mask, value: Value, key: &MaskKey) -> Value {
+    if value == Value::Null {
+        return value;
+    }
+    match mask {
+        Mask::None => value,
+        Mask::Partial => Value::String(partial(&text(value))),
+        Mask::Hash => Value::String(hash(&text(value), key)),
+        Mask::Redact => Value::String(REDACTED.to_string()),
+        Mask::Null => Value::Null,
+    }
+}
+
+fn text(value: Value) -> String {
+    match value {
+        Value::String(s) => s,
+        other => other.to_string(),
+    }
+}
+
+/// Replace all but the last characters by `*`
+///
+/// Values of eight characters or more keep their last four; shorter ones keep
+/// a quarter of theirs.
+fn partial(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let visible = if chars.len() >= 2 * PARTIAL_VISIBLE {
+        PARTIAL_VISIBLE
+    } else {
+        chars.len() / 4
+    };
+    let hidden = chars.len() - visible;
+    "*".repeat(hidden) + &chars[hidden..].iter().collect::<String>()
+}
+
+/// First 16 bytes of the value's HMAC, in hex
+fn hash(text: &str, key: &MaskKey) -> String {
+    let tag = hmac::sign(&key.0, text.as_bytes());
+    tag.as_ref()[..16].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Resolve a column from the parent row through the caller's mask
+pub fn resolve_masked<'a>(
+    ctx: &ResolverContext<'a>,
+    name: &str,
+    column: &ColumnConfig,
+) -> async_graphql::Result<Option<FieldValue<'a>>> {
+    let mask = mask_for(column, ctx.data_opt::<Principal>());
+    if mask == Mask::None {
+        return resolve_from_parent(ctx, name);
+    }
+    let key = ctx.data::<MaskKey>()?;
+    let value = match parent_row(ctx)?.0 {
+        Value::Object(obj) => obj.get(name).cloned().unwrap_or(Value::Null),
+        _ => Value::Null,
+    };
+    Ok(match apply_mask(mask, value, key) {
+        Value::Null => None,
+        value => Some(FieldValue::value(value)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask_for_picks_the_most_revealing_role_mask() {
+        let column = ColumnConfig {
+            mask: Some(Mask::Redact),
+            role_masks: [("support".to_string(), Mask::Partial), ("hr".to_string(), Mask::None)].into(),
+            ..Default::default()
+        };
+        let principal = |roles: &[&str]| Principal {
+            roles: roles.iter().map(|role| role.to_string()).collect(),
+            ..Default::default()
+        };
+
+        assert_eq!(mask_for(&column, None), Mask::Redact);
+        assert_eq!(mask_for(&column, Some(&principal(&["sales"]))), Mask::Redact);
+        assert_eq!(mask_for(&column, Some(&principal(&["support"]))), Mask::Partial);
+        assert_eq!(mask_for(&column, Some(&principal(&["support", "hr"]))), Mask::None);
+        assert_eq!(mask_for(&ColumnConfig::default(), None), Mask::None);
+    }
+
+    #[test]
+    fn test_apply_mask() {
+        let key = MaskKey::generate().unwrap();
+        let ssn = || Value::String("123-45-6789".to_string());
+
+        assert_eq!(apply_mask(Mask::None, ssn(), &key), ssn());
+        assert_eq!(apply_mask(Mask::Partial, ssn(), &key), Value::String("*******6789".to_string()));
+        assert_eq!(apply_mask(Mask::Partial, Value::String("abc".to_string()), &key), Value::String("***".to_string()));
+        assert_eq!(apply_mask(Mask::Redact, ssn(), &key), Value::String(REDACTED.to_string()));
+        assert_eq!(apply_mask(Mask::Null, ssn(), &key), Value::Null);
+        assert_eq!(apply_mask(Mask::Redact, Value::Null, &key), Value::Null);
+
+        // Equal values hash alike under one key, and differently under another
+        let hashed = apply_mask(Mask::Hash, ssn(), &key);
+        assert_eq!(hashed, apply_mask(Mask::Hash, ssn(), &key));
+        assert_ne!(hashed, apply_mask(Mask::Hash, Value::String("123-45-6780".to_string()), &key));
+        assert_ne!(hashed, apply_mask(Mask::Hash, ssn(), &MaskKey::generate().unwrap()));
+        assert!(matches!(hashed, Value::String(s) if s.len() == 32));
+    }
+}
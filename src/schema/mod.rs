@@ -1,15 +1,53 @@
-/// GraphQL schema generation from Delta tables
-///
-/// This module provides functionality to generate GraphQL schemas from Delta table
-/// Arrow schemas, including type mapping, resolvers, and dynamic schema building.
-
-mod builder;
-mod resolver;
-mod scalars;
-mod type_mapping;
-
-pub use builder::SchemaBuilder;
-pub use resolver::{create_get_resolver, create_list_resolver, record_batch_to_graphql_value};
-pub use scalars::{register_custom_scalars, Date, DateTime};
-pub use type_mapping::arrow_to_graphql_type;
-
+//! GraphQL schema generation from Delta tables
+//!
+//! This module provides functionality to generate GraphQL schemas from Delta table
+//! Arrow schemas, including type mapping, resolvers, and dynamic schema building.
+
+mod access;
+mod aggregate;
+mod builder;
+mod changes;
+mod connection;
+mod empty_scan;
+mod filter;
+mod key;
+mod loader;
+mod masking;
+mod mutation;
+mod nested;
+mod ordering;
+mod projection;
+mod refresh;
+mod registry;
+mod resolver;
+mod row_count;
+mod row_filter;
+mod scalars;
+mod subscription;
+mod temporal;
+mod type_mapping;
+
+pub use builder::SchemaBuilder;
+pub use changes::{change_type_name, read_changes, ChangeRange};
+pub use connection::Cursor;
+pub use filter::{build_filter_input, filter_to_expr, filter_type_name};
+pub use loader::{LookupGroup, LookupKey, RowLoader};
+pub use mutation::{
+    create_bulk_insert_mutation, create_create_mutation, create_delete_mutation,
+    create_update_mutation, create_upsert_mutation,
+};
+pub use nested::{column_graphql_type, nested_type_name};
+pub use ordering::{parse_order_by, SortKey};
+pub use refresh::RefreshableTable;
+pub use registry::{TableRegistry, TableVersion};
+pub use resolver::{
+    create_aggregate_resolver, create_by_ids_resolver, create_changes_resolver,
+    create_connection_resolver, create_get_resolver, create_list_resolver, create_relation_field,
+    record_batch_to_graphql_value,
+};
+pub use row_count::RowCounter;
+pub use row_filter::{RowFilter, RowFilters};
+pub use scalars::{register_custom_scalars, Date, DateTime};
+pub use subscription::{create_added_subscription, create_changed_subscription};
+pub use type_mapping::arrow_to_graphql_type;
+
@@ -0,0 +1,729 @@
+//! Write mutations backed by Delta operations
+//!
+//! Entities with `mutations = true` get `createX`, `updateX`, `deleteX` and
+//! `upsertX` fields on the `Mutation` root, with `XInput`/`XPatch` input types
+//! generated from the Arrow schema. Each mutation runs as a Delta write, update,
+//! delete or merge against the served table and is retried on a concurrent
+//! commit conflict; the new version is swapped in immediately, so the returned
+//! row (and any following query) reflects the write.
+//!
+//! `bulkInsertX(rows)` appends many rows in a single commit, so ingesting a
+//! batch of events adds one entry to the Delta log instead of one per row.
+//!
+//! Callers must be allowed to query an entity (its `allow` rule) to write to it,
+//! and to read a column (its column `allow` rule) to set it.
+//! An entity's row filter limits writes too: updates and deletes only reach
+//! rows it matches, upserts only overwrite such rows, and written and patched
+//! rows must match it. Creating or upserting a key held by a row outside the
+//! filter fails with the same error, so it doesn't reveal that the row exists.
+
+use crate::config::EntityConfig;
+use crate::error::{NouninatorError, Result};
+use crate::schema::access::{authorize_query, authorize_write, input_columns};
+use crate::schema::filter::{value_to_literal, value_to_scalar};
+use crate::schema::key::{
+    describe_key, key_from_args, key_from_row, key_values_predicate, with_key_arguments,
+};
+use crate::schema::refresh::RefreshableTable;
+use crate::schema::registry::TableRegistry;
+use crate::schema::resolver::{into_field_value, load_rows, parent_field, read_point};
+use crate::schema::row_filter::{all_rows_match, row_predicate};
+use crate::schema::scalars::{big_int_value, BIG_INT};
+use crate::schema::type_mapping::arrow_to_graphql_type;
+
+use async_graphql::dynamic::{
+    Field, FieldFuture, FieldValue, InputObject, InputValue, Object, ResolverContext, TypeRef,
+};
+use async_graphql::{Name, Value};
+use datafusion::arrow::datatypes::{Schema as ArrowSchema, SchemaRef};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::{Column, ScalarValue};
+use datafusion::datasource::TableProvider;
+use datafusion::logical_expr::{binary_expr, Operator};
+use datafusion::common::tree_node::{Transformed, TreeNode};
+use datafusion::prelude::{col, Expr, SessionContext};
+use deltalake::errors::DeltaTableError;
+use deltalake::operations::transaction::TransactionError;
+use deltalake::protocol::SaveMode;
+use deltalake::{DeltaOps, DeltaTable};
+use indexmap::IndexMap;
+use std::future::Future;
+use std::sync::Arc;
+
+/// Attempts per mutation before a commit conflict is reported to the client
+pub const MAX_WRITE_ATTEMPTS: usize = 5;
+
+/// Rows per Arrow record batch written by a bulk insert
+pub const BULK_INSERT_BATCH_ROWS: usize = 8192;
+
+/// Shared object type returned by `bulkInsertX`
+pub const BULK_INSERT_RESULT: &str = "BulkInsertResult";
+
+/// Name of the generated input type for an entity (e.g. `CustomerInput`)
+pub fn input_type_name(graphql_name: &str) -> String {
+    format!("{}Input", graphql_name)
+}
+
+/// Name of the generated patch type for an entity (e.g. `CustomerPatch`)
+pub fn patch_type_name(graphql_name: &str) -> String {
+    format!("{}Patch", graphql_name)
+}
+
+/// Fail unless `XInput` can set every non-nullable column
+///
+/// Struct, list and map columns have no input types, so rows written through
+/// mutations leave them null; a table where they can't be null could never
+/// be written to.
+pub fn check_mutation_columns(entity: &EntityConfig, arrow_schema: &ArrowSchema) -> Result<()> {
+    let unsettable = arrow_schema
+        .fields()
+        .iter()
+        .find(|field| !field.is_nullable() && arrow_to_graphql_type(field.name(), field.data_type(), false).is_none());
+    match unsettable {
+        Some(field) => Err(NouninatorError::Config(format!(
+            "Entity '{}' enables mutations but its non-nullable column '{}' cannot be set by mutation inputs",
+            entity.graphql_name,
+            field.name()
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Build the `XInput` (full row) and `XPatch` (partial row) input types
+///
+/// `XInput` requires every non-nullable column; `XPatch` makes every column but
+/// the primary key columns optional.
+pub fn build_mutation_inputs(entity: &EntityConfig, arrow_schema: &ArrowSchema) -> Vec<InputObject> {
+    let mut input = InputObject::new(input_type_name(&entity.graphql_name))
+        .description(format!("A {} row to write", entity.graphql_name));
+    let mut patch = InputObject::new(patch_type_name(&entity.graphql_name))
+        .description(format!("Columns to change on a {} row", entity.graphql_name));
+
+    for field in arrow_schema.fields() {
+        if let Some(type_ref) = arrow_to_graphql_type(field.name(), field.data_type(), field.is_nullable()) {
+            input = input.field(InputValue::new(field.name(), type_ref));
+        }
+        if entity.primary_key.columns().contains(field.name()) {
+            continue;
+        }
+        if let Some(type_ref) = arrow_to_graphql_type(field.name(), field.data_type(), true) {
+            patch = patch.field(InputValue::new(field.name(), type_ref));
+        }
+    }
+
+    vec![input, patch]
+}
+
+/// Build the shared `BulkInsertResult` object type
+pub fn build_bulk_insert_result_type() -> Object {
+    Object::new(BULK_INSERT_RESULT)
+        .description("The commit written by a bulk insert")
+        .field(parent_field("version", TypeRef::named_nn(BIG_INT)))
+        .field(parent_field("rowCount", TypeRef::named_nn(BIG_INT)))
+}
+
+/// Convert input objects into a record batch of the table's schema
+///
+/// Missing columns are written as nulls; a missing or null value for a
+/// non-nullable column is rejected.
+pub fn rows_to_record_batch(rows: &[Value], schema: SchemaRef) -> Result<RecordBatch> {
+    let mut columns = Vec::with_capacity(schema.fields().len());
+
+    for field in schema.fields() {
+        let mut scalars = Vec::with_capacity(rows.len());
+        for row in rows {
+            let Value::Object(obj) = row else {
+                return Err(NouninatorError::SchemaGeneration(
+                    "Rows must be input objects".to_string(),
+                ));
+            };
+            let value = obj.get(field.name().as_str()).unwrap_or(&Value::Null);
+            if matches!(value, Value::Null) && !field.is_nullable() {
+                return Err(NouninatorError::SchemaGeneration(format!(
+                    "Column '{}' cannot be null",
+                    field.name()
+                )));
+            }
+            scalars.push(value_to_scalar(value, field.data_type())?);
+        }
+
+        let array = if scalars.is_empty() {
+            datafusion::arrow::array::new_empty_array(field.data_type())
+        } else {
+            ScalarValue::iter_to_array(scalars)?
+        };
+        columns.push(array);
+    }
+
+    RecordBatch::try_new(schema, columns)
+        .map_err(|e| NouninatorError::SchemaGeneration(format!("Invalid rows: {}", e)))
+}
+
+/// Run a Delta operation against the latest version, retrying on commit conflicts
+///
+/// `operation` receives a fresh copy of the latest snapshot on every attempt and
+/// returns the table after its commit plus a result. The committed version is
+/// swapped into `table` before returning.
+pub async fn commit_with_retry<T, F, Fut>(table: &RefreshableTable, operation: F) -> Result<T>
+where
+    F: Fn(DeltaTable) -> Fut,
+    Fut: Future<Output = std::result::Result<(DeltaTable, T), DeltaTableError>>,
+{
+    let mut attempt = 1;
+    loop {
+        // Start from the latest commit so the conflict window is as small as possible
+        table.refresh().await?;
+
+        match operation(table.snapshot().as_ref().clone()).await {
+            Ok((committed, result)) => {
+                table.replace(committed);
+                return Ok(result);
+            }
+            Err(e) if is_commit_conflict(&e) && attempt < MAX_WRITE_ATTEMPTS => {
+                tracing::warn!("Commit conflict on attempt {}, retrying: {}", attempt, e);
+                attempt += 1;
+            }
+            Err(e) => {
+                return Err(NouninatorError::SchemaGeneration(format!("Write failed: {}", e)));
+            }
+        }
+    }
+}
+
+fn is_commit_conflict(error: &DeltaTableError) -> bool {
+    matches!(
+        error,
+        DeltaTableError::VersionAlreadyExists(_)
+            | DeltaTableError::Transaction {
+                source: TransactionError::CommitConflict(_)
+                    | TransactionError::VersionAlreadyExists(_)
+                    | TransactionError::MaxCommitAttempts(_)
+            }
+    )
+}
+
+/// Merge rows into a table on its primary key columns
+///
+/// Matching rows are overwritten when `update_matched` is set (and, with a
+/// `matched_filter`, only where it holds for the existing row); other rows are
+/// inserted. Returns the table after the commit and the number of rows
+/// written (inserted or overwritten).
+pub async fn merge_rows(
+    table: DeltaTable,
+    batch: RecordBatch,
+    primary_key: &[String],
+    update_matched: bool,
+    matched_filter: Option<Expr>,
+) -> std::result::Result<(DeltaTable, usize), DeltaTableError> {
+    let columns: Vec<String> = batch.schema().fields().iter().map(|f| f.name().clone()).collect();
+    let source = SessionContext::new().read_batch(batch)?;
+    let source_column = |name: &str| col(Column::new(Some("source"), name));
+
+    // deltalake turns `target.pk = source.pk` into a file-pruning filter and
+    // drops the source rows when every target file is pruned, so inserting a
+    // new key would write nothing. Primary keys are never null, so `IS NOT
+    // DISTINCT FROM` matches the same rows without being used for pruning.
+    let on = primary_key
+        .iter()
+        .map(|column| {
+            binary_expr(
+                col(Column::new(Some("target"), column)),
+                Operator::IsNotDistinctFrom,
+                source_column(column),
+            )
+        })
+        .reduce(|on, column_matches| on.and(column_matches))
+        .ok_or_else(|| DeltaTableError::Generic("Primary key has no columns".to_string()))?;
+
+    let mut merge = DeltaOps(table)
+        .merge(source, on)
+        .with_source_alias("source")
+        .with_target_alias("target");
+
+    if update_matched {
+        // The filter names the table's columns; they are the target's in the merge
+        let matched_filter = matched_filter
+            .map(|filter| {
+                filter.transform(|expr| match expr {
+                    Expr::Column(column) if column.relation.is_none() => Ok(Transformed::yes(col(
+                        Column::new(Some("target"), column.name),
+                    ))),
+                    other => Ok(Transformed::no(other)),
+                })
+            })
+            .transpose()?
+            .map(|transformed| transformed.data);
+
+        merge = merge.when_matched_update(|update| {
+            let update = match matched_filter {
+                Some(filter) => update.predicate(filter),
+                None => update,
+            };
+            columns
+                .iter()
+                .filter(|name| !primary_key.contains(name))
+                .fold(update, |update, name| {
+                    update.update(Column::new_unqualified(name), source_column(name))
+                })
+        })?;
+    }
+
+    merge = merge.when_not_matched_insert(|insert| {
+        columns.iter().fold(insert, |insert, name| {
+            insert.set(Column::new_unqualified(name), source_column(name))
+        })
+    })?;
+
+    let (table, metrics) = merge.await?;
+    Ok((table, metrics.num_target_rows_inserted + metrics.num_target_rows_updated))
+}
+
+/// Create the `createX(input)` mutation
+pub fn create_create_mutation(entity: &EntityConfig) -> Field {
+    let entity = Arc::new(entity.clone());
+    let field_name = format!("create{}", entity.graphql_name);
+
+    Field::new(
+        field_name,
+        TypeRef::named_nn(&entity.graphql_name),
+        {
+            let entity = entity.clone();
+            move |ctx: ResolverContext| {
+                let entity = entity.clone();
+
+                FieldFuture::new(async move {
+                    authorize_query(&ctx, &entity)?;
+
+                    let input = ctx.args.try_get("input")?.as_value().clone();
+                    authorize_write(&ctx, &entity, input_columns(std::slice::from_ref(&input)))?;
+                    let key = key_from_row(&input, &entity, "input")?;
+
+                    let table = served_table(&ctx, &entity)?;
+                    let batch = rows_to_record_batch(&[input], table.schema())
+                        .map_err(|e| format!("Invalid input: {}", e))?;
+                    let row_filter = check_row_filter(&ctx, &entity, std::slice::from_ref(&batch)).await?;
+
+                    let inserted = commit_with_retry(&table, |snapshot| {
+                        merge_rows(snapshot, batch.clone(), entity.primary_key.columns(), false, None)
+                    })
+                    .await
+                    .map_err(|e| e.to_string())?;
+
+                    if inserted == 0 {
+                        // A row the caller can't see must not give away that it exists
+                        if row_filter.is_some() && written_row(&ctx, &entity, &key).await?.is_none() {
+                            return Err(row_filter_error(&entity));
+                        }
+                        return Err(format!(
+                            "{} with {} already exists",
+                            entity.graphql_name,
+                            describe_key(&key)
+                        )
+                        .into());
+                    }
+
+                    written_row(&ctx, &entity, &key).await
+                })
+            }
+        },
+    )
+    .argument(InputValue::new(
+        "input",
+        TypeRef::named_nn(input_type_name(&entity.graphql_name)),
+    ))
+}
+
+/// Create the `upsertX(input)` mutation (insert, or overwrite the row with the same key)
+pub fn create_upsert_mutation(entity: &EntityConfig) -> Field {
+    let entity = Arc::new(entity.clone());
+    let field_name = format!("upsert{}", entity.graphql_name);
+
+    Field::new(
+        field_name,
+        TypeRef::named_nn(&entity.graphql_name),
+        {
+            let entity = entity.clone();
+            move |ctx: ResolverContext| {
+                let entity = entity.clone();
+
+                FieldFuture::new(async move {
+                    authorize_query(&ctx, &entity)?;
+
+                    let input = ctx.args.try_get("input")?.as_value().clone();
+                    authorize_write(&ctx, &entity, input_columns(std::slice::from_ref(&input)))?;
+                    let key = key_from_row(&input, &entity, "input")?;
+
+                    let table = served_table(&ctx, &entity)?;
+                    let batch = rows_to_record_batch(&[input], table.schema())
+                        .map_err(|e| format!("Invalid input: {}", e))?;
+                    let row_filter = check_row_filter(&ctx, &entity, std::slice::from_ref(&batch)).await?;
+
+                    let written = commit_with_retry(&table, |snapshot| {
+                        merge_rows(
+                            snapshot,
+                            batch.clone(),
+                            entity.primary_key.columns(),
+                            true,
+                            row_filter.clone(),
+                        )
+                    })
+                    .await
+                    .map_err(|e| e.to_string())?;
+
+                    // Nothing is written only when the existing row is outside the row filter
+                    if written == 0 {
+                        return Err(row_filter_error(&entity));
+                    }
+                    written_row(&ctx, &entity, &key).await
+                })
+            }
+        },
+    )
+    .argument(InputValue::new(
+        "input",
+        TypeRef::named_nn(input_type_name(&entity.graphql_name)),
+    ))
+}
+
+/// Create the `updateX(pk, patch)` mutation
+///
+/// Returns the updated row, or null if no row has the key.
+pub fn create_update_mutation(entity: &EntityConfig, arrow_schema: &ArrowSchema) -> Field {
+    let entity = Arc::new(entity.clone());
+    let field_name = format!("update{}", entity.graphql_name);
+
+    let field = Field::new(field_name, TypeRef::named(&entity.graphql_name), {
+        let entity = entity.clone();
+        move |ctx: ResolverContext| {
+            let entity = entity.clone();
+
+            FieldFuture::new(async move {
+                authorize_query(&ctx, &entity)?;
+
+                let key = key_from_args(&ctx, &entity)?;
+                let Value::Object(patch) = ctx.args.try_get("patch")?.as_value().clone() else {
+                    return Err("'patch' must be an object".into());
+                };
+                if patch.is_empty() {
+                    return Err("'patch' must set at least one column".into());
+                }
+                authorize_write(&ctx, &entity, patch.keys())?;
+
+                let table = served_table(&ctx, &entity)?;
+                let schema = table.schema();
+                let mut predicate = key_values_predicate(&key, &schema)
+                    .map_err(|e| format!("Invalid primary key: {}", e))?;
+
+                let mut assignments = Vec::with_capacity(patch.len());
+                for (column, value) in &patch {
+                    let field = schema
+                        .field_with_name(column)
+                        .map_err(|_| format!("Unknown column '{}'", column))?;
+                    if matches!(value, Value::Null) && !field.is_nullable() {
+                        return Err(format!("Column '{}' cannot be null", column).into());
+                    }
+                    let literal = value_to_literal(value, field.data_type())
+                        .map_err(|e| format!("Invalid patch: {}", e))?;
+                    assignments.push((column.to_string(), literal));
+                }
+
+                // Only rows that match the row filter both before and after the patch are updated
+                let row_filter = row_predicate(&ctx, &entity)?;
+                if let Some(row_filter) = &row_filter {
+                    let patched = patched_predicate(row_filter.clone(), &assignments)
+                        .map_err(|e| format!("Invalid patch: {}", e))?;
+                    predicate = predicate.and(row_filter.clone()).and(patched);
+                }
+
+                let updated = commit_with_retry(&table, |snapshot| {
+                    let predicate = predicate.clone();
+                    let assignments = assignments.clone();
+                    async move {
+                        let update = assignments.into_iter().fold(
+                            DeltaOps(snapshot).update().with_predicate(predicate),
+                            |update, (column, literal)| {
+                                update.with_update(Column::new_unqualified(column), literal)
+                            },
+                        );
+                        let (table, metrics) = update.await?;
+                        Ok((table, metrics.num_updated_rows))
+                    }
+                })
+                .await
+                .map_err(|e| e.to_string())?;
+
+                if updated == 0 {
+                    if row_filter.is_some() && written_row(&ctx, &entity, &key).await?.is_some() {
+                        return Err(row_filter_error(&entity));
+                    }
+                    return Ok(None);
+                }
+                written_row(&ctx, &entity, &key).await
+            })
+        }
+    });
+
+    with_key_arguments(field, &entity, arrow_schema).argument(InputValue::new(
+        "patch",
+        TypeRef::named_nn(patch_type_name(&entity.graphql_name)),
+    ))
+}
+
+/// Create the `deleteX(pk)` mutation
+///
+/// Returns whether a row was deleted.
+pub fn create_delete_mutation(entity: &EntityConfig, arrow_schema: &ArrowSchema) -> Field {
+    let entity = Arc::new(entity.clone());
+    let field_name = format!("delete{}", entity.graphql_name);
+
+    let field = Field::new(field_name, TypeRef::named_nn(TypeRef::BOOLEAN), {
+        let entity = entity.clone();
+        move |ctx: ResolverContext| {
+            let entity = entity.clone();
+
+            FieldFuture::new(async move {
+                authorize_query(&ctx, &entity)?;
+
+                let key = key_from_args(&ctx, &entity)?;
+
+                let table = served_table(&ctx, &entity)?;
+                let mut predicate = key_values_predicate(&key, &table.schema())
+                    .map_err(|e| format!("Invalid primary key: {}", e))?;
+                if let Some(row_filter) = row_predicate(&ctx, &entity)? {
+                    predicate = predicate.and(row_filter);
+                }
+
+                let deleted = commit_with_retry(&table, |snapshot| {
+                    let predicate = predicate.clone();
+                    async move {
+                        let (table, metrics) =
+                            DeltaOps(snapshot).delete().with_predicate(predicate).await?;
+                        Ok((table, metrics.num_deleted_rows))
+                    }
+                })
+                .await
+                .map_err(|e| e.to_string())?;
+
+                Ok(Some(FieldValue::value(deleted > 0)))
+            })
+        }
+    });
+
+    with_key_arguments(field, &entity, arrow_schema)
+}
+
+/// Create the `bulkInsertX(rows)` mutation
+///
+/// All rows are appended in one commit; keys are not checked against existing
+/// rows. Calls with more than `max_bulk_insert_rows` rows are rejected.
+pub fn create_bulk_insert_mutation(entity: &EntityConfig) -> Field {
+    let entity = Arc::new(entity.clone());
+    let field_name = format!("bulkInsert{}", entity.graphql_name);
+
+    Field::new(field_name, TypeRef::named_nn(BULK_INSERT_RESULT), {
+        let entity = entity.clone();
+        move |ctx: ResolverContext| {
+            let entity = entity.clone();
+
+            FieldFuture::new(async move {
+                authorize_query(&ctx, &entity)?;
+
+                let Value::List(rows) = ctx.args.try_get("rows")?.as_value().clone() else {
+                    return Err("'rows' must be a list".into());
+                };
+                if rows.is_empty() {
+                    return Err("'rows' must contain at least one row".into());
+                }
+                authorize_write(&ctx, &entity, input_columns(&rows))?;
+                let max_rows = entity.max_bulk_insert_rows();
+                if rows.len() > max_rows {
+                    return Err(format!(
+                        "Cannot insert {} rows at once (max_bulk_insert_rows is {})",
+                        rows.len(),
+                        max_rows
+                    )
+                    .into());
+                }
+
+                let table = served_table(&ctx, &entity)?;
+                let schema = table.schema();
+                let batches = rows
+                    .chunks(BULK_INSERT_BATCH_ROWS)
+                    .map(|chunk| rows_to_record_batch(chunk, schema.clone()))
+                    .collect::<Result<Vec<_>>>()
+                    .map_err(|e| format!("Invalid rows: {}", e))?;
+                check_row_filter(&ctx, &entity, &batches).await?;
+
+                let version = commit_with_retry(&table, |snapshot| {
+                    let batches = batches.clone();
+                    async move {
+                        let table = DeltaOps(snapshot)
+                            .write(batches)
+                            .with_save_mode(SaveMode::Append)
+                            .await?;
+                        let version = table.version();
+                        Ok((table, version))
+                    }
+                })
+                .await
+                .map_err(|e| e.to_string())?;
+
+                let mut result = IndexMap::new();
+                result.insert(Name::new("version"), big_int_value(version));
+                result.insert(Name::new("rowCount"), big_int_value(rows.len() as u64));
+                Ok(Some(FieldValue::owned_any(Value::Object(result))))
+            })
+        }
+    })
+    .argument(InputValue::new(
+        "rows",
+        TypeRef::named_nn_list_nn(input_type_name(&entity.graphql_name)),
+    ))
+}
+
+/// The served (writable) table of an entity
+pub(crate) fn served_table(
+    ctx: &ResolverContext<'_>,
+    entity: &EntityConfig,
+) -> std::result::Result<Arc<RefreshableTable>, async_graphql::Error> {
+    let registry = ctx
+        .data::<Arc<TableRegistry>>()
+        .map_err(|_e| "Failed to get table registry")?;
+    registry.served_table(&entity.table).ok_or_else(|| {
+        format!("Table '{}' is not a Delta table; mutations are unavailable", entity.table).into()
+    })
+}
+
+/// Reject rows outside the entity's row filter
+///
+/// Returns the bound row filter, if the entity has one.
+async fn check_row_filter(
+    ctx: &ResolverContext<'_>,
+    entity: &EntityConfig,
+    batches: &[RecordBatch],
+) -> async_graphql::Result<Option<Expr>> {
+    let Some(row_filter) = row_predicate(ctx, entity)? else {
+        return Ok(None);
+    };
+    for batch in batches {
+        if !all_rows_match(batch.clone(), row_filter.clone()).await.map_err(|e| e.to_string())? {
+            return Err(row_filter_error(entity));
+        }
+    }
+    Ok(Some(row_filter))
+}
+
+/// The error for a write the row filter doesn't allow
+///
+/// Used alike for rows written outside the filter and for keys held by rows
+/// outside it, so callers can't tell whether such rows exist.
+fn row_filter_error(entity: &EntityConfig) -> async_graphql::Error {
+    format!("Rows written to {} must match its row filter", entity.graphql_name).into()
+}
+
+/// A row filter as it applies to a row after `assignments` are made
+///
+/// Each patched column is replaced by the value it is set to.
+fn patched_predicate(row_filter: Expr, assignments: &[(String, Expr)]) -> Result<Expr> {
+    let patched = row_filter.transform(|expr| match &expr {
+        Expr::Column(column) => match assignments.iter().find(|(name, _)| *name == column.name) {
+            Some((_, literal)) => Ok(Transformed::yes(literal.clone())),
+            None => Ok(Transformed::no(expr)),
+        },
+        _ => Ok(Transformed::no(expr)),
+    })?;
+    Ok(patched.data)
+}
+
+/// Read back a written row by primary key
+async fn written_row(
+    ctx: &ResolverContext<'_>,
+    entity: &EntityConfig,
+    key: &[(String, Value)],
+) -> async_graphql::Result<Option<FieldValue<'static>>> {
+    let read = read_point(ctx, &entity.table).await?;
+    let rows = load_rows(ctx, entity, key, &read).await?;
+    Ok(rows.into_iter().next().map(|row| into_field_value(row, &read)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::array::{Array, Int64Array, StringArray};
+    use datafusion::arrow::datatypes::{DataType, Field as ArrowField};
+
+    fn test_schema() -> SchemaRef {
+        Arc::new(ArrowSchema::new(vec![
+            ArrowField::new("id", DataType::Int64, false),
+            ArrowField::new("word", DataType::Utf8, true),
+        ]))
+    }
+
+    fn row(pairs: &[(&str, Value)]) -> Value {
+        let mut map = IndexMap::new();
+        for (name, value) in pairs {
+            map.insert(Name::new(name), value.clone());
+        }
+        Value::Object(map)
+    }
+
+    #[test]
+    fn test_rows_to_record_batch() {
+        let rows = vec![
+            row(&[("id", Value::String("1".to_string())), ("word", Value::String("cat".to_string()))]),
+            row(&[("id", Value::Number(2.into()))]),
+        ];
+        let batch = rows_to_record_batch(&rows, test_schema()).unwrap();
+
+        let ids = batch.column(0).as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(ids.values(), &[1, 2]);
+        let words = batch.column(1).as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(words.value(0), "cat");
+        assert!(words.is_null(1));
+    }
+
+    #[test]
+    fn test_rows_to_record_batch_requires_non_nullable_columns() {
+        let rows = vec![row(&[("word", Value::String("cat".to_string()))])];
+        assert!(rows_to_record_batch(&rows, test_schema()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_commit_conflicts_are_retried() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap().to_string();
+        let batch = rows_to_record_batch(
+            &[
+                row(&[("id", Value::Number(1.into()))]),
+                row(&[("id", Value::Number(2.into()))]),
+            ],
+            test_schema(),
+        )
+        .unwrap();
+        let table = DeltaOps::try_from_uri(&path).await.unwrap().write(vec![batch]).await.unwrap();
+        let table = RefreshableTable::new("words", table);
+
+        let attempts = AtomicUsize::new(0);
+        let deleted = commit_with_retry(&table, |snapshot| {
+            let path = path.clone();
+            let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if attempt == 0 {
+                    // Another writer rewrites the same file first
+                    let other = deltalake::open_table(&path).await?;
+                    DeltaOps(other).delete().with_predicate("id = 2").await?;
+                }
+                let (table, metrics) = DeltaOps(snapshot).delete().with_predicate("id = 1").await?;
+                Ok((table, metrics.num_deleted_rows))
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        assert_eq!(deleted, 1);
+        assert_eq!(table.version(), 2);
+    }
+}
@@ -0,0 +1,271 @@
+//! Nested object and list types for struct, list and map columns
+//!
+//! A struct column becomes a field of a generated object type named after the
+//! entity and the column path (`Customer.address` → `CustomerAddress`,
+//! `Customer.address.geo` → `CustomerAddressGeo`). List columns become GraphQL
+//! lists of their element type (a list of structs reuses the column's name for
+//! the element type), and map columns become lists of `{ key value }` entry
+//! objects (`Customer.attributes` → `[CustomerAttributesEntry!]`).
+//!
+//! Rows already carry these values as nested `Value::Object`s and
+//! `Value::List`s (see `record_batch_to_graphql_value`), so the nested types
+//! resolve their fields from the parent value like entity types. Elements that
+//! are themselves objects or lists are non-null: async-graphql cannot return a
+//! null object inside a list, so null elements of those types are dropped.
+
+use crate::schema::resolver::parent_field;
+use crate::schema::type_mapping::arrow_to_graphql_type;
+use crate::unity::to_pascal_case;
+
+use async_graphql::dynamic::{Object, TypeRef};
+use datafusion::arrow::datatypes::{DataType as ArrowDataType, Field as ArrowField, FieldRef, Fields};
+
+/// Name of the object type generated for a struct column (e.g. `CustomerAddress`)
+pub fn nested_type_name(parent_type: &str, field_name: &str) -> String {
+    format!("{}{}", parent_type, to_pascal_case(field_name))
+}
+
+/// Name of the entry type generated for a map column (e.g. `CustomerAttributesEntry`)
+pub fn map_entry_type_name(type_name: &str) -> String {
+    format!("{}Entry", type_name)
+}
+
+/// GraphQL type of a column, including struct, list and map columns
+///
+/// `parent_type` is the name nested types are derived from: the entity name for
+/// top-level columns, the enclosing nested type name for struct children.
+/// Returns `None` for columns that are skipped, including structs without any
+/// supported child column.
+pub fn column_graphql_type(parent_type: &str, field: &ArrowField, nullable: bool) -> Option<TypeRef> {
+    nested_graphql_type(
+        &nested_type_name(parent_type, field.name()),
+        field.name(),
+        field.data_type(),
+        nullable,
+    )
+}
+
+/// Whether values of a type are converted to objects or lists
+pub fn is_nested_type(data_type: &ArrowDataType) -> bool {
+    matches!(
+        data_type,
+        ArrowDataType::Struct(_)
+            | ArrowDataType::List(_)
+            | ArrowDataType::LargeList(_)
+            | ArrowDataType::FixedSizeList(_, _)
+            | ArrowDataType::Map(_, _)
+    )
+}
+
+/// The key and value fields of a map's entries
+pub fn map_entry_fields(entries: &ArrowField) -> Option<(&FieldRef, &FieldRef)> {
+    match entries.data_type() {
+        ArrowDataType::Struct(fields) if fields.len() == 2 => Some((&fields[0], &fields[1])),
+        _ => None,
+    }
+}
+
+/// GraphQL type of a value, naming any generated object type `type_name`
+///
+/// `field_name` is the column name, used for ID inference on scalar values
+/// and list elements.
+fn nested_graphql_type(
+    type_name: &str,
+    field_name: &str,
+    data_type: &ArrowDataType,
+    nullable: bool,
+) -> Option<TypeRef> {
+    let type_ref = match data_type {
+        ArrowDataType::Struct(children) => {
+            if !children
+                .iter()
+                .any(|child| column_graphql_type(type_name, child, true).is_some())
+            {
+                tracing::warn!(
+                    "Struct field '{}' has no supported columns, skipping field",
+                    field_name
+                );
+                return None;
+            }
+            TypeRef::named(type_name)
+        }
+        ArrowDataType::List(item)
+        | ArrowDataType::LargeList(item)
+        | ArrowDataType::FixedSizeList(item, _) => {
+            let nullable_items = item.is_nullable() && !is_nested_type(item.data_type());
+            let element = nested_graphql_type(type_name, field_name, item.data_type(), nullable_items)?;
+            TypeRef::List(Box::new(element))
+        }
+        ArrowDataType::Map(entries, _) => {
+            let (key, value) = map_entry_fields(entries)?;
+            let entry_name = map_entry_type_name(type_name);
+            nested_graphql_type(&nested_type_name(&entry_name, "key"), "key", key.data_type(), true)?;
+            nested_graphql_type(&nested_type_name(&entry_name, "value"), "value", value.data_type(), true)?;
+            TypeRef::List(Box::new(TypeRef::named_nn(entry_name)))
+        }
+        _ => return arrow_to_graphql_type(field_name, data_type, nullable),
+    };
+
+    Some(if nullable {
+        type_ref
+    } else {
+        TypeRef::NonNull(Box::new(type_ref))
+    })
+}
+
+/// Build the object types for every struct and map column of a schema, recursively
+pub fn build_nested_types(parent_type: &str, fields: &Fields) -> Vec<Object> {
+    let mut objects = Vec::new();
+
+    for field in fields {
+        if column_graphql_type(parent_type, field, true).is_none() {
+            continue;
+        }
+        collect_nested_types(
+            &nested_type_name(parent_type, field.name()),
+            &format!("The {} of a {}", field.name(), parent_type),
+            field.data_type(),
+            &mut objects,
+        );
+    }
+
+    objects
+}
+
+fn collect_nested_types(
+    type_name: &str,
+    description: &str,
+    data_type: &ArrowDataType,
+    objects: &mut Vec<Object>,
+) {
+    match data_type {
+        ArrowDataType::Struct(children) => {
+            let mut object = Object::new(type_name).description(description);
+            for child in children {
+                if let Some(type_ref) = column_graphql_type(type_name, child, child.is_nullable()) {
+                    object = object.field(parent_field(child.name(), type_ref));
+                }
+            }
+            objects.push(object);
+            objects.extend(build_nested_types(type_name, children));
+        }
+        ArrowDataType::List(item)
+        | ArrowDataType::LargeList(item)
+        | ArrowDataType::FixedSizeList(item, _) => {
+            collect_nested_types(type_name, description, item.data_type(), objects);
+        }
+        ArrowDataType::Map(entries, _) => {
+            let Some((key, value)) = map_entry_fields(entries) else {
+                return;
+            };
+            let entry_name = map_entry_type_name(type_name);
+            let key_name = nested_type_name(&entry_name, "key");
+            let value_name = nested_type_name(&entry_name, "value");
+
+            let mut entry = Object::new(&entry_name).description(format!("An entry of {}", type_name));
+            if let Some(type_ref) = nested_graphql_type(&key_name, "key", key.data_type(), false) {
+                entry = entry.field(parent_field("key", type_ref));
+            }
+            let value_nullable = value.is_nullable();
+            if let Some(type_ref) = nested_graphql_type(&value_name, "value", value.data_type(), value_nullable) {
+                entry = entry.field(parent_field("value", type_ref));
+            }
+            objects.push(entry);
+
+            let key_description = format!("The key of a {}", entry_name);
+            collect_nested_types(&key_name, &key_description, key.data_type(), objects);
+            let value_description = format!("The value of a {}", entry_name);
+            collect_nested_types(&value_name, &value_description, value.data_type(), objects);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::datatypes::Schema as ArrowSchema;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_nested_types_are_named_by_path() {
+        let geo = ArrowDataType::Struct(Fields::from(vec![
+            ArrowField::new("lat", ArrowDataType::Float64, true),
+            ArrowField::new("lon", ArrowDataType::Float64, true),
+        ]));
+        let address = ArrowDataType::Struct(Fields::from(vec![
+            ArrowField::new("city", ArrowDataType::Utf8, false),
+            ArrowField::new("geo", geo, true),
+        ]));
+        let schema = ArrowSchema::new(vec![
+            ArrowField::new("id", ArrowDataType::Int64, false),
+            ArrowField::new("shipping_address", address, true),
+            ArrowField::new("empty", ArrowDataType::Struct(Fields::empty()), true),
+        ]);
+
+        let type_ref = column_graphql_type("Customer", schema.field(1), true).unwrap();
+        assert_eq!(type_ref.to_string(), "CustomerShippingAddress");
+        assert!(column_graphql_type("Customer", schema.field(2), true).is_none());
+
+        let names: Vec<String> = build_nested_types("Customer", schema.fields())
+            .iter()
+            .map(|object| object.type_name().to_string())
+            .collect();
+        assert_eq!(names, vec!["CustomerShippingAddress", "CustomerShippingAddressGeo"]);
+    }
+
+    #[test]
+    fn test_list_and_map_types() {
+        let item = ArrowDataType::Struct(Fields::from(vec![ArrowField::new(
+            "sku",
+            ArrowDataType::Utf8,
+            false,
+        )]));
+        let entries = ArrowField::new(
+            "entries",
+            ArrowDataType::Struct(Fields::from(vec![
+                ArrowField::new("key", ArrowDataType::Utf8, false),
+                ArrowField::new("value", ArrowDataType::Int64, true),
+            ])),
+            false,
+        );
+        let schema = ArrowSchema::new(vec![
+            ArrowField::new_list("tags", ArrowField::new_list_field(ArrowDataType::Utf8, true), true),
+            ArrowField::new_list(
+                "product_ids",
+                ArrowField::new_list_field(ArrowDataType::Int64, false),
+                false,
+            ),
+            ArrowField::new_fixed_size_list(
+                "point",
+                ArrowField::new_list_field(ArrowDataType::Float32, true),
+                2,
+                true,
+            ),
+            ArrowField::new_large_list("line_items", ArrowField::new_list_field(item, true), true),
+            ArrowField::new("attributes", ArrowDataType::Map(Arc::new(entries), false), true),
+        ]);
+
+        let types: Vec<String> = schema
+            .fields()
+            .iter()
+            .map(|field| column_graphql_type("Order", field, field.is_nullable()).unwrap().to_string())
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                "[String]",
+                "[BigInt!]!",
+                "[Float]",
+                "[OrderLineItems!]",
+                "[OrderAttributesEntry!]",
+            ]
+        );
+
+        let names: Vec<String> = build_nested_types("Order", schema.fields())
+            .iter()
+            .map(|object| object.type_name().to_string())
+            .collect();
+        assert_eq!(names, vec!["OrderLineItems", "OrderAttributesEntry"]);
+    }
+}
@@ -0,0 +1,264 @@
+//! Sorting support for list queries
+//!
+//! This module generates a per-entity enum of sortable columns (`XColumn`) and
+//! an `XOrderBy` input type, and turns `orderBy` arguments into DataFusion sort
+//! expressions. The primary key columns are always appended as tiebreakers so
+//! page boundaries are deterministic across Delta files.
+
+use crate::config::{EntityConfig, PrimaryKey};
+use crate::error::{NouninatorError, Result};
+use crate::schema::type_mapping::arrow_to_graphql_type;
+
+use async_graphql::dynamic::{Enum, InputObject, InputValue, TypeRef};
+use async_graphql::{Name, Value};
+use datafusion::arrow::datatypes::Schema as ArrowSchema;
+use datafusion::prelude::{ident, Expr};
+
+/// Shared enum for sort direction
+pub const SORT_DIRECTION: &str = "SortDirection";
+
+/// Shared enum for null placement
+pub const NULLS_ORDER: &str = "NullsOrder";
+
+/// Name of the generated column enum for an entity (e.g. `CustomerColumn`)
+pub fn column_enum_name(graphql_name: &str) -> String {
+    format!("{}Column", graphql_name)
+}
+
+/// Name of the generated order-by input type for an entity (e.g. `CustomerOrderBy`)
+pub fn order_by_type_name(graphql_name: &str) -> String {
+    format!("{}OrderBy", graphql_name)
+}
+
+/// Register the shared `SortDirection` and `NullsOrder` enums
+pub fn register_ordering_enums() -> Vec<Enum> {
+    vec![
+        Enum::new(SORT_DIRECTION)
+            .description("Sort direction")
+            .item("ASC")
+            .item("DESC"),
+        Enum::new(NULLS_ORDER)
+            .description("Placement of null values in sorted results")
+            .item("FIRST")
+            .item("LAST"),
+    ]
+}
+
+/// Names of the columns that can be sorted on (all columns with a scalar GraphQL type)
+pub fn sortable_columns(arrow_schema: &ArrowSchema) -> Vec<String> {
+    arrow_schema
+        .fields()
+        .iter()
+        .filter(|field| arrow_to_graphql_type(field.name(), field.data_type(), true).is_some())
+        .map(|field| field.name().to_string())
+        .collect()
+}
+
+/// Build the `XColumn` enum listing an entity's sortable columns
+pub fn build_column_enum(entity: &EntityConfig, arrow_schema: &ArrowSchema) -> Enum {
+    Enum::new(column_enum_name(&entity.graphql_name))
+        .description(format!("Columns of {}", entity.graphql_name))
+        .items(sortable_columns(arrow_schema))
+}
+
+/// Build the `XOrderBy` input object for an entity
+pub fn build_order_by_input(entity: &EntityConfig) -> InputObject {
+    InputObject::new(order_by_type_name(&entity.graphql_name))
+        .description(format!("Sort order for {}", entity.graphql_name))
+        .field(InputValue::new(
+            "field",
+            TypeRef::named_nn(column_enum_name(&entity.graphql_name)),
+        ))
+        .field(
+            InputValue::new("direction", TypeRef::named_nn(SORT_DIRECTION))
+                .default_value(Value::Enum(Name::new("ASC"))),
+        )
+        .field(InputValue::new("nulls", TypeRef::named(NULLS_ORDER)))
+}
+
+/// A single resolved sort key
+#[derive(Debug, Clone, PartialEq)]
+pub struct SortKey {
+    /// Column to sort on
+    pub column: String,
+    /// Ascending (`true`) or descending (`false`)
+    pub ascending: bool,
+    /// Whether nulls sort before non-null values
+    pub nulls_first: bool,
+}
+
+impl SortKey {
+    /// Ascending sort with nulls last (the SQL default)
+    pub fn asc(column: impl Into<String>) -> Self {
+        Self {
+            column: column.into(),
+            ascending: true,
+            nulls_first: false,
+        }
+    }
+
+    /// Convert to a DataFusion sort expression
+    pub fn to_expr(&self) -> Expr {
+        ident(&self.column).sort(self.ascending, self.nulls_first)
+    }
+}
+
+/// Resolve an `orderBy` argument into sort keys
+///
+/// # Arguments
+///
+/// * `order_by` - The `[XOrderBy!]` argument value (or `None` if omitted)
+/// * `primary_key` - Primary key columns, appended as tiebreakers if not already sorted on
+/// * `schema` - Arrow schema of the table (used to validate column names)
+///
+/// # Returns
+///
+/// The sort keys to apply, in order
+pub fn parse_order_by(
+    order_by: Option<&Value>,
+    primary_key: &PrimaryKey,
+    schema: &ArrowSchema,
+) -> Result<Vec<SortKey>> {
+    let mut keys = Vec::new();
+
+    if let Some(Value::List(items)) = order_by {
+        for item in items {
+            keys.push(parse_sort_key(item, schema)?);
+        }
+    }
+
+    // Always end with the primary key so pagination is stable
+    for column in primary_key.columns() {
+        if schema.field_with_name(column).is_ok() && !keys.iter().any(|k| &k.column == column) {
+            keys.push(SortKey::asc(column));
+        }
+    }
+
+    Ok(keys)
+}
+
+fn parse_sort_key(item: &Value, schema: &ArrowSchema) -> Result<SortKey> {
+    let Value::Object(obj) = item else {
+        return Err(NouninatorError::SchemaGeneration(
+            "orderBy entries must be objects".to_string(),
+        ));
+    };
+
+    let column = match obj.get("field") {
+        Some(Value::Enum(name)) => name.to_string(),
+        Some(Value::String(name)) => name.clone(),
+        _ => {
+            return Err(NouninatorError::SchemaGeneration(
+                "orderBy entry is missing 'field'".to_string(),
+            ))
+        }
+    };
+
+    if schema.field_with_name(&column).is_err() {
+        return Err(NouninatorError::SchemaGeneration(format!(
+            "Unknown sort column '{}'",
+            column
+        )));
+    }
+
+    let ascending = !matches!(enum_value(obj.get("direction")), Some("DESC"));
+
+    // Default null placement matches SQL: last for ASC, first for DESC
+    let nulls_first = match enum_value(obj.get("nulls")) {
+        Some("FIRST") => true,
+        Some("LAST") => false,
+        _ => !ascending,
+    };
+
+    Ok(SortKey {
+        column,
+        ascending,
+        nulls_first,
+    })
+}
+
+fn enum_value(value: Option<&Value>) -> Option<&str> {
+    match value {
+        Some(Value::Enum(name)) => Some(name.as_str()),
+        Some(Value::String(s)) => Some(s.as_str()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::datatypes::{DataType, Field as ArrowField};
+    use indexmap::IndexMap;
+
+    fn test_schema() -> ArrowSchema {
+        ArrowSchema::new(vec![
+            ArrowField::new("id", DataType::Int64, false),
+            ArrowField::new("rank", DataType::Int64, true),
+        ])
+    }
+
+    fn order(field: &str, direction: Option<&str>, nulls: Option<&str>) -> Value {
+        let mut map = IndexMap::new();
+        map.insert(Name::new("field"), Value::Enum(Name::new(field)));
+        if let Some(direction) = direction {
+            map.insert(Name::new("direction"), Value::Enum(Name::new(direction)));
+        }
+        if let Some(nulls) = nulls {
+            map.insert(Name::new("nulls"), Value::Enum(Name::new(nulls)));
+        }
+        Value::Object(map)
+    }
+
+    #[test]
+    fn test_default_orders_by_primary_key() {
+        let keys = parse_order_by(None, &"id".into(), &test_schema()).unwrap();
+        assert_eq!(keys, vec![SortKey::asc("id")]);
+    }
+
+    #[test]
+    fn test_primary_key_appended_as_tiebreaker() {
+        let order_by = Value::List(vec![order("rank", Some("DESC"), None)]);
+        let keys = parse_order_by(Some(&order_by), &"id".into(), &test_schema()).unwrap();
+        assert_eq!(
+            keys,
+            vec![
+                SortKey {
+                    column: "rank".to_string(),
+                    ascending: false,
+                    nulls_first: true,
+                },
+                SortKey::asc("id"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_explicit_nulls_and_primary_key_direction() {
+        let order_by = Value::List(vec![
+            order("rank", None, Some("FIRST")),
+            order("id", Some("DESC"), None),
+        ]);
+        let keys = parse_order_by(Some(&order_by), &"id".into(), &test_schema()).unwrap();
+        assert_eq!(keys.len(), 2);
+        assert!(keys[0].ascending && keys[0].nulls_first);
+        assert!(!keys[1].ascending);
+    }
+
+    #[test]
+    fn test_composite_primary_key_appended_in_order() {
+        let primary_key = PrimaryKey::Columns(vec!["rank".to_string(), "id".to_string()]);
+        let order_by = Value::List(vec![order("id", Some("DESC"), None)]);
+        let keys = parse_order_by(Some(&order_by), &primary_key, &test_schema()).unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].column, "id");
+        assert!(!keys[0].ascending);
+        assert_eq!(keys[1], SortKey::asc("rank"));
+    }
+
+    #[test]
+    fn test_unknown_column_is_rejected() {
+        let order_by = Value::List(vec![order("missing", None, None)]);
+        assert!(parse_order_by(Some(&order_by), &"id".into(), &test_schema()).is_err());
+    }
+}
@@ -0,0 +1,218 @@
+//! Refreshable Delta table providers
+//!
+//! A Delta table registered in the `SessionContext` is a snapshot of the
+//! version that was current when it was opened. `RefreshableTable` wraps that
+//! snapshot behind a lock so a background task can load newer commits and swap
+//! them in atomically: queries that already started keep scanning the snapshot
+//! they planned against, new queries see the new version.
+
+use crate::error::{NouninatorError, Result};
+
+use async_trait::async_trait;
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::catalog::Session;
+use datafusion::common::Statistics;
+use datafusion::datasource::{TableProvider, TableType};
+use datafusion::logical_expr::TableProviderFilterPushDown;
+use datafusion::physical_plan::ExecutionPlan;
+use datafusion::prelude::Expr;
+use deltalake::DeltaTable;
+use std::any::Any;
+use std::sync::{Arc, RwLock};
+use tokio::sync::watch;
+
+/// Delta table provider whose snapshot can be swapped while serving
+pub struct RefreshableTable {
+    /// Registered table name (for logging)
+    name: String,
+    /// Currently served snapshot
+    current: RwLock<Arc<DeltaTable>>,
+    /// Notifies subscribers of the served version
+    versions: watch::Sender<i64>,
+}
+
+impl RefreshableTable {
+    /// Wrap an opened Delta table
+    pub fn new(name: &str, table: DeltaTable) -> Self {
+        let (versions, _) = watch::channel(table.version());
+        Self {
+            name: name.to_string(),
+            current: RwLock::new(Arc::new(table)),
+            versions,
+        }
+    }
+
+    /// Watch the served version (updated after every successful refresh)
+    pub fn subscribe(&self) -> watch::Receiver<i64> {
+        self.versions.subscribe()
+    }
+
+    /// Currently served snapshot
+    pub fn snapshot(&self) -> Arc<DeltaTable> {
+        self.current.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Currently served version
+    pub fn version(&self) -> i64 {
+        self.snapshot().version()
+    }
+
+    /// Load new commits from the Delta log, if any
+    ///
+    /// Returns the newly served version, or `None` if the table is unchanged.
+    /// A new version whose schema differs from the served one is not swapped
+    /// in: the GraphQL schema was generated from the original columns.
+    pub async fn refresh(&self) -> Result<Option<i64>> {
+        let current = self.snapshot();
+
+        let latest = current.get_latest_version().await.map_err(|e| {
+            NouninatorError::SchemaGeneration(format!(
+                "Failed to check '{}' for new versions: {}",
+                self.name, e
+            ))
+        })?;
+        if latest <= current.version() {
+            return Ok(None);
+        }
+
+        let mut updated = current.as_ref().clone();
+        updated.update().await.map_err(|e| {
+            NouninatorError::SchemaGeneration(format!(
+                "Failed to load version {} of '{}': {}",
+                latest, self.name, e
+            ))
+        })?;
+
+        if updated.schema() != current.schema() {
+            return Err(NouninatorError::SchemaGeneration(format!(
+                "Schema of '{}' changed at version {}; restart the server to serve it",
+                self.name,
+                updated.version()
+            )));
+        }
+
+        // A mutation may have served a newer version while this one loaded
+        let version = updated.version();
+        Ok(self.swap(updated).then_some(version))
+    }
+
+    /// Serve a table this server just committed to
+    ///
+    /// Ignored if a newer version is already being served.
+    pub fn replace(&self, table: DeltaTable) {
+        self.swap(table);
+    }
+
+    /// Serve `table` if it is newer than the served snapshot
+    ///
+    /// Returns whether it was swapped in. The version check and the swap
+    /// happen under the same write lock, so the served version never goes
+    /// backwards.
+    fn swap(&self, table: DeltaTable) -> bool {
+        let version = table.version();
+        let mut current = self.current.write().unwrap_or_else(|e| e.into_inner());
+        if version <= current.version() {
+            return false;
+        }
+        *current = Arc::new(table);
+        drop(current);
+        self.versions.send_replace(version);
+        true
+    }
+}
+
+#[async_trait]
+impl TableProvider for RefreshableTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        TableProvider::schema(self.snapshot().as_ref())
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> datafusion::error::Result<Vec<TableProviderFilterPushDown>> {
+        self.snapshot().supports_filters_pushdown(filters)
+    }
+
+    fn statistics(&self) -> Option<Statistics> {
+        self.snapshot().statistics()
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> datafusion::error::Result<Arc<dyn ExecutionPlan>> {
+        // Plan against one snapshot even if a refresh lands mid-scan
+        let snapshot = self.snapshot();
+        snapshot.scan(state, projection, filters, limit).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use deltalake::arrow::array::Int64Array;
+    use deltalake::arrow::datatypes::{DataType, Field, Schema};
+    use deltalake::arrow::record_batch::RecordBatch;
+    use deltalake::protocol::SaveMode;
+    use deltalake::DeltaOps;
+
+    async fn append(path: &str, ids: Vec<i64>) {
+        let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)]));
+        let batch = RecordBatch::try_new(schema, vec![Arc::new(Int64Array::from(ids))]).unwrap();
+        DeltaOps::try_from_uri(path)
+            .await
+            .unwrap()
+            .write(vec![batch])
+            .with_save_mode(SaveMode::Append)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_refresh_swaps_in_new_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        append(path, vec![1]).await;
+
+        let table = RefreshableTable::new("ids", deltalake::open_table(path).await.unwrap());
+        assert_eq!(table.version(), 0);
+        assert_eq!(table.refresh().await.unwrap(), None);
+
+        append(path, vec![2]).await;
+        append(path, vec![3]).await;
+        assert_eq!(table.refresh().await.unwrap(), Some(2));
+        assert_eq!(table.version(), 2);
+        assert_eq!(table.refresh().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_older_snapshots_are_never_served() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        append(path, vec![1]).await;
+        append(path, vec![2]).await;
+
+        let table = RefreshableTable::new("ids", deltalake::open_table(path).await.unwrap());
+        let versions = table.subscribe();
+        assert_eq!(table.version(), 1);
+
+        // A refresh that loaded version 0 after a newer commit was served
+        let stale = deltalake::open_table_with_version(path, 0).await.unwrap();
+        assert!(!table.swap(stale));
+        table.replace(deltalake::open_table_with_version(path, 0).await.unwrap());
+        assert_eq!(table.version(), 1);
+        assert_eq!(*versions.borrow(), 1);
+    }
+}
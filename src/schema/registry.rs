@@ -0,0 +1,178 @@
+//! Delta table registry and snapshot cache
+//!
+//! The `SessionContext` only knows the version of each Delta table that was
+//! registered at startup. The registry remembers where each Delta table lives
+//! so resolvers can load historical snapshots for `asOfVersion` /
+//! `asOfTimestamp` arguments. Loaded snapshots are cached per (table, version)
+//! so repeated time-travel queries don't replay the Delta log every time.
+//!
+//! It also tracks the refreshable providers serving the latest version of
+//! each Delta table, so the server can refresh them and report what it serves.
+
+use crate::error::{NouninatorError, Result};
+use crate::schema::refresh::RefreshableTable;
+
+use deltalake::DeltaTable;
+use indexmap::IndexMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Maximum number of historical snapshots kept in memory
+pub const MAX_CACHED_SNAPSHOTS: usize = 32;
+
+/// A point in a Delta table's history
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TableVersion {
+    /// An exact table version
+    Version(i64),
+    /// The latest version committed at or before an RFC 3339 timestamp
+    Timestamp(String),
+}
+
+/// Registry of Delta table locations with a snapshot cache
+#[derive(Default)]
+pub struct TableRegistry {
+    /// Registered table name -> Delta table URI
+    locations: RwLock<HashMap<String, String>>,
+    /// Registered table name -> provider serving the latest version
+    served: RwLock<HashMap<String, Arc<RefreshableTable>>>,
+    /// (table name, version) -> loaded snapshot, oldest first
+    snapshots: Mutex<IndexMap<(String, i64), Arc<DeltaTable>>>,
+}
+
+impl TableRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember the location of a registered Delta table
+    pub fn register_delta(&self, table: &str, location: &str) {
+        self.locations
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(table.to_string(), location.to_string());
+    }
+
+    /// Location of a registered Delta table
+    pub fn location(&self, table: &str) -> Option<String> {
+        self.locations
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(table)
+            .cloned()
+    }
+
+    /// Remember the provider serving the latest version of a Delta table
+    pub fn register_served(&self, table: &str, provider: Arc<RefreshableTable>) {
+        self.served
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(table.to_string(), provider);
+    }
+
+    /// Provider serving the latest version of a Delta table
+    pub fn served_table(&self, table: &str) -> Option<Arc<RefreshableTable>> {
+        self.served
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(table)
+            .cloned()
+    }
+
+    /// Currently served version of every Delta table
+    pub fn served_versions(&self) -> BTreeMap<String, i64> {
+        self.served
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|(table, provider)| (table.clone(), provider.version()))
+            .collect()
+    }
+
+    /// Load (or fetch from cache) a historical snapshot of a table
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Registered table name
+    /// * `version` - Version or timestamp to load
+    pub async fn snapshot(&self, table: &str, version: &TableVersion) -> Result<Arc<DeltaTable>> {
+        let location = self.location(table).ok_or_else(|| {
+            NouninatorError::SchemaGeneration(format!(
+                "Table '{}' is not a Delta table; time travel is unavailable",
+                table
+            ))
+        })?;
+
+        if let TableVersion::Version(v) = version {
+            if let Some(cached) = self.cached(table, *v) {
+                return Ok(cached);
+            }
+        }
+
+        let loaded = match version {
+            TableVersion::Version(v) => deltalake::open_table_with_version(&location, *v).await,
+            TableVersion::Timestamp(ts) => deltalake::open_table_with_ds(&location, ts).await,
+        }
+        .map_err(|e| {
+            NouninatorError::SchemaGeneration(format!(
+                "Failed to load snapshot of '{}': {}",
+                table, e
+            ))
+        })?;
+
+        tracing::debug!("Loaded snapshot of '{}' at version {}", table, loaded.version());
+
+        Ok(self.insert(table, Arc::new(loaded)))
+    }
+
+    /// Number of snapshots currently cached
+    pub fn cached_snapshots(&self) -> usize {
+        self.snapshots.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    fn cached(&self, table: &str, version: i64) -> Option<Arc<DeltaTable>> {
+        self.snapshots
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&(table.to_string(), version))
+            .cloned()
+    }
+
+    fn insert(&self, table: &str, snapshot: Arc<DeltaTable>) -> Arc<DeltaTable> {
+        let mut snapshots = self.snapshots.lock().unwrap_or_else(|e| e.into_inner());
+        let key = (table.to_string(), snapshot.version());
+
+        // A concurrent load of the same version may have won the race
+        if let Some(existing) = snapshots.get(&key) {
+            return existing.clone();
+        }
+
+        snapshots.insert(key, snapshot.clone());
+        while snapshots.len() > MAX_CACHED_SNAPSHOTS {
+            snapshots.shift_remove_index(0);
+        }
+
+        snapshot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_time_travel_requires_delta_table() {
+        let registry = TableRegistry::new();
+        let result = registry.snapshot("csv_table", &TableVersion::Version(0)).await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_register_delta_location() {
+        let registry = TableRegistry::new();
+        registry.register_delta("nouns", "examples/delta/nouns");
+        assert_eq!(registry.location("nouns").as_deref(), Some("examples/delta/nouns"));
+        assert!(registry.location("verbs").is_none());
+    }
+}
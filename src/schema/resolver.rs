@@ -1,573 +1,1921 @@
-/// GraphQL resolvers for query operations
-///
-/// This module provides resolver functions for GraphQL queries, including:
-/// - Get by primary key resolvers
-/// - List with pagination resolvers
-/// - Data conversion from Arrow RecordBatch to GraphQL Value
-
-use crate::config::EntityConfig;
-use crate::error::{NouninatorError, Result};
-use crate::schema::type_mapping::to_snake_case;
-
-use datafusion::arrow::array::*;
-use datafusion::arrow::datatypes::{DataType as ArrowDataType};
-use datafusion::arrow::record_batch::RecordBatch;
-use async_graphql::dynamic::{Field, FieldFuture, FieldValue, ResolverContext, TypeRef};
-use async_graphql::{Name, Value};
-use datafusion::prelude::SessionContext;
-use indexmap::IndexMap;
-use std::sync::Arc;
-
-/// Create get_X(id) resolver for an entity
-///
-/// This creates a resolver that fetches a single entity by its primary key.
-///
-/// # Arguments
-///
-/// * `entity` - Entity configuration
-///
-/// # Returns
-///
-/// A GraphQL Field with the resolver function
-pub fn create_get_resolver(entity: &EntityConfig) -> Field {
-    let table_name = entity.table.clone();
-    let primary_key = entity.primary_key.clone();
-    let primary_key_arg = entity.primary_key.clone(); // Clone for the argument
-    let graphql_name = entity.graphql_name.clone();
-    let field_name = format!("{}", to_snake_case(&graphql_name));
-
-    Field::new(
-        field_name,
-        TypeRef::named(&graphql_name),
-        move |ctx: ResolverContext| {
-            let table_name = table_name.clone();
-            let primary_key = primary_key.clone();
-
-            FieldFuture::new(async move {
-                // Extract primary key value from arguments
-                let pk_arg = ctx
-                    .args
-                    .try_get(&primary_key)
-                    .map_err(|_| format!("Primary key '{}' argument missing", primary_key))?;
-                
-                let pk_value: String = match pk_arg.string() {
-                    Ok(s) => s.to_string(),
-                    Err(_) => return Err("Primary key must be a string".into()),
-                };
-
-                // Get DataFusion context from schema data
-                let datafusion_ctx = ctx
-                    .data::<Arc<SessionContext>>()
-                    .map_err(|_e| "Failed to get DataFusion context")?;
-
-                // Build SQL query
-                let sql = format!(
-                    "SELECT * FROM \"{}\" WHERE \"{}\" = '{}'",
-                    table_name, primary_key, pk_value
-                );
-
-                tracing::debug!("Executing query: {}", sql);
-
-                // Execute query
-                let df = datafusion_ctx
-                    .sql(&sql)
-                    .await
-                    .map_err(|e| format!("Query execution failed: {}", e))?;
-
-                let batches = df
-                    .collect()
-                    .await
-                    .map_err(|e| format!("Data collection failed: {}", e))?;
-
-                // Convert first row to GraphQL Value
-                if batches.is_empty() || batches[0].num_rows() == 0 {
-                    return Ok(None);
-                }
-
-                let record_batch = &batches[0];
-                let row_value = record_batch_to_graphql_value(record_batch, 0)
-                    .map_err(|e| format!("Failed to convert row: {}", e))?;
-
-                // Return as owned_any so async-graphql can handle field extraction
-                Ok(Some(FieldValue::owned_any(row_value)))
-            })
-        },
-    )
-    .argument(async_graphql::dynamic::InputValue::new(
-        primary_key_arg,
-        TypeRef::named_nn(TypeRef::ID),
-    ))
-}
-
-/// Create list_X(limit, offset) resolver for an entity
-///
-/// This creates a resolver that fetches a paginated list of entities.
-///
-/// # Arguments
-///
-/// * `entity` - Entity configuration
-///
-/// # Returns
-///
-/// A GraphQL Field with the resolver function
-pub fn create_list_resolver(entity: &EntityConfig) -> Field {
-    let table_name = entity.table.clone();
-    let graphql_name = entity.graphql_name.clone();
-    let field_name = format!("list_{}", to_snake_case(&graphql_name));
-
-    Field::new(
-        field_name,
-        TypeRef::named_nn_list_nn(&graphql_name),
-        move |ctx: ResolverContext| {
-            let table_name = table_name.clone();
-
-            FieldFuture::new(async move {
-                // Extract pagination arguments
-                let limit: i64 = ctx
-                    .args
-                    .try_get("limit")
-                    .ok()
-                    .and_then(|v| v.i64().ok())
-                    .unwrap_or(100);
-                let offset: i64 = ctx
-                    .args
-                    .try_get("offset")
-                    .ok()
-                    .and_then(|v| v.i64().ok())
-                    .unwrap_or(0);
-
-                // Enforce max limit
-                let limit = limit.min(1000);
-
-                // Get DataFusion context from schema data
-                let datafusion_ctx = ctx
-                    .data::<Arc<SessionContext>>()
-                    .map_err(|_e| "Failed to get DataFusion context")?;
-
-                // Build SQL query
-                let sql = format!(
-                    "SELECT * FROM \"{}\" LIMIT {} OFFSET {}",
-                    table_name, limit, offset
-                );
-
-                tracing::debug!("Executing query: {}", sql);
-
-                // Execute query
-                let df = datafusion_ctx
-                    .sql(&sql)
-                    .await
-                    .map_err(|e| format!("Query execution failed: {}", e))?;
-
-                let batches = df
-                    .collect()
-                    .await
-                    .map_err(|e| format!("Data collection failed: {}", e))?;
-
-                // Convert all rows to GraphQL array
-                let mut results = Vec::new();
-                for batch in batches {
-                    for row_idx in 0..batch.num_rows() {
-                        let row_value = record_batch_to_graphql_value(&batch, row_idx)
-                            .map_err(|e| format!("Failed to convert row: {}", e))?;
-                        results.push(FieldValue::owned_any(row_value));
-                    }
-                }
-
-                Ok(Some(FieldValue::list(results)))
-            })
-        },
-    )
-    .argument(async_graphql::dynamic::InputValue::new(
-        "limit",
-        TypeRef::named(TypeRef::INT),
-    ))
-    .argument(async_graphql::dynamic::InputValue::new(
-        "offset",
-        TypeRef::named(TypeRef::INT),
-    ))
-}
-
-/// Convert a single row from RecordBatch to GraphQL Value (Object)
-///
-/// This function handles type conversion from Arrow types to GraphQL types,
-/// including special handling for timestamps, dates, and ID fields.
-///
-/// # Arguments
-///
-/// * `batch` - The RecordBatch containing the data
-/// * `row_idx` - The index of the row to convert
-///
-/// # Returns
-///
-/// A GraphQL Value::Object representing the row
-pub fn record_batch_to_graphql_value(batch: &RecordBatch, row_idx: usize) -> Result<Value> {
-    let schema = batch.schema();
-    let mut object_map = IndexMap::new();
-
-    for (col_idx, field) in schema.fields().iter().enumerate() {
-        let column = batch.column(col_idx);
-
-        // Check if value is null
-        if column.is_null(row_idx) {
-            object_map.insert(Name::new(field.name()), Value::Null);
-            continue;
-        }
-
-        // Convert based on data type
-        let value = match column.data_type() {
-            ArrowDataType::Int8 => {
-                let array = column.as_any().downcast_ref::<Int8Array>().unwrap();
-                Value::Number((array.value(row_idx) as i32).into())
-            }
-            ArrowDataType::Int16 => {
-                let array = column.as_any().downcast_ref::<Int16Array>().unwrap();
-                Value::Number((array.value(row_idx) as i32).into())
-            }
-            ArrowDataType::Int32 => {
-                let array = column.as_any().downcast_ref::<Int32Array>().unwrap();
-                Value::Number(array.value(row_idx).into())
-            }
-            ArrowDataType::Int64 => {
-                let array = column.as_any().downcast_ref::<Int64Array>().unwrap();
-                // For ID fields, convert to string
-                if field.name().ends_with("_id") || field.name() == "id" {
-                    Value::String(array.value(row_idx).to_string())
-                } else {
-                    Value::Number(array.value(row_idx).into())
-                }
-            }
-            ArrowDataType::UInt8 => {
-                let array = column.as_any().downcast_ref::<UInt8Array>().unwrap();
-                Value::Number(serde_json::Number::from(array.value(row_idx)))
-            }
-            ArrowDataType::UInt16 => {
-                let array = column.as_any().downcast_ref::<UInt16Array>().unwrap();
-                Value::Number(serde_json::Number::from(array.value(row_idx)))
-            }
-            ArrowDataType::UInt32 => {
-                let array = column.as_any().downcast_ref::<UInt32Array>().unwrap();
-                Value::Number(serde_json::Number::from(array.value(row_idx)))
-            }
-            ArrowDataType::UInt64 => {
-                let array = column.as_any().downcast_ref::<UInt64Array>().unwrap();
-                // For ID fields, convert to string
-                if field.name().ends_with("_id") || field.name() == "id" {
-                    Value::String(array.value(row_idx).to_string())
-                } else {
-                    // Note: u64 may not fit in i64/JSON number, so convert to string for large values
-                    let val = array.value(row_idx);
-                    if val <= i64::MAX as u64 {
-                        Value::Number(serde_json::Number::from(val))
-                    } else {
-                        Value::String(val.to_string())
-                    }
-                }
-            }
-            ArrowDataType::Float32 => {
-                let array = column.as_any().downcast_ref::<Float32Array>().unwrap();
-                let f = array.value(row_idx);
-                Value::Number(
-                    serde_json::Number::from_f64(f as f64)
-                        .ok_or_else(|| NouninatorError::SchemaGeneration("Invalid float value".to_string()))?,
-                )
-            }
-            ArrowDataType::Float64 => {
-                let array = column.as_any().downcast_ref::<Float64Array>().unwrap();
-                let f = array.value(row_idx);
-                Value::Number(
-                    serde_json::Number::from_f64(f)
-                        .ok_or_else(|| NouninatorError::SchemaGeneration("Invalid float value".to_string()))?,
-                )
-            }
-            ArrowDataType::Utf8 => {
-                let array = column.as_any().downcast_ref::<StringArray>().unwrap();
-                Value::String(array.value(row_idx).to_string())
-            }
-            ArrowDataType::LargeUtf8 => {
-                let array = column.as_any().downcast_ref::<LargeStringArray>().unwrap();
-                Value::String(array.value(row_idx).to_string())
-            }
-            ArrowDataType::Boolean => {
-                let array = column.as_any().downcast_ref::<BooleanArray>().unwrap();
-                Value::Boolean(array.value(row_idx))
-            }
-            ArrowDataType::Timestamp(unit, _tz) => {
-                use datafusion::arrow::datatypes::TimeUnit;
-                let timestamp_ns = match unit {
-                    TimeUnit::Nanosecond => {
-                        let array = column
-                            .as_any()
-                            .downcast_ref::<TimestampNanosecondArray>()
-                            .unwrap();
-                        array.value(row_idx)
-                    }
-                    TimeUnit::Microsecond => {
-                        let array = column
-                            .as_any()
-                            .downcast_ref::<TimestampMicrosecondArray>()
-                            .unwrap();
-                        array.value(row_idx) * 1_000
-                    }
-                    TimeUnit::Millisecond => {
-                        let array = column
-                            .as_any()
-                            .downcast_ref::<TimestampMillisecondArray>()
-                            .unwrap();
-                        array.value(row_idx) * 1_000_000
-                    }
-                    TimeUnit::Second => {
-                        let array = column
-                            .as_any()
-                            .downcast_ref::<TimestampSecondArray>()
-                            .unwrap();
-                        array.value(row_idx) * 1_000_000_000
-                    }
-                };
-
-                // Convert to ISO 8601 string
-                let secs = timestamp_ns / 1_000_000_000;
-                let nsecs = (timestamp_ns % 1_000_000_000) as u32;
-
-                use chrono::{DateTime, Utc};
-                let datetime = DateTime::<Utc>::from_timestamp(secs, nsecs)
-                    .ok_or_else(|| {
-                        NouninatorError::SchemaGeneration(format!(
-                            "Invalid timestamp: {}",
-                            timestamp_ns
-                        ))
-                    })?;
-                Value::String(datetime.to_rfc3339())
-            }
-            ArrowDataType::Date32 => {
-                let array = column.as_any().downcast_ref::<Date32Array>().unwrap();
-                let days = array.value(row_idx);
-
-                use chrono::NaiveDate;
-                let date = NaiveDate::from_ymd_opt(1970, 1, 1)
-                    .ok_or_else(|| {
-                        NouninatorError::SchemaGeneration("Invalid base date".to_string())
-                    })?
-                    .checked_add_signed(chrono::Duration::days(days as i64))
-                    .ok_or_else(|| {
-                        NouninatorError::SchemaGeneration(format!("Invalid date: {} days", days))
-                    })?;
-
-                Value::String(date.format("%Y-%m-%d").to_string())
-            }
-            ArrowDataType::Date64 => {
-                let array = column.as_any().downcast_ref::<Date64Array>().unwrap();
-                let millis = array.value(row_idx);
-
-                use chrono::NaiveDate;
-                let date = NaiveDate::from_ymd_opt(1970, 1, 1)
-                    .ok_or_else(|| {
-                        NouninatorError::SchemaGeneration("Invalid base date".to_string())
-                    })?
-                    .checked_add_signed(chrono::Duration::milliseconds(millis))
-                    .ok_or_else(|| {
-                        NouninatorError::SchemaGeneration(format!("Invalid date: {} ms", millis))
-                    })?;
-
-                Value::String(date.format("%Y-%m-%d").to_string())
-            }
-            _ => {
-                tracing::warn!(
-                    "Unsupported type {:?} for field '{}', returning null",
-                    column.data_type(),
-                    field.name()
-                );
-                Value::Null
-            }
-        };
-
-        object_map.insert(Name::new(field.name()), value);
-    }
-
-    Ok(Value::Object(object_map))
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use datafusion::arrow::datatypes::{DataType, Field as ArrowField, Schema as ArrowSchema};
-    use std::sync::Arc;
-
-    #[test]
-    fn test_record_batch_to_graphql_value_basic_types() {
-        // Create a simple schema with basic types
-        let schema = Arc::new(ArrowSchema::new(vec![
-            ArrowField::new("id", DataType::Int64, false),
-            ArrowField::new("name", DataType::Utf8, false),
-            ArrowField::new("age", DataType::Int32, true),
-            ArrowField::new("active", DataType::Boolean, false),
-        ]));
-
-        // Create arrays
-        let id_array = Int64Array::from(vec![1]);
-        let name_array = StringArray::from(vec!["Alice"]);
-        let age_array = Int32Array::from(vec![Some(30)]);
-        let active_array = BooleanArray::from(vec![true]);
-
-        // Create record batch
-        let batch = RecordBatch::try_new(
-            schema,
-            vec![
-                Arc::new(id_array),
-                Arc::new(name_array),
-                Arc::new(age_array),
-                Arc::new(active_array),
-            ],
-        )
-        .unwrap();
-
-        // Convert to GraphQL value
-        let result = record_batch_to_graphql_value(&batch, 0).unwrap();
-
-        // Verify the result
-        if let Value::Object(obj) = result {
-            assert_eq!(obj.get("id").unwrap(), &Value::String("1".to_string())); // ID fields are strings
-            assert_eq!(obj.get("name").unwrap(), &Value::String("Alice".to_string()));
-            assert_eq!(obj.get("age").unwrap(), &Value::Number(30.into()));
-            assert_eq!(obj.get("active").unwrap(), &Value::Boolean(true));
-        } else {
-            panic!("Expected Value::Object");
-        }
-    }
-
-    #[test]
-    fn test_record_batch_to_graphql_value_nullable() {
-        // Create schema with nullable field
-        let schema = Arc::new(ArrowSchema::new(vec![
-            ArrowField::new("id", DataType::Int64, false),
-            ArrowField::new("nickname", DataType::Utf8, true),
-        ]));
-
-        // Create arrays with null value
-        let id_array = Int64Array::from(vec![1]);
-        let nickname_array = StringArray::from(vec![None as Option<&str>]);
-
-        // Create record batch
-        let batch = RecordBatch::try_new(
-            schema,
-            vec![Arc::new(id_array), Arc::new(nickname_array)],
-        )
-        .unwrap();
-
-        // Convert to GraphQL value
-        let result = record_batch_to_graphql_value(&batch, 0).unwrap();
-
-        // Verify the result
-        if let Value::Object(obj) = result {
-            assert_eq!(obj.get("id").unwrap(), &Value::String("1".to_string()));
-            assert_eq!(obj.get("nickname").unwrap(), &Value::Null);
-        } else {
-            panic!("Expected Value::Object");
-        }
-    }
-
-    #[test]
-    fn test_record_batch_to_graphql_value_numeric_types() {
-        // Create schema with various numeric types
-        let schema = Arc::new(ArrowSchema::new(vec![
-            ArrowField::new("int8", DataType::Int8, false),
-            ArrowField::new("int16", DataType::Int16, false),
-            ArrowField::new("int32", DataType::Int32, false),
-            ArrowField::new("int64", DataType::Int64, false),
-            ArrowField::new("uint8", DataType::UInt8, false),
-            ArrowField::new("float32", DataType::Float32, false),
-            ArrowField::new("float64", DataType::Float64, false),
-        ]));
-
-        // Create arrays
-        let int8_array = Int8Array::from(vec![10i8]);
-        let int16_array = Int16Array::from(vec![100i16]);
-        let int32_array = Int32Array::from(vec![1000i32]);
-        let int64_array = Int64Array::from(vec![10000i64]);
-        let uint8_array = UInt8Array::from(vec![255u8]);
-        let float32_array = Float32Array::from(vec![3.14f32]);
-        let float64_array = Float64Array::from(vec![2.718f64]);
-
-        // Create record batch
-        let batch = RecordBatch::try_new(
-            schema,
-            vec![
-                Arc::new(int8_array),
-                Arc::new(int16_array),
-                Arc::new(int32_array),
-                Arc::new(int64_array),
-                Arc::new(uint8_array),
-                Arc::new(float32_array),
-                Arc::new(float64_array),
-            ],
-        )
-        .unwrap();
-
-        // Convert to GraphQL value
-        let result = record_batch_to_graphql_value(&batch, 0).unwrap();
-
-        // Verify the result
-        if let Value::Object(obj) = result {
-            assert_eq!(obj.get("int8").unwrap(), &Value::Number(10.into()));
-            assert_eq!(obj.get("int16").unwrap(), &Value::Number(100.into()));
-            assert_eq!(obj.get("int32").unwrap(), &Value::Number(1000.into()));
-            assert_eq!(obj.get("int64").unwrap(), &Value::Number(10000.into()));
-            assert_eq!(obj.get("uint8").unwrap(), &Value::Number(255.into()));
-            // Float comparisons
-            match obj.get("float32").unwrap() {
-                Value::Number(n) => {
-                    let f = n.as_f64().unwrap();
-                    assert!((f - 3.14).abs() < 0.01);
-                }
-                _ => panic!("Expected number"),
-            }
-            match obj.get("float64").unwrap() {
-                Value::Number(n) => {
-                    let f = n.as_f64().unwrap();
-                    assert!((f - 2.718).abs() < 0.001);
-                }
-                _ => panic!("Expected number"),
-            }
-        } else {
-            panic!("Expected Value::Object");
-        }
-    }
-
-    #[test]
-    fn test_record_batch_to_graphql_value_id_fields() {
-        // Create schema with ID-like fields
-        let schema = Arc::new(ArrowSchema::new(vec![
-            ArrowField::new("id", DataType::Int64, false),
-            ArrowField::new("user_id", DataType::Int64, false),
-            ArrowField::new("count", DataType::Int64, false), // Not an ID field
-        ]));
-
-        // Create arrays
-        let id_array = Int64Array::from(vec![123]);
-        let user_id_array = Int64Array::from(vec![456]);
-        let count_array = Int64Array::from(vec![789]);
-
-        // Create record batch
-        let batch = RecordBatch::try_new(
-            schema,
-            vec![
-                Arc::new(id_array),
-                Arc::new(user_id_array),
-                Arc::new(count_array),
-            ],
-        )
-        .unwrap();
-
-        // Convert to GraphQL value
-        let result = record_batch_to_graphql_value(&batch, 0).unwrap();
-
-        // Verify the result - ID fields should be strings, non-ID int64 should be numbers
-        if let Value::Object(obj) = result {
-            assert_eq!(obj.get("id").unwrap(), &Value::String("123".to_string()));
-            assert_eq!(obj.get("user_id").unwrap(), &Value::String("456".to_string()));
-            assert_eq!(obj.get("count").unwrap(), &Value::Number(789.into()));
-        } else {
-            panic!("Expected Value::Object");
-        }
-    }
-}
-
+//! GraphQL resolvers for query operations
+//!
+//! This module provides resolver functions for GraphQL queries, including:
+//! - Get by primary key resolvers
+//! - List with pagination resolvers
+//! - Nested relation resolvers
+//! - Relay-style connection resolvers with keyset cursors
+//! - Aggregate resolvers
+//! - Change Data Feed resolvers
+//! - Data conversion from Arrow RecordBatch to GraphQL Value
+
+use crate::config::{EntityConfig, ForbiddenFields, RelationConfig, RelationKind};
+use crate::error::{NouninatorError, Result};
+use crate::schema::access::{authorize_columns, authorize_query, can_query};
+use crate::schema::aggregate::{
+    aggregate_columns, aggregate_row_to_value, aggregate_type_name, count_expr, parse_group_by,
+    COLUMN_FUNCTIONS,
+};
+use crate::schema::changes::{
+    change_data_feed_enabled, change_page, change_page_type_name, read_changes, ChangeRange, MAX_CHANGE_VERSIONS,
+};
+use crate::schema::connection::{connection_type_name, reverse_sort_keys, Cursor};
+use crate::schema::filter::{filter_to_expr, filter_type_name, key_predicate};
+use crate::schema::key::{
+    key_from_args, key_from_list_item, key_list_item_type_name, lookup_type_name, with_key_arguments, KeyValues,
+};
+use crate::schema::loader::{LookupGroup, LookupKey, RowLoader};
+use crate::schema::nested::is_nested_type;
+use crate::schema::ordering::{column_enum_name, order_by_type_name, parse_order_by, SortKey};
+use crate::schema::registry::{TableRegistry, TableVersion};
+use crate::schema::row_filter::row_predicate;
+use crate::schema::row_count::row_budget;
+use crate::schema::scalars::{big_int_to_i64, big_int_value, BIG_INT};
+use crate::schema::projection::{projected_columns, selected_field_names};
+use crate::schema::temporal::{
+    format_duration, format_instant, format_local_datetime, format_time, parse_timezone, IsoDuration,
+};
+use crate::schema::type_mapping::to_snake_case;
+
+use datafusion::arrow::array::timezone::Tz;
+use datafusion::arrow::array::*;
+use datafusion::arrow::datatypes::*;
+use datafusion::arrow::datatypes::{DataType as ArrowDataType, Schema as ArrowSchema};
+use datafusion::arrow::record_batch::RecordBatch;
+use async_graphql::dataloader::DataLoader;
+use async_graphql::dynamic::{Field, FieldFuture, FieldValue, ResolverContext, TypeRef};
+use async_graphql::{Name, SelectionField, Value};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use datafusion::prelude::{ident, DataFrame, SessionContext};
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::OnceCell;
+
+/// Create get_X(id) resolver for an entity
+///
+/// This creates a resolver that fetches a single entity by its primary key.
+/// The key arguments are converted to the primary key columns' Arrow types and
+/// applied as a DataFusion predicate, so they are never interpolated into SQL.
+/// A composite key takes one typed argument per key column.
+///
+/// # Arguments
+///
+/// * `entity` - Entity configuration
+/// * `arrow_schema` - Arrow schema of the entity's table (types the key arguments)
+///
+/// # Returns
+///
+/// A GraphQL Field with the resolver function
+pub fn create_get_resolver(entity: &EntityConfig, arrow_schema: &ArrowSchema) -> Field {
+    let graphql_name = entity.graphql_name.clone();
+    let field_name = to_snake_case(&graphql_name);
+    let entity_arc = Arc::new(entity.clone());
+
+    let field = Field::new(
+        field_name,
+        TypeRef::named(&graphql_name),
+        move |ctx: ResolverContext| {
+            let entity = entity_arc.clone();
+
+            FieldFuture::new(async move {
+                authorize_query(&ctx, &entity)?;
+
+                // Extract primary key values from arguments
+                let key = key_from_args(&ctx, &entity)?;
+
+                let read = read_point(&ctx, &entity.table).await?;
+                let rows = load_rows(&ctx, &entity, &key, &read).await?;
+
+                // Wrap the row so its fields and relations can downcast it
+                Ok(rows.into_iter().next().map(|row| into_field_value(row, &read)))
+            })
+        },
+    );
+
+    with_timezone_argument(with_time_travel_arguments(with_key_arguments(field, entity, arrow_schema)))
+}
+
+/// Create X_by_ids(ids) resolver for an entity
+///
+/// This creates a resolver that fetches many entities by primary key with a
+/// single `IN` query. Results are returned in the order of `ids` as `XLookup`
+/// wrappers whose `node` is `null` for keys that match no row. Entities with a
+/// composite primary key take a list of `XKey` objects instead of `ID`s. At
+/// most `max_lookup_ids` keys are accepted per call.
+///
+/// # Arguments
+///
+/// * `entity` - Entity configuration
+///
+/// # Returns
+///
+/// A GraphQL Field with the resolver function
+pub fn create_by_ids_resolver(entity: &EntityConfig) -> Field {
+    let graphql_name = entity.graphql_name.clone();
+    let field_name = format!("{}_by_ids", to_snake_case(&graphql_name));
+    let id_type = key_list_item_type_name(entity);
+    let entity = Arc::new(entity.clone());
+
+    let field = Field::new(
+        field_name,
+        TypeRef::named_nn_list_nn(lookup_type_name(&graphql_name)),
+        move |ctx: ResolverContext| {
+            let entity = entity.clone();
+
+            FieldFuture::new(async move {
+                authorize_query(&ctx, &entity)?;
+
+                let ids = ctx.args.try_get("ids")?.list()?;
+                let max_ids = entity.max_lookup_ids();
+                if ids.len() > max_ids {
+                    return Err(format!(
+                        "Cannot look up {} ids at once (max_lookup_ids is {})",
+                        ids.len(),
+                        max_ids
+                    )
+                    .into());
+                }
+
+                let keys = ids
+                    .iter()
+                    .map(|id| key_from_list_item(id.as_value(), &entity, "ids"))
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+                let fields = ctx.look_ahead().field("node").selection_fields();
+                let read = read_point(&ctx, &entity.table).await?;
+                let rows = load_rows_by_keys(&ctx, &entity, &keys, &fields, None, &read).await?;
+
+                Ok(Some(FieldValue::list(rows.into_iter().map(|rows| {
+                    let node = rows.into_iter().next().unwrap_or(Value::Null);
+                    into_field_value(object_value([("node", node)]), &read)
+                }))))
+            })
+        },
+    )
+    .argument(async_graphql::dynamic::InputValue::new(
+        "ids",
+        TypeRef::named_nn_list_nn(id_type),
+    ));
+
+    with_timezone_argument(with_time_travel_arguments(field))
+}
+
+/// Create list_X(filter, orderBy, limit, offset) resolver for an entity
+///
+/// This creates a resolver that fetches a filtered, sorted, paginated list of entities.
+/// The `filter` argument is translated into a DataFusion predicate so it can be
+/// pushed down into the table scan. Results are always ordered (by the primary key
+/// unless `orderBy` says otherwise) so page boundaries are stable.
+///
+/// # Arguments
+///
+/// * `entity` - Entity configuration
+///
+/// # Returns
+///
+/// A GraphQL Field with the resolver function
+pub fn create_list_resolver(entity: &EntityConfig) -> Field {
+    let graphql_name = entity.graphql_name.clone();
+    let field_name = format!("list_{}", to_snake_case(&graphql_name));
+    let entity = Arc::new(entity.clone());
+
+    let field = Field::new(
+        field_name,
+        TypeRef::named_nn_list_nn(&graphql_name),
+        move |ctx: ResolverContext| {
+            let entity = entity.clone();
+
+            FieldFuture::new(async move {
+                authorize_query(&ctx, &entity)?;
+                let read = read_point(&ctx, &entity.table).await?;
+                let rows = fetch_list(&ctx, &entity, None, &read).await?;
+
+                // Convert all rows to GraphQL array
+                Ok(Some(into_field_value(Value::List(rows), &read)))
+            })
+        },
+    );
+
+    with_timezone_argument(with_time_travel_arguments(with_list_arguments(field, &graphql_name)))
+}
+
+/// Create X_aggregate(filter, groupBy) resolver for an entity
+///
+/// This creates a resolver returning one `XAggregate` per group (or a single
+/// one without `groupBy`). Only the aggregate functions and columns selected
+/// in the query are computed, and the filter is pushed down into the scan.
+///
+/// # Arguments
+///
+/// * `entity` - Entity configuration
+///
+/// # Returns
+///
+/// A GraphQL Field with the resolver function
+pub fn create_aggregate_resolver(entity: &EntityConfig) -> Field {
+    let graphql_name = entity.graphql_name.clone();
+    let field_name = format!("{}_aggregate", to_snake_case(&graphql_name));
+    let entity_arc = Arc::new(entity.clone());
+
+    let field = Field::new(
+        field_name,
+        TypeRef::named_nn_list_nn(aggregate_type_name(&graphql_name)),
+        move |ctx: ResolverContext| {
+            let entity = entity_arc.clone();
+
+            FieldFuture::new(async move {
+                authorize_query(&ctx, &entity)?;
+
+                let read = read_point(&ctx, &entity.table).await?;
+                let df = filtered_dataframe(&ctx, &entity, &read).await?;
+                let schema = df.schema().as_arrow().clone();
+
+                let group_by = parse_group_by(
+                    ctx.args.get("groupBy").as_ref().map(|v| v.as_value()),
+                    &schema,
+                )
+                .map_err(|e| format!("Invalid groupBy: {}", e))?;
+
+                // Only compute the selected aggregates
+                let lookahead = ctx.look_ahead();
+                let mut functions = Vec::new();
+                for function in COLUMN_FUNCTIONS {
+                    let columns = aggregate_columns(function, &schema);
+                    let selected =
+                        selected_field_names(&lookahead.field(function.field_name()).selection_fields());
+                    for column in selected {
+                        if columns.iter().any(|(c, _)| *c == column) {
+                            functions.push((function, column));
+                        }
+                    }
+                }
+
+                let mut aggregates = vec![count_expr()];
+                aggregates.extend(functions.iter().map(|(f, column)| f.to_expr(column)));
+
+                let mut df = df
+                    .aggregate(group_by.iter().map(ident).collect(), aggregates)
+                    .map_err(|e| format!("Aggregation failed: {}", e))?;
+
+                // Deterministic group order
+                if !group_by.is_empty() {
+                    df = df
+                        .sort(group_by.iter().map(|c| SortKey::asc(c.as_str()).to_expr()).collect())
+                        .map_err(|e| format!("Aggregation failed: {}", e))?;
+                }
+
+                tracing::debug!("Executing plan: {}", df.logical_plan().display_indent());
+
+                let batches = df
+                    .collect()
+                    .await
+                    .map_err(|e| format!("Data collection failed: {}", e))?;
+
+                let results = batches_to_values(&batches, read.tz()?.as_ref())?
+                    .into_iter()
+                    .map(|row| FieldValue::owned_any(aggregate_row_to_value(row, &group_by, &functions)));
+
+                Ok(Some(FieldValue::list(results)))
+            })
+        },
+    )
+    .argument(async_graphql::dynamic::InputValue::new(
+        "filter",
+        TypeRef::named(filter_type_name(&entity.graphql_name)),
+    ))
+    .argument(async_graphql::dynamic::InputValue::new(
+        "groupBy",
+        TypeRef::named_nn_list(column_enum_name(&entity.graphql_name)),
+    ));
+
+    with_timezone_argument(with_time_travel_arguments(field))
+}
+
+/// Create X_changes resolver for a Delta-backed entity
+///
+/// Reads the table's Change Data Feed between two versions and/or commit
+/// timestamps. `fromVersion` is required and `toVersion` defaults to the
+/// version currently served. A page covers at most `MAX_CHANGE_VERSIONS`
+/// commits and `limit` rows; its `nextVersion`/`nextOffset` are the
+/// `fromVersion`/`offset` to continue from, so a downstream service syncs by
+/// following them while `hasMore` and keeps the last ones for its next sync.
+///
+/// # Arguments
+///
+/// * `entity` - Entity configuration
+///
+/// # Returns
+///
+/// A GraphQL Field with the resolver function
+pub fn create_changes_resolver(entity: &EntityConfig) -> Field {
+    let graphql_name = entity.graphql_name.clone();
+    let field_name = format!("{}_changes", to_snake_case(&graphql_name));
+    let entity_arc = Arc::new(entity.clone());
+
+    let field = Field::new(
+        field_name,
+        TypeRef::named_nn(change_page_type_name(&graphql_name)),
+        move |ctx: ResolverContext| {
+            let entity = entity_arc.clone();
+
+            FieldFuture::new(async move {
+                authorize_query(&ctx, &entity)?;
+
+                let registry = ctx
+                    .data::<Arc<TableRegistry>>()
+                    .map_err(|_e| "Failed to get table registry")?;
+                let snapshot = registry
+                    .served_table(&entity.table)
+                    .ok_or_else(|| format!("Table '{}' is not a Delta table", entity.table))?
+                    .snapshot();
+
+                if !change_data_feed_enabled(&snapshot) {
+                    return Err(format!(
+                        "Change Data Feed is not enabled on '{}' (set delta.enableChangeDataFeed = true)",
+                        entity.table
+                    )
+                    .into());
+                }
+
+                let from_version = optional_big_int_arg(&ctx, "fromVersion")?.ok_or("'fromVersion' is required")?;
+                let requested_to = optional_big_int_arg(&ctx, "toVersion")?.unwrap_or(snapshot.version());
+                if from_version > requested_to {
+                    return Err("'fromVersion' must not be greater than 'toVersion'".into());
+                }
+                let range = ChangeRange {
+                    from_version,
+                    to_version: requested_to.min(from_version.saturating_add(MAX_CHANGE_VERSIONS - 1)),
+                    from_timestamp: optional_timestamp_arg(&ctx, "fromTimestamp")?,
+                    to_timestamp: optional_timestamp_arg(&ctx, "toTimestamp")?,
+                };
+
+                // One extra row tells whether the page is full
+                let (offset, limit) = page_arguments(&ctx);
+                let filter = ctx.args.get("filter").map(|filter| filter.as_value().clone());
+                let row_filter = row_predicate(&ctx, &entity)?;
+                let timezone = requested_timezone(&ctx)?;
+                let rows = read_changes(
+                    &snapshot,
+                    &range,
+                    filter.as_ref(),
+                    row_filter.as_ref(),
+                    timezone.as_ref(),
+                    entity.primary_key.columns(),
+                    Some((offset, limit + 1)),
+                )
+                .await
+                .map_err(|e| e.to_string())?;
+
+                Ok(Some(FieldValue::owned_any(change_page(rows, &range, requested_to, offset, limit))))
+            })
+        },
+    )
+    .argument(async_graphql::dynamic::InputValue::new(
+        "fromVersion",
+        TypeRef::named_nn(BIG_INT),
+    ))
+    .argument(async_graphql::dynamic::InputValue::new(
+        "toVersion",
+        TypeRef::named(BIG_INT),
+    ))
+    .argument(async_graphql::dynamic::InputValue::new(
+        "fromTimestamp",
+        TypeRef::named("DateTime"),
+    ))
+    .argument(async_graphql::dynamic::InputValue::new(
+        "toTimestamp",
+        TypeRef::named("DateTime"),
+    ))
+    .argument(async_graphql::dynamic::InputValue::new(
+        "filter",
+        TypeRef::named(filter_type_name(&entity.graphql_name)),
+    ))
+    .argument(async_graphql::dynamic::InputValue::new(
+        "limit",
+        TypeRef::named(TypeRef::INT),
+    ))
+    .argument(
+        async_graphql::dynamic::InputValue::new("offset", TypeRef::named(TypeRef::INT))
+            .description("Changes of `fromVersion` to skip (a page's `nextOffset`)"),
+    );
+
+    with_timezone_argument(field)
+}
+
+/// Create the nested field for a relation on the source entity's type
+///
+/// `one` relations resolve to the first target row whose `remote_column` equals
+/// the parent's `local_column`; `many` relations resolve to a list and accept
+/// the same `filter`/`orderBy`/`limit`/`offset` arguments as `list_X`.
+/// Following a relation reveals its `local_column`, so callers who may not
+/// read that column unmasked get the source entity's `forbidden_fields`
+/// behaviour instead.
+///
+/// # Arguments
+///
+/// * `source` - Configuration of the entity the relation is on
+/// * `relation` - Relation configuration
+/// * `target` - Configuration of the related entity
+///
+/// # Returns
+///
+/// A GraphQL Field with the resolver function
+pub fn create_relation_field(source: &EntityConfig, relation: &RelationConfig, target: &EntityConfig) -> Field {
+    let kind = relation.kind;
+    let type_ref = match kind {
+        RelationKind::One => TypeRef::named(&target.graphql_name),
+        RelationKind::Many => TypeRef::named_nn_list_nn(&target.graphql_name),
+    };
+    let source_arc = Arc::new(source.clone());
+    let relation_arc = Arc::new(relation.clone());
+    let target_arc = Arc::new(target.clone());
+
+    let field = Field::new(relation.name.clone(), type_ref, move |ctx: ResolverContext| {
+        let source = source_arc.clone();
+        let relation = relation_arc.clone();
+        let target = target_arc.clone();
+
+        FieldFuture::new(async move {
+            // Relations to an entity the caller may not query fail or resolve to nothing
+            if !can_query(&ctx, &target) {
+                return forbidden_relation(
+                    target.forbidden_fields(),
+                    kind,
+                    format!("Not authorized to query {}", target.graphql_name),
+                );
+            }
+            // ...and so do relations joining on a column the caller may not read
+            if authorize_columns(&ctx, &source, [&relation.local_column]).is_err() {
+                return forbidden_relation(
+                    source.forbidden_fields(),
+                    kind,
+                    format!("Not authorized to read {}.{}", source.graphql_name, relation.local_column),
+                );
+            }
+            authorize_query(&ctx, &target)?;
+
+            // Read the join key from the parent row
+            let (parent, _) = parent_row(&ctx)?;
+            let read = read_point(&ctx, &target.table).await?;
+            let local_value = match parent {
+                Value::Object(obj) => obj.get(relation.local_column.as_str()).cloned(),
+                _ => None,
+            }
+            .unwrap_or(Value::Null);
+
+            match kind {
+                RelationKind::One => {
+                    if matches!(local_value, Value::Null) {
+                        return Ok(None);
+                    }
+                    let key = [(relation.remote_column.clone(), local_value)];
+                    let rows = load_rows(&ctx, &target, &key, &read).await?;
+                    Ok(rows.into_iter().next().map(|row| into_field_value(row, &read)))
+                }
+                RelationKind::Many => {
+                    if matches!(local_value, Value::Null) {
+                        return Ok(Some(FieldValue::list(Vec::<FieldValue>::new())));
+                    }
+                    // Without a filter or explicit order the rows can be batched
+                    // across parents, each parent's page read in primary key order
+                    let rows = if ctx.args.get("filter").is_none() && ctx.args.get("orderBy").is_none() {
+                        let key = [(relation.remote_column.clone(), local_value.clone())];
+                        load_page(&ctx, &target, &key, page_arguments(&ctx), &read).await?
+                    } else {
+                        fetch_list(
+                            &ctx,
+                            &target,
+                            Some((relation.remote_column.as_str(), &local_value)),
+                            &read,
+                        )
+                        .await?
+                    };
+                    Ok(Some(into_field_value(Value::List(rows), &read)))
+                }
+            }
+        })
+    });
+
+    let field = with_timezone_argument(field);
+    match kind {
+        RelationKind::One => field,
+        RelationKind::Many => with_list_arguments(field, &target.graphql_name),
+    }
+}
+
+/// Resolve a relation the caller may not follow: an error, or `null`/`[]`
+fn forbidden_relation(
+    forbidden: ForbiddenFields,
+    kind: RelationKind,
+    message: String,
+) -> async_graphql::Result<Option<FieldValue<'static>>> {
+    match (forbidden, kind) {
+        (ForbiddenFields::Error, _) => Err(message.into()),
+        (ForbiddenFields::Null, RelationKind::One) => Ok(None),
+        (ForbiddenFields::Null, RelationKind::Many) => Ok(Some(FieldValue::list(Vec::<FieldValue>::new()))),
+    }
+}
+
+/// Add the `filter`, `orderBy`, `limit` and `offset` arguments of a list field
+fn with_list_arguments(field: Field, graphql_name: &str) -> Field {
+    field
+        .argument(async_graphql::dynamic::InputValue::new(
+            "filter",
+            TypeRef::named(filter_type_name(graphql_name)),
+        ))
+        .argument(async_graphql::dynamic::InputValue::new(
+            "orderBy",
+            TypeRef::named_nn_list(order_by_type_name(graphql_name)),
+        ))
+        .argument(async_graphql::dynamic::InputValue::new(
+            "limit",
+            TypeRef::named(TypeRef::INT),
+        ))
+        .argument(async_graphql::dynamic::InputValue::new(
+            "offset",
+            TypeRef::named(TypeRef::INT),
+        ))
+}
+
+/// Load all rows of an entity where each `(column, value)` pair of `key` matches
+///
+/// Goes through the request's `DataLoader`, so lookups made while resolving
+/// sibling fields are coalesced into one `IN` query. Values are converted to
+/// their columns' Arrow types (never interpolated into SQL), and only the
+/// selected columns are read.
+pub(crate) async fn load_rows(
+    ctx: &ResolverContext<'_>,
+    entity: &EntityConfig,
+    key: &[(String, Value)],
+    read: &ReadPoint,
+) -> std::result::Result<Vec<Value>, async_graphql::Error> {
+    let fields = ctx.look_ahead().selection_fields();
+    let mut rows = load_rows_by_keys(ctx, entity, &[key.to_vec()], &fields, None, read).await?;
+    Ok(rows.pop().unwrap_or_default())
+}
+
+/// Load one `(offset, limit)` page of the rows matching `key`, in primary key order
+///
+/// Like [`load_rows`], but the page is applied per key inside the batched
+/// query, so only the requested rows of each key are read.
+async fn load_page(
+    ctx: &ResolverContext<'_>,
+    entity: &EntityConfig,
+    key: &[(String, Value)],
+    page: (usize, usize),
+    read: &ReadPoint,
+) -> std::result::Result<Vec<Value>, async_graphql::Error> {
+    let fields = ctx.look_ahead().selection_fields();
+    let mut rows = load_rows_by_keys(ctx, entity, &[key.to_vec()], &fields, Some(page), read).await?;
+    Ok(rows.pop().unwrap_or_default())
+}
+
+/// Load the rows matching each of `keys`, in the order of `keys`
+///
+/// Every key must name the same columns. All keys go to the `DataLoader` at
+/// once, so they are fetched with a single query. Only the columns needed by
+/// the selected `fields` are read, and only `page` of each key's rows if given.
+async fn load_rows_by_keys(
+    ctx: &ResolverContext<'_>,
+    entity: &EntityConfig,
+    keys: &[KeyValues],
+    fields: &[SelectionField<'_>],
+    page: Option<(usize, usize)>,
+    read: &ReadPoint,
+) -> std::result::Result<Vec<Vec<Value>>, async_graphql::Error> {
+    let loader = ctx
+        .data::<DataLoader<RowLoader>>()
+        .map_err(|_e| "Failed to get row loader")?;
+    let Some(first) = keys.first() else {
+        return Ok(Vec::new());
+    };
+
+    let df = versioned_dataframe(ctx, &entity.table, read).await?;
+    let schema = df.schema().as_arrow().clone();
+
+    let group = Arc::new(LookupGroup {
+        table: entity.table.clone(),
+        columns: first.iter().map(|(column, _)| column.clone()).collect(),
+        primary_key: entity.primary_key.columns().to_vec(),
+        version: read.as_of.as_ref().map(|as_of| as_of.version),
+        projection: selection_columns(entity, fields, &[], &schema),
+        timezone: read.timezone.clone(),
+        row_filter: row_predicate(ctx, entity)?,
+        page,
+    });
+    let lookup_keys = keys
+        .iter()
+        .map(|key| {
+            let values: Vec<&Value> = key.iter().map(|(_, value)| value).collect();
+            LookupKey::new(group.clone(), &values, &schema).map_err(|e| format!("Invalid primary key: {}", e))
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let rows = loader.load_many(lookup_keys.iter().cloned()).await?;
+    Ok(lookup_keys
+        .iter()
+        .map(|key| rows.get(key).cloned().unwrap_or_default())
+        .collect())
+}
+
+/// Read the `offset` and `limit` arguments (limit defaults to 100, capped at
+/// 1000 and at the rows the caller's API key may still be sent)
+fn page_arguments(ctx: &ResolverContext<'_>) -> (usize, usize) {
+    // Extract pagination arguments
+    let limit: i64 = ctx
+        .args
+        .try_get("limit")
+        .ok()
+        .and_then(|v| v.i64().ok())
+        .unwrap_or(100);
+    let offset: i64 = ctx
+        .args
+        .try_get("offset")
+        .ok()
+        .and_then(|v| v.i64().ok())
+        .unwrap_or(0);
+
+    // Enforce max limit
+    (offset.max(0) as usize, (limit.clamp(0, 1000) as usize).min(row_budget(ctx)))
+}
+
+/// Fetch a filtered, sorted page of an entity's rows
+///
+/// Reads the `filter`, `orderBy`, `limit` and `offset` arguments from the
+/// field being resolved. `key` optionally restricts rows to those where a
+/// column equals a value (used for `many` relations).
+async fn fetch_list(
+    ctx: &ResolverContext<'_>,
+    entity: &EntityConfig,
+    key: Option<(&str, &Value)>,
+    read: &ReadPoint,
+) -> std::result::Result<Vec<Value>, async_graphql::Error> {
+    let (offset, limit) = page_arguments(ctx);
+
+    let mut df = filtered_dataframe(ctx, entity, read).await?;
+
+    if let Some((column, value)) = key {
+        let predicate = key_predicate(column, value, df.schema().as_arrow())
+            .map_err(|e| e.to_string())?;
+        df = df
+            .filter(predicate)
+            .map_err(|e| format!("Query execution failed: {}", e))?;
+    }
+
+    // Apply ordering (primary key is always the final tiebreaker)
+    let sort_keys = parse_order_by(
+        ctx.args.get("orderBy").as_ref().map(|v| v.as_value()),
+        &entity.primary_key,
+        df.schema().as_arrow(),
+    )
+    .map_err(|e| format!("Invalid orderBy: {}", e))?;
+
+    if !sort_keys.is_empty() {
+        df = df
+            .sort(sort_keys.iter().map(SortKey::to_expr).collect())
+            .map_err(|e| format!("Invalid orderBy: {}", e))?;
+    }
+
+    let df = df
+        .limit(offset, Some(limit))
+        .map_err(|e| format!("Query execution failed: {}", e))?;
+
+    // Only read the columns the client selected
+    let df = project_selection(df, entity, &ctx.look_ahead().selection_fields(), &[])?;
+
+    tracing::debug!("Executing plan: {}", df.logical_plan().display_indent());
+
+    let batches = df
+        .collect()
+        .await
+        .map_err(|e| format!("Data collection failed: {}", e))?;
+
+    Ok(batches_to_values(&batches, read.tz()?.as_ref())?)
+}
+
+/// Create X_connection(filter, orderBy, first, after, last, before) resolver for an entity
+///
+/// This creates a Relay-style connection resolver using opaque keyset cursors
+/// over the sort key (the primary key unless `orderBy` says otherwise). Each page
+/// is fetched with a predicate on the cursor's sort key values rather than an
+/// offset, so deep pages are cheap and stable while the table is appended to.
+///
+/// # Arguments
+///
+/// * `entity` - Entity configuration
+///
+/// # Returns
+///
+/// A GraphQL Field with the resolver function
+pub fn create_connection_resolver(entity: &EntityConfig) -> Field {
+    let graphql_name = entity.graphql_name.clone();
+    let field_name = format!("{}_connection", to_snake_case(&graphql_name));
+    let filter_type = filter_type_name(&graphql_name);
+    let order_by_type = order_by_type_name(&graphql_name);
+    let entity = Arc::new(entity.clone());
+
+    let field = Field::new(
+        field_name,
+        TypeRef::named_nn(connection_type_name(&graphql_name)),
+        move |ctx: ResolverContext| {
+            let entity = entity.clone();
+
+            FieldFuture::new(async move {
+                authorize_query(&ctx, &entity)?;
+
+                let first = optional_i64_arg(&ctx, "first")?;
+                let last = optional_i64_arg(&ctx, "last")?;
+                if first.is_some() && last.is_some() {
+                    return Err("Cannot combine 'first' and 'last'".into());
+                }
+
+                // Paginating backwards scans in reverse order and flips the page afterwards
+                let backward = last.is_some();
+                let page_size = (first.or(last).unwrap_or(100).clamp(0, 1000) as usize).min(row_budget(&ctx));
+
+                let after = optional_cursor_arg(&ctx, "after")?;
+                let before = optional_cursor_arg(&ctx, "before")?;
+
+                let read = read_point(&ctx, &entity.table).await?;
+                let mut df = filtered_dataframe(&ctx, &entity, &read).await?;
+                let schema = df.schema().as_arrow().clone();
+
+                let sort_keys = parse_order_by(
+                    ctx.args.get("orderBy").as_ref().map(|v| v.as_value()),
+                    &entity.primary_key,
+                    &schema,
+                )
+                .map_err(|e| format!("Invalid orderBy: {}", e))?;
+                // Cursors carry the raw sort key values, primary key tiebreakers included
+                authorize_columns(&ctx, &entity, sort_keys.iter().map(|key| &key.column))?;
+
+                // Only count the full result set when the client asks for it
+                let total_count = if ctx.look_ahead().field("totalCount").exists() {
+                    df.clone()
+                        .count()
+                        .await
+                        .map_err(|e| format!("Query execution failed: {}", e))?
+                } else {
+                    0
+                };
+
+                if let Some(cursor) = &after {
+                    let predicate = cursor
+                        .after_predicate(&sort_keys, &schema)
+                        .map_err(|e| format!("Invalid 'after' cursor: {}", e))?;
+                    df = df
+                        .filter(predicate)
+                        .map_err(|e| format!("Invalid 'after' cursor: {}", e))?;
+                }
+
+                if let Some(cursor) = &before {
+                    let predicate = cursor
+                        .after_predicate(&reverse_sort_keys(&sort_keys), &schema)
+                        .map_err(|e| format!("Invalid 'before' cursor: {}", e))?;
+                    df = df
+                        .filter(predicate)
+                        .map_err(|e| format!("Invalid 'before' cursor: {}", e))?;
+                }
+
+                let scan_keys = if backward {
+                    reverse_sort_keys(&sort_keys)
+                } else {
+                    sort_keys.clone()
+                };
+
+                // Fetch one extra row to find out whether another page exists
+                let df = df
+                    .sort(scan_keys.iter().map(SortKey::to_expr).collect())
+                    .and_then(|df| df.limit(0, Some(page_size + 1)))
+                    .map_err(|e| format!("Query execution failed: {}", e))?;
+
+                // Only read the selected node columns, plus the sort key for cursors
+                let required: Vec<&str> = sort_keys.iter().map(|k| k.column.as_str()).collect();
+                let df = project_selection(
+                    df,
+                    &entity,
+                    &ctx.look_ahead().field("edges").field("node").selection_fields(),
+                    &required,
+                )?;
+
+                tracing::debug!("Executing plan: {}", df.logical_plan().display_indent());
+
+                let batches = df
+                    .collect()
+                    .await
+                    .map_err(|e| format!("Data collection failed: {}", e))?;
+
+                let mut rows = batches_to_values(&batches, read.tz()?.as_ref())?;
+                let has_more = rows.len() > page_size;
+                rows.truncate(page_size);
+                if backward {
+                    rows.reverse();
+                }
+
+                let mut edges = Vec::with_capacity(rows.len());
+                for row in rows {
+                    let cursor = Cursor::for_row(&row, &sort_keys)
+                        .map_err(|e| format!("Failed to build cursor: {}", e))?
+                        .encode();
+                    edges.push(object_value([
+                        ("cursor", Value::String(cursor)),
+                        ("node", row),
+                    ]));
+                }
+
+                let edge_cursor = |edge: Option<&Value>| match edge {
+                    Some(Value::Object(obj)) => obj.get("cursor").cloned().unwrap_or(Value::Null),
+                    _ => Value::Null,
+                };
+
+                let page_info = object_value([
+                    (
+                        "hasNextPage",
+                        Value::Boolean(if backward { before.is_some() } else { has_more }),
+                    ),
+                    (
+                        "hasPreviousPage",
+                        Value::Boolean(if backward { has_more } else { after.is_some() }),
+                    ),
+                    ("startCursor", edge_cursor(edges.first())),
+                    ("endCursor", edge_cursor(edges.last())),
+                ]);
+
+                let connection = object_value([
+                    ("edges", Value::List(edges)),
+                    ("pageInfo", page_info),
+                    ("totalCount", big_int_value(total_count as u64)),
+                ]);
+
+                Ok(Some(into_field_value(connection, &read)))
+            })
+        },
+    )
+    .argument(async_graphql::dynamic::InputValue::new(
+        "filter",
+        TypeRef::named(filter_type),
+    ))
+    .argument(async_graphql::dynamic::InputValue::new(
+        "orderBy",
+        TypeRef::named_nn_list(order_by_type),
+    ))
+    .argument(async_graphql::dynamic::InputValue::new(
+        "first",
+        TypeRef::named(TypeRef::INT),
+    ))
+    .argument(async_graphql::dynamic::InputValue::new(
+        "after",
+        TypeRef::named(TypeRef::STRING),
+    ))
+    .argument(async_graphql::dynamic::InputValue::new(
+        "last",
+        TypeRef::named(TypeRef::INT),
+    ))
+    .argument(async_graphql::dynamic::InputValue::new(
+        "before",
+        TypeRef::named(TypeRef::STRING),
+    ));
+
+    with_timezone_argument(with_time_travel_arguments(field))
+}
+
+/// Add the `asOfVersion` and `asOfTimestamp` time travel arguments
+fn with_time_travel_arguments(field: Field) -> Field {
+    field
+        .argument(
+            async_graphql::dynamic::InputValue::new("asOfVersion", TypeRef::named(BIG_INT))
+                .description("Read the Delta table as of this version"),
+        )
+        .argument(
+            async_graphql::dynamic::InputValue::new("asOfTimestamp", TypeRef::named("DateTime"))
+                .description("Read the Delta table as of the latest version committed at or before this time"),
+        )
+}
+
+/// Add the `timezone` argument (see `timezone_argument`)
+fn with_timezone_argument(field: Field) -> Field {
+    field.argument(timezone_argument())
+}
+
+/// The `timezone` argument, choosing the zone `DateTime` values are rendered in
+pub(crate) fn timezone_argument() -> async_graphql::dynamic::InputValue {
+    async_graphql::dynamic::InputValue::new("timezone", TypeRef::named(TypeRef::STRING)).description(
+        "Render DateTime values in this IANA timezone (e.g. \"Europe/Paris\") or UTC offset \
+         (e.g. \"+02:00\") instead of each column's own zone",
+    )
+}
+
+/// Read the `timezone` argument of the field being resolved
+pub(crate) fn requested_timezone(
+    ctx: &ResolverContext<'_>,
+) -> std::result::Result<Option<Tz>, async_graphql::Error> {
+    optional_string_arg(ctx, "timezone")?
+        .map(|name| parse_timezone(&name).map_err(|e| e.to_string().into()))
+        .transpose()
+}
+
+/// Read the time travel arguments of the field being resolved
+fn requested_version(
+    ctx: &ResolverContext<'_>,
+) -> std::result::Result<Option<TableVersion>, async_graphql::Error> {
+    let version = optional_big_int_arg(ctx, "asOfVersion")?;
+    let timestamp = match ctx.args.get("asOfTimestamp") {
+        Some(value) if !value.is_null() => Some(value.string()?.to_string()),
+        _ => None,
+    };
+
+    match (version, timestamp) {
+        (Some(_), Some(_)) => Err("Cannot combine 'asOfVersion' and 'asOfTimestamp'".into()),
+        (Some(version), None) => Ok(Some(TableVersion::Version(version))),
+        (None, Some(timestamp)) => Ok(Some(TableVersion::Timestamp(timestamp))),
+        (None, None) => Ok(None),
+    }
+}
+
+/// The snapshot of a table a row was read from
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AsOf {
+    /// Registered table name
+    table: String,
+    /// Version read
+    version: i64,
+    /// RFC 3339 time the snapshot stands for: the `asOfTimestamp` asked for,
+    /// else the commit time of `version`
+    timestamp: String,
+}
+
+/// How an entity's rows were read: the snapshot and the `timezone` to render in
+///
+/// Relation fields have no time travel arguments of their own, so they read
+/// their target as of the parent row's snapshot: the same version for the
+/// same table, else the snapshot's time. They inherit its `timezone` unless
+/// they are given one.
+#[derive(Debug, Default)]
+pub(crate) struct ReadPoint {
+    /// Snapshot read (`None` for the currently served version)
+    as_of: Option<AsOf>,
+    /// Zone to render `DateTime` values in (`None` for each column's zone)
+    timezone: Option<String>,
+    /// Reads of relations from rows read at this point, by (table, timezone),
+    /// so sibling rows share one snapshot lookup
+    relations: Mutex<HashMap<(String, Option<String>), RelationRead>>,
+}
+
+/// A relation's read, resolved by the first row that needs it
+type RelationRead = Arc<OnceCell<Arc<ReadPoint>>>;
+
+impl ReadPoint {
+    /// A read of the served version in the field's own `timezone`
+    pub(crate) fn served(ctx: &ResolverContext<'_>) -> std::result::Result<Arc<Self>, async_graphql::Error> {
+        let timezone = optional_string_arg(ctx, "timezone")?;
+        Ok(Arc::new(Self { timezone, ..Default::default() }))
+    }
+
+    /// How a relation from rows read at this point reads `table`
+    async fn relation(
+        &self,
+        table: &str,
+        timezone: Option<String>,
+        registry: &TableRegistry,
+    ) -> std::result::Result<Arc<ReadPoint>, async_graphql::Error> {
+        let cell = self
+            .relations
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry((table.to_string(), timezone.clone()))
+            .or_default()
+            .clone();
+
+        let read = cell
+            .get_or_try_init(|| async {
+                let as_of = match self.version_of(table, registry) {
+                    Some(version) => Some(resolve_as_of(registry, table, version).await?),
+                    None => None,
+                };
+                Ok::<_, async_graphql::Error>(Arc::new(ReadPoint { as_of, timezone, ..Default::default() }))
+            })
+            .await?;
+        Ok(read.clone())
+    }
+
+    /// The version of `table` a relation from a row read at this point reads
+    fn version_of(&self, table: &str, registry: &TableRegistry) -> Option<TableVersion> {
+        let as_of = self.as_of.as_ref()?;
+        if as_of.table == table {
+            Some(TableVersion::Version(as_of.version))
+        } else if registry.location(table).is_some() {
+            Some(TableVersion::Timestamp(as_of.timestamp.clone()))
+        } else {
+            // Tables that aren't Delta tables have no history to read
+            None
+        }
+    }
+
+    /// The parsed `timezone`
+    fn tz(&self) -> std::result::Result<Option<Tz>, async_graphql::Error> {
+        self.timezone
+            .as_deref()
+            .map(|name| parse_timezone(name).map_err(|e| e.to_string().into()))
+            .transpose()
+    }
+}
+
+/// A converted row together with how it was read (see [`ReadPoint`])
+struct ReadRow {
+    value: Value,
+    read: Arc<ReadPoint>,
+}
+
+/// The row (or object built from rows) a field is resolved on, and how it was read
+pub(crate) fn parent_row<'a>(
+    ctx: &ResolverContext<'a>,
+) -> async_graphql::Result<(&'a Value, Option<&'a Arc<ReadPoint>>)> {
+    match ctx.parent_value.downcast_ref::<ReadRow>() {
+        Some(row) => Ok((&row.value, Some(&row.read))),
+        None => Ok((ctx.parent_value.try_downcast_ref::<Value>()?, None)),
+    }
+}
+
+/// Resolve how the field being resolved reads `table`
+///
+/// The field's own `asOfVersion`/`asOfTimestamp` and `timezone` arguments
+/// win; without them it reads as of its parent row (see [`ReadPoint`]).
+pub(crate) async fn read_point(
+    ctx: &ResolverContext<'_>,
+    table: &str,
+) -> std::result::Result<Arc<ReadPoint>, async_graphql::Error> {
+    let registry = ctx
+        .data::<Arc<TableRegistry>>()
+        .map_err(|_e| "Failed to get table registry")?;
+    let inherited = match parent_row(ctx) {
+        Ok((_, Some(read))) => Some(read.clone()),
+        _ => None,
+    };
+
+    let timezone = match optional_string_arg(ctx, "timezone")? {
+        Some(timezone) => Some(timezone),
+        None => inherited.as_ref().and_then(|read| read.timezone.clone()),
+    };
+    match (requested_version(ctx)?, inherited) {
+        (None, Some(parent)) => parent.relation(table, timezone, registry).await,
+        (version, _) => {
+            let as_of = match version {
+                Some(version) => Some(resolve_as_of(registry, table, version).await?),
+                None => None,
+            };
+            Ok(Arc::new(ReadPoint { as_of, timezone, ..Default::default() }))
+        }
+    }
+}
+
+/// Load the snapshot of `table` at `version` and record what it stands for
+async fn resolve_as_of(
+    registry: &TableRegistry,
+    table: &str,
+    version: TableVersion,
+) -> std::result::Result<AsOf, async_graphql::Error> {
+    let snapshot = registry
+        .snapshot(table, &version)
+        .await
+        .map_err(|e| e.to_string())?;
+    let timestamp = match version {
+        TableVersion::Timestamp(timestamp) => timestamp,
+        TableVersion::Version(_) => snapshot
+            .snapshot()
+            .ok()
+            .and_then(|state| state.version_timestamp(snapshot.version()))
+            .and_then(chrono::DateTime::from_timestamp_millis)
+            .map(|time| time.to_rfc3339_opts(chrono::SecondsFormat::Millis, true))
+            .ok_or_else(|| format!("Failed to read the commit time of version {}", snapshot.version()))?,
+    };
+
+    Ok(AsOf {
+        table: table.to_string(),
+        version: snapshot.version(),
+        timestamp,
+    })
+}
+
+/// Load an entity's table as a DataFrame at the snapshot `read` resolved
+async fn versioned_dataframe(
+    ctx: &ResolverContext<'_>,
+    table_name: &str,
+    read: &ReadPoint,
+) -> std::result::Result<DataFrame, async_graphql::Error> {
+    // Get DataFusion context from schema data
+    let datafusion_ctx = ctx
+        .data::<Arc<SessionContext>>()
+        .map_err(|_e| "Failed to get DataFusion context")?;
+
+    let Some(as_of) = &read.as_of else {
+        return datafusion_ctx
+            .table(table_name)
+            .await
+            .map_err(|e| format!("Query execution failed: {}", e).into());
+    };
+
+    // `read_point` already loaded the snapshot, so this hits the cache
+    let registry = ctx
+        .data::<Arc<TableRegistry>>()
+        .map_err(|_e| "Failed to get table registry")?;
+    let snapshot = registry
+        .snapshot(table_name, &TableVersion::Version(as_of.version))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    datafusion_ctx
+        .read_table(snapshot)
+        .map_err(|e| format!("Query execution failed: {}", e).into())
+}
+
+/// Columns to read for the selected fields plus the `required` columns
+///
+/// The primary key is always kept, as are the local columns of any selected
+/// relations (nested resolvers read them from the parent row).
+fn selection_columns(
+    entity: &EntityConfig,
+    fields: &[SelectionField<'_>],
+    required: &[&str],
+    schema: &ArrowSchema,
+) -> Vec<String> {
+    let selected = selected_field_names(fields);
+
+    let mut required = required.to_vec();
+    required.extend(entity.primary_key.columns().iter().map(String::as_str));
+    for relation in &entity.relation {
+        if selected.contains(&relation.name) {
+            required.push(relation.local_column.as_str());
+        }
+    }
+
+    projected_columns(&selected, &required, schema)
+}
+
+/// Project a DataFrame down to the selected fields plus the `required` columns
+fn project_selection(
+    df: DataFrame,
+    entity: &EntityConfig,
+    fields: &[SelectionField<'_>],
+    required: &[&str],
+) -> std::result::Result<DataFrame, async_graphql::Error> {
+    let columns = selection_columns(entity, fields, required, df.schema().as_arrow());
+    let columns: Vec<&str> = columns.iter().map(String::as_str).collect();
+
+    df.select_columns(&columns)
+        .map_err(|e| format!("Query execution failed: {}", e).into())
+}
+
+/// Load an entity's table as a DataFrame with its row filter and the `filter`
+/// argument applied
+async fn filtered_dataframe(
+    ctx: &ResolverContext<'_>,
+    entity: &EntityConfig,
+    read: &ReadPoint,
+) -> std::result::Result<DataFrame, async_graphql::Error> {
+    let mut df = versioned_dataframe(ctx, &entity.table, read).await?;
+
+    // The row filter comes first, so nothing the client sends can widen it
+    if let Some(predicate) = row_predicate(ctx, entity)? {
+        df = df
+            .filter(predicate)
+            .map_err(|e| format!("Query execution failed: {}", e))?;
+    }
+
+    // Apply filter predicate
+    if let Some(filter) = ctx.args.get("filter") {
+        let predicate = filter_to_expr(filter.as_value(), df.schema().as_arrow())
+            .map_err(|e| format!("Invalid filter: {}", e))?;
+        if let Some(predicate) = predicate {
+            df = df
+                .filter(predicate)
+                .map_err(|e| format!("Invalid filter: {}", e))?;
+        }
+    }
+
+    Ok(df)
+}
+
+fn optional_i64_arg(
+    ctx: &ResolverContext<'_>,
+    name: &str,
+) -> std::result::Result<Option<i64>, async_graphql::Error> {
+    match ctx.args.get(name) {
+        Some(value) if !value.is_null() => Ok(Some(value.i64()?)),
+        _ => Ok(None),
+    }
+}
+
+fn optional_big_int_arg(
+    ctx: &ResolverContext<'_>,
+    name: &str,
+) -> std::result::Result<Option<i64>, async_graphql::Error> {
+    match ctx.args.get(name) {
+        Some(value) if !value.is_null() => big_int_to_i64(value.as_value())
+            .map(Some)
+            .ok_or_else(|| format!("'{}' must fit a signed 64-bit integer", name).into()),
+        _ => Ok(None),
+    }
+}
+
+fn optional_string_arg(
+    ctx: &ResolverContext<'_>,
+    name: &str,
+) -> std::result::Result<Option<String>, async_graphql::Error> {
+    match ctx.args.get(name) {
+        Some(value) if !value.is_null() => Ok(Some(value.string()?.to_string())),
+        _ => Ok(None),
+    }
+}
+
+fn optional_timestamp_arg(
+    ctx: &ResolverContext<'_>,
+    name: &str,
+) -> std::result::Result<Option<chrono::DateTime<chrono::Utc>>, async_graphql::Error> {
+    match ctx.args.get(name) {
+        Some(value) if !value.is_null() => {
+            let timestamp = chrono::DateTime::parse_from_rfc3339(value.string()?)
+                .map_err(|e| format!("Invalid '{}': {}", name, e))?;
+            Ok(Some(timestamp.with_timezone(&chrono::Utc)))
+        }
+        _ => Ok(None),
+    }
+}
+
+fn optional_cursor_arg(
+    ctx: &ResolverContext<'_>,
+    name: &str,
+) -> std::result::Result<Option<Cursor>, async_graphql::Error> {
+    match ctx.args.get(name) {
+        Some(value) if !value.is_null() => Ok(Some(
+            Cursor::decode(value.string()?).map_err(|e| format!("Invalid '{}' cursor: {}", name, e))?,
+        )),
+        _ => Ok(None),
+    }
+}
+
+/// Build a `Value::Object` from key/value pairs
+fn object_value<const N: usize>(entries: [(&str, Value); N]) -> Value {
+    Value::Object(
+        entries
+            .into_iter()
+            .map(|(key, value)| (Name::new(key), value))
+            .collect(),
+    )
+}
+
+/// Convert every row of the given batches to GraphQL values
+fn batches_to_values(
+    batches: &[RecordBatch],
+    timezone: Option<&Tz>,
+) -> std::result::Result<Vec<Value>, String> {
+    let mut results = Vec::new();
+    for batch in batches {
+        for row_idx in 0..batch.num_rows() {
+            let row_value = record_batch_to_graphql_value(batch, row_idx, timezone)
+                .map_err(|e| format!("Failed to convert row: {}", e))?;
+            results.push(row_value);
+        }
+    }
+    Ok(results)
+}
+
+/// Build a field that resolves by looking up `name` on the parent value
+pub(crate) fn parent_field(name: impl Into<String>, ty: TypeRef) -> Field {
+    let name = name.into();
+    let key = name.clone();
+    Field::new(name, ty, move |ctx| {
+        let key = key.clone();
+        FieldFuture::new(async move { resolve_from_parent(&ctx, &key) })
+    })
+}
+
+/// Resolve a field by looking it up on the parent `Value::Object`
+///
+/// Used by entity types and the connection/edge wrapper types, whose parents
+/// are converted rows (or objects built from them).
+pub(crate) fn resolve_from_parent<'a>(
+    ctx: &ResolverContext<'a>,
+    name: &str,
+) -> async_graphql::Result<Option<FieldValue<'a>>> {
+    let (parent, read) = parent_row(ctx)?;
+
+    if let Value::Object(obj) = parent {
+        match obj.get(name) {
+            // Null object-typed fields (e.g. a null struct) must resolve to `None`
+            Some(Value::Null) => return Ok(None),
+            // Nested objects (edges, nodes) are read the same way as their parent
+            Some(value) => {
+                let read = read.cloned().unwrap_or_default();
+                return Ok(Some(into_field_value(value.clone(), &read)));
+            }
+            None => {}
+        }
+    }
+
+    Ok(Some(FieldValue::NULL))
+}
+
+/// Wrap a converted value so nested object types can downcast it
+///
+/// Objects carry `read` along, so relations on them read as of the same point.
+pub(crate) fn into_field_value(value: Value, read: &Arc<ReadPoint>) -> FieldValue<'static> {
+    match value {
+        Value::Object(_) => FieldValue::owned_any(ReadRow { value, read: read.clone() }),
+        Value::List(items) => FieldValue::list(items.into_iter().map(|item| into_field_value(item, read))),
+        other => FieldValue::value(other),
+    }
+}
+
+/// Convert a single row from RecordBatch to GraphQL Value (Object)
+///
+/// This function handles type conversion from Arrow types to GraphQL types,
+/// including special handling for timestamps, dates, and ID fields.
+///
+/// # Arguments
+///
+/// * `batch` - The RecordBatch containing the data
+/// * `row_idx` - The index of the row to convert
+/// * `timezone` - Zone to render `DateTime` values in (defaults to each column's zone)
+///
+/// # Returns
+///
+/// A GraphQL Value::Object representing the row
+pub fn record_batch_to_graphql_value(
+    batch: &RecordBatch,
+    row_idx: usize,
+    timezone: Option<&Tz>,
+) -> Result<Value> {
+    let schema = batch.schema();
+    let mut object_map = IndexMap::new();
+
+    for (col_idx, field) in schema.fields().iter().enumerate() {
+        let value = array_value_to_graphql(field.name(), batch.column(col_idx), row_idx, timezone)?;
+        object_map.insert(Name::new(field.name()), value);
+    }
+
+    Ok(Value::Object(object_map))
+}
+
+/// Convert a single array element to a GraphQL value
+///
+/// `field_name` drives ID inference for integer columns (and the elements of
+/// integer lists). Struct elements become nested `Value::Object`s, lists become
+/// `Value::List`s and maps become lists of `{ key value }` objects, converted
+/// recursively.
+fn array_value_to_graphql(
+    field_name: &str,
+    column: &ArrayRef,
+    row_idx: usize,
+    timezone: Option<&Tz>,
+) -> Result<Value> {
+    if column.is_null(row_idx) {
+        return Ok(Value::Null);
+    }
+
+    // Convert based on data type
+    let value = match column.data_type() {
+        ArrowDataType::Int8 => {
+            let array = column.as_any().downcast_ref::<Int8Array>().unwrap();
+            Value::Number((array.value(row_idx) as i32).into())
+        }
+        ArrowDataType::Int16 => {
+            let array = column.as_any().downcast_ref::<Int16Array>().unwrap();
+            Value::Number((array.value(row_idx) as i32).into())
+        }
+        ArrowDataType::Int32 => {
+            let array = column.as_any().downcast_ref::<Int32Array>().unwrap();
+            Value::Number(array.value(row_idx).into())
+        }
+        ArrowDataType::Int64 => {
+            let array = column.as_any().downcast_ref::<Int64Array>().unwrap();
+            // For ID fields, convert to string
+            if field_name.ends_with("_id") || field_name == "id" {
+                Value::String(array.value(row_idx).to_string())
+            } else {
+                big_int_value(array.value(row_idx))
+            }
+        }
+        ArrowDataType::UInt8 => {
+            let array = column.as_any().downcast_ref::<UInt8Array>().unwrap();
+            Value::Number(serde_json::Number::from(array.value(row_idx)))
+        }
+        ArrowDataType::UInt16 => {
+            let array = column.as_any().downcast_ref::<UInt16Array>().unwrap();
+            Value::Number(serde_json::Number::from(array.value(row_idx)))
+        }
+        ArrowDataType::UInt32 => {
+            let array = column.as_any().downcast_ref::<UInt32Array>().unwrap();
+            Value::Number(serde_json::Number::from(array.value(row_idx)))
+        }
+        ArrowDataType::UInt64 => {
+            let array = column.as_any().downcast_ref::<UInt64Array>().unwrap();
+            // For ID fields, convert to string
+            if field_name.ends_with("_id") || field_name == "id" {
+                Value::String(array.value(row_idx).to_string())
+            } else {
+                big_int_value(array.value(row_idx))
+            }
+        }
+        ArrowDataType::Float32 => {
+            let array = column.as_any().downcast_ref::<Float32Array>().unwrap();
+            let f = array.value(row_idx);
+            Value::Number(
+                serde_json::Number::from_f64(f as f64)
+                    .ok_or_else(|| NouninatorError::SchemaGeneration("Invalid float value".to_string()))?,
+            )
+        }
+        ArrowDataType::Float64 => {
+            let array = column.as_any().downcast_ref::<Float64Array>().unwrap();
+            let f = array.value(row_idx);
+            Value::Number(
+                serde_json::Number::from_f64(f)
+                    .ok_or_else(|| NouninatorError::SchemaGeneration("Invalid float value".to_string()))?,
+            )
+        }
+        ArrowDataType::Utf8 => {
+            let array = column.as_any().downcast_ref::<StringArray>().unwrap();
+            Value::String(array.value(row_idx).to_string())
+        }
+        ArrowDataType::LargeUtf8 => {
+            let array = column.as_any().downcast_ref::<LargeStringArray>().unwrap();
+            Value::String(array.value(row_idx).to_string())
+        }
+        ArrowDataType::Boolean => {
+            let array = column.as_any().downcast_ref::<BooleanArray>().unwrap();
+            Value::Boolean(array.value(row_idx))
+        }
+        ArrowDataType::Timestamp(unit, column_timezone) => {
+            let datetime = match unit {
+                TimeUnit::Nanosecond => column
+                    .as_primitive::<TimestampNanosecondType>()
+                    .value_as_datetime(row_idx),
+                TimeUnit::Microsecond => column
+                    .as_primitive::<TimestampMicrosecondType>()
+                    .value_as_datetime(row_idx),
+                TimeUnit::Millisecond => column
+                    .as_primitive::<TimestampMillisecondType>()
+                    .value_as_datetime(row_idx),
+                TimeUnit::Second => column
+                    .as_primitive::<TimestampSecondType>()
+                    .value_as_datetime(row_idx),
+            }
+            .ok_or_else(|| {
+                NouninatorError::SchemaGeneration(format!("Invalid timestamp in '{}'", field_name))
+            })?;
+
+            match column_timezone {
+                // An instant: render it in the requested zone, or the column's
+                Some(column_timezone) => {
+                    let zone = match timezone {
+                        Some(zone) => *zone,
+                        None => parse_timezone(column_timezone)?,
+                    };
+                    Value::String(format_instant(datetime.and_utc(), &zone))
+                }
+                // Wall-clock time without a zone
+                None => Value::String(format_local_datetime(datetime)),
+            }
+        }
+        ArrowDataType::Time32(_) | ArrowDataType::Time64(_) => {
+            let time = match column.data_type() {
+                ArrowDataType::Time32(TimeUnit::Second) => {
+                    column.as_primitive::<Time32SecondType>().value_as_time(row_idx)
+                }
+                ArrowDataType::Time32(_) => {
+                    column.as_primitive::<Time32MillisecondType>().value_as_time(row_idx)
+                }
+                ArrowDataType::Time64(TimeUnit::Microsecond) => {
+                    column.as_primitive::<Time64MicrosecondType>().value_as_time(row_idx)
+                }
+                _ => column.as_primitive::<Time64NanosecondType>().value_as_time(row_idx),
+            }
+            .ok_or_else(|| {
+                NouninatorError::SchemaGeneration(format!("Invalid time in '{}'", field_name))
+            })?;
+            Value::String(format_time(time))
+        }
+        ArrowDataType::Duration(unit) => {
+            let duration = match unit {
+                TimeUnit::Nanosecond => column
+                    .as_primitive::<DurationNanosecondType>()
+                    .value_as_duration(row_idx),
+                TimeUnit::Microsecond => column
+                    .as_primitive::<DurationMicrosecondType>()
+                    .value_as_duration(row_idx),
+                TimeUnit::Millisecond => column
+                    .as_primitive::<DurationMillisecondType>()
+                    .value_as_duration(row_idx),
+                TimeUnit::Second => column
+                    .as_primitive::<DurationSecondType>()
+                    .value_as_duration(row_idx),
+            }
+            .and_then(|duration| duration.num_nanoseconds())
+            .ok_or_else(|| {
+                NouninatorError::SchemaGeneration(format!("Duration in '{}' is out of range", field_name))
+            })?;
+            Value::String(format_duration(IsoDuration::from_nanos(duration)))
+        }
+        ArrowDataType::Interval(unit) => {
+            let duration = match unit {
+                IntervalUnit::YearMonth => IsoDuration {
+                    months: column.as_primitive::<IntervalYearMonthType>().value(row_idx) as i64,
+                    ..Default::default()
+                },
+                IntervalUnit::DayTime => {
+                    let value = column.as_primitive::<IntervalDayTimeType>().value(row_idx);
+                    IsoDuration {
+                        months: 0,
+                        days: value.days as i64,
+                        nanos: value.milliseconds as i64 * 1_000_000,
+                    }
+                }
+                IntervalUnit::MonthDayNano => {
+                    let value = column.as_primitive::<IntervalMonthDayNanoType>().value(row_idx);
+                    IsoDuration {
+                        months: value.months as i64,
+                        days: value.days as i64,
+                        nanos: value.nanoseconds,
+                    }
+                }
+            };
+            Value::String(format_duration(duration))
+        }
+        ArrowDataType::Date32 => {
+            let array = column.as_any().downcast_ref::<Date32Array>().unwrap();
+            let days = array.value(row_idx);
+
+            use chrono::NaiveDate;
+            let date = NaiveDate::from_ymd_opt(1970, 1, 1)
+                .ok_or_else(|| {
+                    NouninatorError::SchemaGeneration("Invalid base date".to_string())
+                })?
+                .checked_add_signed(chrono::Duration::days(days as i64))
+                .ok_or_else(|| {
+                    NouninatorError::SchemaGeneration(format!("Invalid date: {} days", days))
+                })?;
+
+            Value::String(date.format("%Y-%m-%d").to_string())
+        }
+        ArrowDataType::Date64 => {
+            let array = column.as_any().downcast_ref::<Date64Array>().unwrap();
+            let millis = array.value(row_idx);
+
+            use chrono::NaiveDate;
+            let date = NaiveDate::from_ymd_opt(1970, 1, 1)
+                .ok_or_else(|| {
+                    NouninatorError::SchemaGeneration("Invalid base date".to_string())
+                })?
+                .checked_add_signed(chrono::Duration::milliseconds(millis))
+                .ok_or_else(|| {
+                    NouninatorError::SchemaGeneration(format!("Invalid date: {} ms", millis))
+                })?;
+
+            Value::String(date.format("%Y-%m-%d").to_string())
+        }
+        ArrowDataType::Decimal128(_, _) => {
+            let array = column.as_any().downcast_ref::<Decimal128Array>().unwrap();
+            Value::String(array.value_as_string(row_idx))
+        }
+        ArrowDataType::Decimal256(_, _) => {
+            let array = column.as_any().downcast_ref::<Decimal256Array>().unwrap();
+            Value::String(array.value_as_string(row_idx))
+        }
+        ArrowDataType::Binary => {
+            let array = column.as_any().downcast_ref::<BinaryArray>().unwrap();
+            Value::String(STANDARD.encode(array.value(row_idx)))
+        }
+        ArrowDataType::LargeBinary => {
+            let array = column.as_any().downcast_ref::<LargeBinaryArray>().unwrap();
+            Value::String(STANDARD.encode(array.value(row_idx)))
+        }
+        ArrowDataType::FixedSizeBinary(_) => {
+            let array = column.as_any().downcast_ref::<FixedSizeBinaryArray>().unwrap();
+            Value::String(STANDARD.encode(array.value(row_idx)))
+        }
+        ArrowDataType::BinaryView => {
+            let array = column.as_any().downcast_ref::<BinaryViewArray>().unwrap();
+            Value::String(STANDARD.encode(array.value(row_idx)))
+        }
+        ArrowDataType::Struct(_) => {
+            let array = column.as_any().downcast_ref::<StructArray>().unwrap();
+            let mut object_map = IndexMap::new();
+            for (child, field) in array.columns().iter().zip(array.fields()) {
+                let value = array_value_to_graphql(field.name(), child, row_idx, timezone)?;
+                object_map.insert(Name::new(field.name()), value);
+            }
+            Value::Object(object_map)
+        }
+        ArrowDataType::List(_) => {
+            let array = column.as_any().downcast_ref::<ListArray>().unwrap();
+            list_to_graphql(field_name, &array.value(row_idx), timezone)?
+        }
+        ArrowDataType::LargeList(_) => {
+            let array = column.as_any().downcast_ref::<LargeListArray>().unwrap();
+            list_to_graphql(field_name, &array.value(row_idx), timezone)?
+        }
+        ArrowDataType::FixedSizeList(_, _) => {
+            let array = column.as_any().downcast_ref::<FixedSizeListArray>().unwrap();
+            list_to_graphql(field_name, &array.value(row_idx), timezone)?
+        }
+        ArrowDataType::Map(_, _) => {
+            let array = column.as_any().downcast_ref::<MapArray>().unwrap();
+            let entries = array.value(row_idx);
+            let mut items = Vec::with_capacity(entries.len());
+            for idx in 0..entries.len() {
+                let mut entry = IndexMap::new();
+                entry.insert(Name::new("key"), array_value_to_graphql("key", entries.column(0), idx, timezone)?);
+                entry.insert(Name::new("value"), array_value_to_graphql("value", entries.column(1), idx, timezone)?);
+                items.push(Value::Object(entry));
+            }
+            Value::List(items)
+        }
+        _ => {
+            tracing::warn!(
+                "Unsupported type {:?} for field '{}', returning null",
+                column.data_type(),
+                field_name
+            );
+            Value::Null
+        }
+    };
+
+    Ok(value)
+}
+
+/// Convert the elements of a list value
+///
+/// Null elements of nested types are dropped (see `nested`).
+fn list_to_graphql(field_name: &str, values: &ArrayRef, timezone: Option<&Tz>) -> Result<Value> {
+    let skip_nulls = is_nested_type(values.data_type());
+    let mut items = Vec::with_capacity(values.len());
+    for idx in 0..values.len() {
+        if skip_nulls && values.is_null(idx) {
+            continue;
+        }
+        items.push(array_value_to_graphql(field_name, values, idx, timezone)?);
+    }
+    Ok(Value::List(items))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::datatypes::{DataType, Field as ArrowField, Schema as ArrowSchema};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_record_batch_to_graphql_value_basic_types() {
+        // Create a simple schema with basic types
+        let schema = Arc::new(ArrowSchema::new(vec![
+            ArrowField::new("id", DataType::Int64, false),
+            ArrowField::new("name", DataType::Utf8, false),
+            ArrowField::new("age", DataType::Int32, true),
+            ArrowField::new("active", DataType::Boolean, false),
+        ]));
+
+        // Create arrays
+        let id_array = Int64Array::from(vec![1]);
+        let name_array = StringArray::from(vec!["Alice"]);
+        let age_array = Int32Array::from(vec![Some(30)]);
+        let active_array = BooleanArray::from(vec![true]);
+
+        // Create record batch
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(id_array),
+                Arc::new(name_array),
+                Arc::new(age_array),
+                Arc::new(active_array),
+            ],
+        )
+        .unwrap();
+
+        // Convert to GraphQL value
+        let result = record_batch_to_graphql_value(&batch, 0, None).unwrap();
+
+        // Verify the result
+        if let Value::Object(obj) = result {
+            assert_eq!(obj.get("id").unwrap(), &Value::String("1".to_string())); // ID fields are strings
+            assert_eq!(obj.get("name").unwrap(), &Value::String("Alice".to_string()));
+            assert_eq!(obj.get("age").unwrap(), &Value::Number(30.into()));
+            assert_eq!(obj.get("active").unwrap(), &Value::Boolean(true));
+        } else {
+            panic!("Expected Value::Object");
+        }
+    }
+
+    #[test]
+    fn test_record_batch_to_graphql_value_nullable() {
+        // Create schema with nullable field
+        let schema = Arc::new(ArrowSchema::new(vec![
+            ArrowField::new("id", DataType::Int64, false),
+            ArrowField::new("nickname", DataType::Utf8, true),
+        ]));
+
+        // Create arrays with null value
+        let id_array = Int64Array::from(vec![1]);
+        let nickname_array = StringArray::from(vec![None as Option<&str>]);
+
+        // Create record batch
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![Arc::new(id_array), Arc::new(nickname_array)],
+        )
+        .unwrap();
+
+        // Convert to GraphQL value
+        let result = record_batch_to_graphql_value(&batch, 0, None).unwrap();
+
+        // Verify the result
+        if let Value::Object(obj) = result {
+            assert_eq!(obj.get("id").unwrap(), &Value::String("1".to_string()));
+            assert_eq!(obj.get("nickname").unwrap(), &Value::Null);
+        } else {
+            panic!("Expected Value::Object");
+        }
+    }
+
+    #[test]
+    fn test_record_batch_to_graphql_value_numeric_types() {
+        // Create schema with various numeric types
+        let schema = Arc::new(ArrowSchema::new(vec![
+            ArrowField::new("int8", DataType::Int8, false),
+            ArrowField::new("int16", DataType::Int16, false),
+            ArrowField::new("int32", DataType::Int32, false),
+            ArrowField::new("int64", DataType::Int64, false),
+            ArrowField::new("uint8", DataType::UInt8, false),
+            ArrowField::new("uint64", DataType::UInt64, false),
+            ArrowField::new("float32", DataType::Float32, false),
+            ArrowField::new("float64", DataType::Float64, false),
+        ]));
+
+        // Create arrays
+        let int8_array = Int8Array::from(vec![10i8]);
+        let int16_array = Int16Array::from(vec![100i16]);
+        let int32_array = Int32Array::from(vec![1000i32]);
+        let int64_array = Int64Array::from(vec![10000i64]);
+        let uint8_array = UInt8Array::from(vec![255u8]);
+        let uint64_array = UInt64Array::from(vec![u64::MAX]);
+        let float32_array = Float32Array::from(vec![1.5f32]);
+        let float64_array = Float64Array::from(vec![2.25f64]);
+
+        // Create record batch
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(int8_array),
+                Arc::new(int16_array),
+                Arc::new(int32_array),
+                Arc::new(int64_array),
+                Arc::new(uint8_array),
+                Arc::new(uint64_array),
+                Arc::new(float32_array),
+                Arc::new(float64_array),
+            ],
+        )
+        .unwrap();
+
+        // Convert to GraphQL value
+        let result = record_batch_to_graphql_value(&batch, 0, None).unwrap();
+
+        // Verify the result
+        if let Value::Object(obj) = result {
+            assert_eq!(obj.get("int8").unwrap(), &Value::Number(10.into()));
+            assert_eq!(obj.get("int16").unwrap(), &Value::Number(100.into()));
+            assert_eq!(obj.get("int32").unwrap(), &Value::Number(1000.into()));
+            assert_eq!(obj.get("int64").unwrap(), &Value::Number(10000.into()));
+            assert_eq!(obj.get("uint8").unwrap(), &Value::Number(255.into()));
+            // BigInt values beyond 2^53 are strings, so JavaScript clients keep every digit
+            assert_eq!(obj.get("uint64").unwrap(), &Value::String(u64::MAX.to_string()));
+            // Float comparisons
+            match obj.get("float32").unwrap() {
+                Value::Number(n) => {
+                    let f = n.as_f64().unwrap();
+                    assert!((f - 1.5).abs() < 0.01);
+                }
+                _ => panic!("Expected number"),
+            }
+            match obj.get("float64").unwrap() {
+                Value::Number(n) => {
+                    let f = n.as_f64().unwrap();
+                    assert!((f - 2.25).abs() < 0.001);
+                }
+                _ => panic!("Expected number"),
+            }
+        } else {
+            panic!("Expected Value::Object");
+        }
+    }
+
+    #[test]
+    fn test_record_batch_to_graphql_value_id_fields() {
+        // Create schema with ID-like fields
+        let schema = Arc::new(ArrowSchema::new(vec![
+            ArrowField::new("id", DataType::Int64, false),
+            ArrowField::new("user_id", DataType::Int64, false),
+            ArrowField::new("count", DataType::Int64, false), // Not an ID field
+        ]));
+
+        // Create arrays
+        let id_array = Int64Array::from(vec![123]);
+        let user_id_array = Int64Array::from(vec![456]);
+        let count_array = Int64Array::from(vec![789]);
+
+        // Create record batch
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(id_array),
+                Arc::new(user_id_array),
+                Arc::new(count_array),
+            ],
+        )
+        .unwrap();
+
+        // Convert to GraphQL value
+        let result = record_batch_to_graphql_value(&batch, 0, None).unwrap();
+
+        // Verify the result - ID fields should be strings, non-ID int64 should be numbers
+        if let Value::Object(obj) = result {
+            assert_eq!(obj.get("id").unwrap(), &Value::String("123".to_string()));
+            assert_eq!(obj.get("user_id").unwrap(), &Value::String("456".to_string()));
+            assert_eq!(obj.get("count").unwrap(), &Value::Number(789.into()));
+        } else {
+            panic!("Expected Value::Object");
+        }
+    }
+
+    #[test]
+    fn test_record_batch_to_graphql_value_struct() {
+        use datafusion::arrow::buffer::NullBuffer;
+        use datafusion::arrow::datatypes::Fields;
+
+        // address: { city: Utf8 (nullable), zip_id: Int64 }, null on the second row
+        let fields = Fields::from(vec![
+            ArrowField::new("city", DataType::Utf8, true),
+            ArrowField::new("zip_id", DataType::Int64, false),
+        ]);
+        let address = StructArray::new(
+            fields.clone(),
+            vec![
+                Arc::new(StringArray::from(vec![None, Some("Oslo")])),
+                Arc::new(Int64Array::from(vec![1, 2])),
+            ],
+            Some(NullBuffer::from(vec![true, false])),
+        );
+        let schema = Arc::new(ArrowSchema::new(vec![ArrowField::new(
+            "address",
+            DataType::Struct(fields),
+            true,
+        )]));
+        let batch = RecordBatch::try_new(schema, vec![Arc::new(address)]).unwrap();
+
+        let first = record_batch_to_graphql_value(&batch, 0, None).unwrap().into_json().unwrap();
+        assert_eq!(
+            first,
+            serde_json::json!({ "address": { "city": null, "zip_id": "1" } })
+        );
+
+        let second = record_batch_to_graphql_value(&batch, 1, None).unwrap().into_json().unwrap();
+        assert_eq!(second, serde_json::json!({ "address": null }));
+    }
+
+    #[test]
+    fn test_record_batch_to_graphql_value_temporal_types() {
+        let schema = Arc::new(ArrowSchema::new(vec![
+            ArrowField::new("opens", DataType::Time64(TimeUnit::Microsecond), true),
+            ArrowField::new("timeout", DataType::Duration(TimeUnit::Millisecond), true),
+            ArrowField::new("period", DataType::Interval(IntervalUnit::MonthDayNano), true),
+            ArrowField::new("seen_at", DataType::Timestamp(TimeUnit::Second, Some("UTC".into())), true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Time64MicrosecondArray::from(vec![34_200_250_000])),
+                Arc::new(DurationMillisecondArray::from(vec![5_400_500])),
+                Arc::new(IntervalMonthDayNanoArray::from(vec![IntervalMonthDayNano::new(
+                    13,
+                    2,
+                    3_600_000_000_000,
+                )])),
+                Arc::new(TimestampSecondArray::from(vec![1_705_312_800]).with_timezone("UTC")),
+            ],
+        )
+        .unwrap();
+
+        let expected = serde_json::json!({
+            "opens": "09:30:00.250",
+            "timeout": "PT1H30M0.5S",
+            "period": "P1Y1M2DT1H",
+            "seen_at": "2024-01-15T10:00:00+00:00",
+        });
+        let value = record_batch_to_graphql_value(&batch, 0, None).unwrap();
+        assert_eq!(value.into_json().unwrap(), expected);
+
+        let tokyo = parse_timezone("Asia/Tokyo").unwrap();
+        let value = record_batch_to_graphql_value(&batch, 0, Some(&tokyo)).unwrap();
+        assert_eq!(value.into_json().unwrap()["seen_at"], "2024-01-15T19:00:00+09:00");
+    }
+}
@@ -0,0 +1,131 @@
+//! Counting the rows sent to API key callers
+//!
+//! [`RowCounter`] looks at every field whose type is an entity (or its
+//! `XChange` type) once the field has resolved, and charges the rows it
+//! returned to the request's [`ApiKey`], if it has one: one per object, or
+//! one per item of a list. Rows reached through relations, connections and
+//! lookups count like rows of root fields; aggregates do not count.
+//!
+//! Rows are only sent while the key's row quota lasts: list limits and page
+//! sizes are capped to [`row_budget`], lists are cut to the rows left when
+//! they resolve, and entity fields fail once nothing is left.
+
+use crate::auth::{ApiKey, QuotaExceeded};
+
+use async_graphql::extensions::{Extension, ExtensionContext, ExtensionFactory, NextResolve, ResolveInfo};
+use async_graphql::registry::MetaTypeName;
+use async_graphql::{Context, ServerError, ServerResult, Value};
+use async_trait::async_trait;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Charges entity rows to the API key a request was made with
+pub struct RowCounter {
+    row_types: Arc<HashSet<String>>,
+}
+
+impl RowCounter {
+    /// Count fields of the given object types
+    pub fn new(row_types: HashSet<String>) -> Self {
+        Self {
+            row_types: Arc::new(row_types),
+        }
+    }
+}
+
+impl ExtensionFactory for RowCounter {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(RowCounterExtension {
+            row_types: self.row_types.clone(),
+        })
+    }
+}
+
+struct RowCounterExtension {
+    row_types: Arc<HashSet<String>>,
+}
+
+#[async_trait]
+impl Extension for RowCounterExtension {
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<Value>> {
+        // Items of a list are resolved like fields too; the list is charged for them
+        let counts = self.row_types.contains(MetaTypeName::concrete_typename(info.return_type))
+            && !info.parent_type.starts_with('[');
+        let Some(key) = ctx.data_opt::<ApiKey>().filter(|_| counts) else {
+            return next.run(ctx, info).await;
+        };
+        // Do not resolve rows that could not be sent
+        let quota_error = |exceeded: QuotaExceeded| ServerError::new(exceeded.to_string(), None);
+        key.admit_rows().map_err(quota_error)?;
+
+        let mut value = next.run(ctx, info).await?;
+        if let Some(value) = &mut value {
+            let rows = row_count(value);
+            let mut charged = key.charge_rows(rows).map_err(quota_error)?;
+            if charged < rows {
+                truncate_rows(value, &mut charged);
+            }
+        }
+        Ok(value)
+    }
+}
+
+/// Rows the request's API key may still be sent, to cap list limits and page sizes with
+pub fn row_budget(ctx: &Context<'_>) -> usize {
+    ctx.data_opt::<ApiKey>()
+        .and_then(ApiKey::remaining_rows)
+        .map_or(usize::MAX, |rows| usize::try_from(rows).unwrap_or(usize::MAX))
+}
+
+/// Rows in a resolved entity field: its objects, through nested lists
+fn row_count(value: &Value) -> u64 {
+    match value {
+        Value::Object(_) => 1,
+        Value::List(items) => items.iter().map(row_count).sum(),
+        _ => 0,
+    }
+}
+
+/// Drop the objects of a resolved list beyond the first `budget`
+fn truncate_rows(value: &mut Value, budget: &mut u64) {
+    if let Value::List(items) = value {
+        items.retain_mut(|item| match item {
+            Value::Object(_) if *budget == 0 => false,
+            Value::Object(_) => {
+                *budget -= 1;
+                true
+            }
+            other => {
+                truncate_rows(other, budget);
+                true
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_row_count() {
+        let value = Value::from_json(serde_json::json!([{"id": "1"}, null, {"id": "2"}])).unwrap();
+        assert_eq!(row_count(&value), 2);
+        assert_eq!(row_count(&Value::Null), 0);
+        assert_eq!(row_count(&Value::from_json(serde_json::json!({"id": "1"})).unwrap()), 1);
+    }
+
+    #[test]
+    fn test_truncate_rows() {
+        let mut value = Value::from_json(serde_json::json!([[{"id": "1"}, null], [{"id": "2"}, {"id": "3"}]])).unwrap();
+        let mut budget = 2;
+        truncate_rows(&mut value, &mut budget);
+        assert_eq!(budget, 0);
+        assert_eq!(value.into_json().unwrap(), serde_json::json!([[{"id": "1"}, null], [{"id": "2"}]]));
+    }
+}
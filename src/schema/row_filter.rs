@@ -0,0 +1,239 @@
+//! Row filters bound to the caller's token claims
+//!
+//! An entity's `row_filter` is a SQL predicate over its columns, such as
+//! `tenant_id = {claims.tenant} AND NOT deleted`. It is parsed once, when the
+//! schema is built, with every `{claims.<path>}` placeholder turned into a
+//! query parameter. Each request binds the parameters to the values of its
+//! token's claims (as typed literals, never as SQL text) and the predicate is
+//! applied to the table before any client argument, so no `filter`, key or
+//! relation can reach rows outside it. A request whose token lacks a claim
+//! the filter needs is rejected.
+
+use crate::auth::Claims;
+use crate::config::EntityConfig;
+use crate::error::{NouninatorError, Result};
+
+use async_graphql::Context;
+use datafusion::arrow::datatypes::Schema as ArrowSchema;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::tree_node::{Transformed, TreeNode};
+use datafusion::common::{DFSchema, ScalarValue};
+use datafusion::logical_expr::expr::Placeholder;
+use datafusion::prelude::{Expr, SessionContext};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+
+/// An entity's parsed row filter
+#[derive(Debug, Clone)]
+pub struct RowFilter {
+    /// The predicate, with a `$n` placeholder per claim
+    predicate: Expr,
+    /// Claim paths bound to `$1`, `$2`, ...
+    claims: Vec<String>,
+}
+
+impl RowFilter {
+    /// Parse a row filter against the entity's table schema
+    pub fn parse(ctx: &SessionContext, filter: &str, schema: &ArrowSchema) -> Result<Self> {
+        let (sql, claims) = claim_parameters(filter)?;
+        let df_schema = DFSchema::try_from(schema.clone())?;
+        let predicate = ctx
+            .parse_sql_expr(&sql, &df_schema)
+            .and_then(|predicate| predicate.infer_placeholder_types(&df_schema))
+            .map_err(|e| NouninatorError::Config(format!("Invalid row filter '{}': {}", filter, e)))?;
+        Ok(Self { predicate, claims })
+    }
+
+    /// The predicate for a caller, with each placeholder bound to its claim
+    ///
+    /// Claim values are cast to the type of the column they are compared
+    /// with where DataFusion can tell.
+    pub fn bind(&self, claims: Option<&Claims>) -> Result<Expr> {
+        let values = self
+            .claims
+            .iter()
+            .map(|path| {
+                let value = claims.and_then(|claims| claims.path(path)).ok_or_else(|| {
+                    NouninatorError::Unauthorized(format!("Token has no '{}' claim", path))
+                })?;
+                claim_scalar(path, value)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        self.predicate
+            .clone()
+            .transform(|expr| {
+                let Expr::Placeholder(Placeholder { id, data_type }) = &expr else {
+                    return Ok(Transformed::no(expr));
+                };
+                let index = id[1..].parse::<usize>().ok().and_then(|n| n.checked_sub(1));
+                let Some(value) = index.and_then(|index| values.get(index)) else {
+                    return Ok(Transformed::no(expr));
+                };
+                let value = match data_type {
+                    Some(data_type) => value.cast_to(data_type)?,
+                    None => value.clone(),
+                };
+                Ok(Transformed::yes(Expr::Literal(value)))
+            })
+            .map(|transformed| transformed.data)
+            .map_err(|e| NouninatorError::Unauthorized(format!("Token claims do not fit the row filter: {}", e)))
+    }
+}
+
+/// Replace `{claims.<path>}` placeholders by `$n` parameters
+///
+/// Returns the SQL and the claim path of each parameter. Repeated paths share
+/// a parameter.
+fn claim_parameters(filter: &str) -> Result<(String, Vec<String>)> {
+    let mut sql = String::with_capacity(filter.len());
+    let mut claims: Vec<String> = Vec::new();
+    let mut rest = filter;
+    while let Some(start) = rest.find("{claims.") {
+        sql.push_str(&rest[..start]);
+        let placeholder = &rest[start + "{claims.".len()..];
+        let end = placeholder.find('}').ok_or_else(|| {
+            NouninatorError::Config(format!("Unterminated claim placeholder in row filter '{}'", filter))
+        })?;
+        let path = &placeholder[..end];
+        if path.is_empty() || !path.chars().all(|c| c.is_alphanumeric() || "_-.:".contains(c)) {
+            return Err(NouninatorError::Config(format!(
+                "Invalid claim '{}' in row filter '{}'",
+                path, filter
+            )));
+        }
+        let index = match claims.iter().position(|claim| claim == path) {
+            Some(index) => index,
+            None => {
+                claims.push(path.to_string());
+                claims.len() - 1
+            }
+        };
+        sql.push_str(&format!("${}", index + 1));
+        rest = &placeholder[end + 1..];
+    }
+    sql.push_str(rest);
+    Ok((sql, claims))
+}
+
+/// The literal for a claim value (a string, number or boolean)
+fn claim_scalar(path: &str, value: &JsonValue) -> Result<ScalarValue> {
+    match value {
+        JsonValue::String(s) => Ok(ScalarValue::Utf8(Some(s.clone()))),
+        JsonValue::Bool(b) => Ok(ScalarValue::Boolean(Some(*b))),
+        JsonValue::Number(n) => Ok(match (n.as_i64(), n.as_u64(), n.as_f64()) {
+            (Some(i), _, _) => ScalarValue::Int64(Some(i)),
+            (None, Some(u), _) => ScalarValue::UInt64(Some(u)),
+            (_, _, f) => ScalarValue::Float64(f),
+        }),
+        _ => Err(NouninatorError::Unauthorized(format!(
+            "Claim '{}' must be a string, number or boolean to be used in a row filter",
+            path
+        ))),
+    }
+}
+
+/// Parsed row filters by entity name, kept in the schema's data
+#[derive(Debug, Clone, Default)]
+pub struct RowFilters(HashMap<String, RowFilter>);
+
+impl RowFilters {
+    pub fn insert(&mut self, graphql_name: &str, filter: RowFilter) {
+        self.0.insert(graphql_name.to_string(), filter);
+    }
+}
+
+/// The row filter predicate of an entity for the current request, if it has one
+pub fn row_predicate(ctx: &Context<'_>, entity: &EntityConfig) -> async_graphql::Result<Option<Expr>> {
+    if entity.row_filter.is_none() {
+        return Ok(None);
+    }
+    let filter = ctx
+        .data_opt::<RowFilters>()
+        .and_then(|filters| filters.0.get(&entity.graphql_name))
+        .ok_or_else(|| format!("Row filter of {} was not compiled", entity.graphql_name))?;
+    let predicate = filter
+        .bind(ctx.data_opt::<Claims>())
+        .map_err(|e| format!("Not authorized to query {}: {}", entity.graphql_name, e))?;
+    Ok(Some(predicate))
+}
+
+/// Whether every row of a batch matches a predicate
+///
+/// Used to keep writes inside an entity's row filter.
+pub async fn all_rows_match(batch: RecordBatch, predicate: Expr) -> Result<bool> {
+    let rows = batch.num_rows();
+    let matching = SessionContext::new().read_batch(batch)?.filter(predicate)?.count().await?;
+    Ok(matching == rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::array::{Int64Array, StringArray};
+    use datafusion::arrow::datatypes::{DataType, Field};
+    use std::sync::Arc;
+
+    fn test_schema() -> ArrowSchema {
+        ArrowSchema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("tenant_id", DataType::Utf8, false),
+            Field::new("level", DataType::Int64, true),
+        ])
+    }
+
+    fn claims(value: serde_json::Value) -> Claims {
+        Claims::new(value.as_object().unwrap().clone())
+    }
+
+    #[test]
+    fn test_claim_parameters() {
+        let (sql, claims) =
+            claim_parameters("tenant_id = {claims.org.tenant} AND (level <= {claims.level} OR tenant_id = {claims.org.tenant})")
+                .unwrap();
+        assert_eq!(sql, "tenant_id = $1 AND (level <= $2 OR tenant_id = $1)");
+        assert_eq!(claims, vec!["org.tenant", "level"]);
+
+        assert!(claim_parameters("tenant_id = {claims.tenant").is_err());
+        assert!(claim_parameters("tenant_id = {claims.a' OR 1=1}").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_bound_filter_selects_the_callers_rows() {
+        let ctx = SessionContext::new();
+        let filter = RowFilter::parse(&ctx, "tenant_id = {claims.tenant} AND level <= {claims.level}", &test_schema())
+            .unwrap();
+
+        let batch = RecordBatch::try_new(
+            Arc::new(test_schema()),
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2, 3])),
+                Arc::new(StringArray::from(vec!["acme", "acme", "globex"])),
+                Arc::new(Int64Array::from(vec![1, 5, 1])),
+            ],
+        )
+        .unwrap();
+
+        // Claim values are bound as literals, so quotes cannot escape them; "3" is cast to the column type
+        let predicate = filter.bind(Some(&claims(serde_json::json!({"tenant": "acme", "level": "3"})))).unwrap();
+        let df = ctx.read_batch(batch.clone()).unwrap().filter(predicate).unwrap();
+        assert_eq!(df.count().await.unwrap(), 1);
+
+        let predicate = filter
+            .bind(Some(&claims(serde_json::json!({"tenant": "x' OR '1'='1", "level": 9}))))
+            .unwrap();
+        let df = ctx.read_batch(batch.clone()).unwrap().filter(predicate.clone()).unwrap();
+        assert_eq!(df.count().await.unwrap(), 0);
+        assert!(!all_rows_match(batch, predicate).await.unwrap());
+
+        let err = filter.bind(Some(&claims(serde_json::json!({"tenant": "acme"})))).unwrap_err();
+        assert!(err.to_string().contains("no 'level' claim"), "{}", err);
+        assert!(filter.bind(None).is_err());
+        let err = filter
+            .bind(Some(&claims(serde_json::json!({"tenant": ["acme"], "level": 1}))))
+            .unwrap_err();
+        assert!(err.to_string().contains("string, number or boolean"), "{}", err);
+
+        assert!(RowFilter::parse(&ctx, "missing_column = {claims.tenant}", &test_schema()).is_err());
+    }
+}
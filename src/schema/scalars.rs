@@ -1,89 +1,261 @@
-/// Custom GraphQL scalar types for Date and DateTime
-///
-/// These scalars handle ISO 8601 formatted date and datetime strings.
-
-use async_graphql::dynamic::Scalar;
-use async_graphql::Value;
-use chrono::{DateTime as ChronoDateTime, NaiveDate};
-
-/// ISO 8601 date scalar (YYYY-MM-DD)
-#[derive(Debug, Clone)]
-pub struct Date;
-
-/// ISO 8601 datetime scalar with timezone
-#[derive(Debug, Clone)]
-pub struct DateTime;
-
-/// Register custom scalars in the schema builder
-pub fn register_custom_scalars() -> Vec<Scalar> {
-    vec![date_scalar(), datetime_scalar()]
-}
-
-/// Create the Date scalar
-fn date_scalar() -> Scalar {
-    Scalar::new("Date")
-        .description("ISO 8601 date format (YYYY-MM-DD)")
-        .validator(|value| {
-            if let Value::String(s) = value {
-                NaiveDate::parse_from_str(s.as_str(), "%Y-%m-%d").is_ok()
-            } else {
-                false
-            }
-        })
-}
-
-/// Create the DateTime scalar
-fn datetime_scalar() -> Scalar {
-    Scalar::new("DateTime")
-        .description("ISO 8601 datetime format with timezone")
-        .validator(|value| {
-            if let Value::String(s) = value {
-                ChronoDateTime::parse_from_rfc3339(s.as_str()).is_ok()
-            } else {
-                false
-            }
-        })
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_date_scalar_registration() {
-        let scalars = register_custom_scalars();
-        assert_eq!(scalars.len(), 2);
-    }
-
-    #[test]
-    fn test_datetime_scalar_registration() {
-        let scalars = register_custom_scalars();
-        assert_eq!(scalars.len(), 2);
-    }
-
-    #[test]
-    fn test_date_validation_valid() {
-        let _value = Value::String("2024-01-15".to_string());
-        let result = NaiveDate::parse_from_str("2024-01-15", "%Y-%m-%d");
-        assert!(result.is_ok());
-    }
-
-    #[test]
-    fn test_date_validation_invalid() {
-        let result = NaiveDate::parse_from_str("invalid-date", "%Y-%m-%d");
-        assert!(result.is_err());
-    }
-
-    #[test]
-    fn test_datetime_validation_valid() {
-        let result = ChronoDateTime::parse_from_rfc3339("2024-01-15T10:00:00Z");
-        assert!(result.is_ok());
-    }
-
-    #[test]
-    fn test_datetime_validation_invalid() {
-        let result = ChronoDateTime::parse_from_rfc3339("not-a-datetime");
-        assert!(result.is_err());
-    }
-}
-
+//! Custom GraphQL scalar types
+//!
+//! `Date` and `DateTime` handle ISO 8601 formatted date and datetime strings.
+//! `BigInt`, `Decimal` and `Bytes` carry values GraphQL's built-in scalars
+//! cannot represent exactly: 64-bit integers, fixed-point decimals (as strings,
+//! so no precision is lost to floats) and binary data (as base64).
+//!
+//! Besides `Date` and `DateTime` (an instant, with offset), temporal columns
+//! map to `LocalDateTime` (a wall-clock datetime without timezone), `Time` (a
+//! time of day) and `Duration` (an ISO 8601 duration); see `temporal`.
+
+use async_graphql::dynamic::Scalar;
+use async_graphql::Value;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use crate::schema::temporal::parse_duration;
+
+use chrono::{DateTime as ChronoDateTime, NaiveDate, NaiveDateTime, NaiveTime};
+
+/// 64-bit integer scalar name
+pub const BIG_INT: &str = "BigInt";
+
+/// String-encoded decimal scalar name
+pub const DECIMAL: &str = "Decimal";
+
+/// Base64-encoded binary scalar name
+pub const BYTES: &str = "Bytes";
+
+/// Datetime without timezone scalar name
+pub const LOCAL_DATE_TIME: &str = "LocalDateTime";
+
+/// Time of day scalar name
+pub const TIME: &str = "Time";
+
+/// ISO 8601 duration scalar name
+pub const DURATION: &str = "Duration";
+
+/// ISO 8601 date scalar (YYYY-MM-DD)
+#[derive(Debug, Clone)]
+pub struct Date;
+
+/// ISO 8601 datetime scalar with timezone
+#[derive(Debug, Clone)]
+pub struct DateTime;
+
+/// Register custom scalars in the schema builder
+pub fn register_custom_scalars() -> Vec<Scalar> {
+    vec![
+        date_scalar(),
+        datetime_scalar(),
+        big_int_scalar(),
+        decimal_scalar(),
+        bytes_scalar(),
+        local_datetime_scalar(),
+        time_scalar(),
+        duration_scalar(),
+    ]
+}
+
+/// Create the Date scalar
+fn date_scalar() -> Scalar {
+    Scalar::new("Date")
+        .description("ISO 8601 date format (YYYY-MM-DD)")
+        .validator(|value| {
+            if let Value::String(s) = value {
+                NaiveDate::parse_from_str(s.as_str(), "%Y-%m-%d").is_ok()
+            } else {
+                false
+            }
+        })
+}
+
+/// Create the DateTime scalar
+fn datetime_scalar() -> Scalar {
+    Scalar::new("DateTime")
+        .description("ISO 8601 datetime format with timezone")
+        .validator(|value| {
+            if let Value::String(s) = value {
+                ChronoDateTime::parse_from_rfc3339(s.as_str()).is_ok()
+            } else {
+                false
+            }
+        })
+}
+
+/// Largest magnitude JSON numbers keep exactly in JavaScript (2^53 - 1)
+pub const MAX_SAFE_INTEGER: u64 = 9_007_199_254_740_991;
+
+/// Create the BigInt scalar
+fn big_int_scalar() -> Scalar {
+    Scalar::new(BIG_INT)
+        .description(
+            "64-bit integer; sent as a number, or as a numeric string beyond ±(2^53 - 1) \
+             where JSON numbers lose precision. Accepts a number or a numeric string",
+        )
+        .validator(is_big_int)
+}
+
+/// A `BigInt` output value: a number while JavaScript keeps it exact, else a string
+pub fn big_int_value(value: impl Into<i128>) -> Value {
+    let value = value.into();
+    if value.unsigned_abs() <= MAX_SAFE_INTEGER as u128 {
+        // Within ±2^53 the value fits an i64
+        Value::Number((value as i64).into())
+    } else {
+        Value::String(value.to_string())
+    }
+}
+
+/// Read a `BigInt` input value (a number or a numeric string) as an i64
+pub fn big_int_to_i64(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Create the Decimal scalar
+fn decimal_scalar() -> Scalar {
+    Scalar::new(DECIMAL)
+        .description("Fixed-point decimal, serialized as a string (e.g. \"1234.50\")")
+        .validator(is_decimal)
+}
+
+/// Create the Bytes scalar
+fn bytes_scalar() -> Scalar {
+    Scalar::new(BYTES)
+        .description("Binary data, serialized as a base64 string")
+        .validator(|value| match value {
+            Value::String(s) => STANDARD.decode(s).is_ok(),
+            _ => false,
+        })
+}
+
+/// Create the LocalDateTime scalar
+fn local_datetime_scalar() -> Scalar {
+    Scalar::new(LOCAL_DATE_TIME)
+        .description("ISO 8601 datetime without timezone (YYYY-MM-DDTHH:MM:SS[.fff])")
+        .validator(|value| match value {
+            Value::String(s) => NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f").is_ok(),
+            _ => false,
+        })
+}
+
+/// Create the Time scalar
+fn time_scalar() -> Scalar {
+    Scalar::new(TIME)
+        .description("ISO 8601 time of day (HH:MM:SS[.fff])")
+        .validator(|value| match value {
+            Value::String(s) => NaiveTime::parse_from_str(s, "%H:%M:%S%.f").is_ok(),
+            _ => false,
+        })
+}
+
+/// Create the Duration scalar
+fn duration_scalar() -> Scalar {
+    Scalar::new(DURATION)
+        .description("ISO 8601 duration (e.g. \"PT1H30M\", \"P1M2D\")")
+        .validator(|value| match value {
+            Value::String(s) => parse_duration(s).is_some(),
+            _ => false,
+        })
+}
+
+fn is_big_int(value: &Value) -> bool {
+    match value {
+        Value::Number(n) => n.is_i64() || n.is_u64(),
+        Value::String(s) => s.parse::<i64>().is_ok() || s.parse::<u64>().is_ok(),
+        _ => false,
+    }
+}
+
+fn is_decimal(value: &Value) -> bool {
+    match value {
+        Value::Number(_) => true,
+        Value::String(s) => {
+            let digits = s.strip_prefix(['-', '+']).unwrap_or(s);
+            let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+            !whole.is_empty()
+                && whole.bytes().all(|b| b.is_ascii_digit())
+                && fraction.bytes().all(|b| b.is_ascii_digit())
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scalar_registration() {
+        let names: Vec<String> = register_custom_scalars()
+            .iter()
+            .map(|scalar| scalar.type_name().to_string())
+            .collect();
+        assert_eq!(
+            names,
+            ["Date", "DateTime", BIG_INT, DECIMAL, BYTES, LOCAL_DATE_TIME, TIME, DURATION]
+        );
+    }
+
+    #[test]
+    fn test_big_int_validation() {
+        assert!(is_big_int(&Value::Number(i64::MAX.into())));
+        assert!(is_big_int(&Value::String("18446744073709551615".to_string())));
+        assert!(!is_big_int(&Value::String("1.5".to_string())));
+    }
+
+    #[test]
+    fn test_big_int_value() {
+        assert_eq!(big_int_value(42u64), Value::Number(42.into()));
+        assert_eq!(big_int_value(-(MAX_SAFE_INTEGER as i64)), Value::Number((-(MAX_SAFE_INTEGER as i64)).into()));
+        assert_eq!(big_int_value(MAX_SAFE_INTEGER + 1), Value::String("9007199254740992".to_string()));
+        assert_eq!(big_int_value(i64::MIN), Value::String("-9223372036854775808".to_string()));
+        assert_eq!(big_int_value(u64::MAX), Value::String("18446744073709551615".to_string()));
+    }
+
+    #[test]
+    fn test_big_int_to_i64() {
+        assert_eq!(big_int_to_i64(&Value::Number(3.into())), Some(3));
+        assert_eq!(big_int_to_i64(&Value::String("-9007199254740993".to_string())), Some(-9_007_199_254_740_993));
+        assert_eq!(big_int_to_i64(&Value::String("18446744073709551615".to_string())), None);
+    }
+
+    #[test]
+    fn test_decimal_validation() {
+        assert!(is_decimal(&Value::String("-1234.50".to_string())));
+        assert!(is_decimal(&Value::String("7".to_string())));
+        assert!(!is_decimal(&Value::String(".5".to_string())));
+        assert!(!is_decimal(&Value::String("1e3".to_string())));
+        assert!(!is_decimal(&Value::String("NaN".to_string())));
+    }
+
+    #[test]
+    fn test_date_validation_valid() {
+        let _value = Value::String("2024-01-15".to_string());
+        let result = NaiveDate::parse_from_str("2024-01-15", "%Y-%m-%d");
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_date_validation_invalid() {
+        let result = NaiveDate::parse_from_str("invalid-date", "%Y-%m-%d");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_datetime_validation_valid() {
+        let result = ChronoDateTime::parse_from_rfc3339("2024-01-15T10:00:00Z");
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_datetime_validation_invalid() {
+        let result = ChronoDateTime::parse_from_rfc3339("not-a-datetime");
+        assert!(result.is_err());
+    }
+}
+
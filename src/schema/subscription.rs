@@ -0,0 +1,248 @@
+//! Subscriptions over the Delta transaction log
+//!
+//! Every Delta-backed entity gets `xAdded(filter)` and `xChanged(filter)`
+//! subscription fields. A subscription waits for the served table to move to
+//! a new version (see `RefreshableTable::refresh`), reads the rows changed by
+//! the new commits and pushes them to the client, one event per row. Nothing
+//! is polled per subscriber: all subscriptions to a table share the refresh
+//! that is already running for it.
+//!
+//! A subscription lives no longer than the token it was authorized with: it
+//! is refused once the token's `exp` has passed, and an open one ends with an
+//! error when it does. Subscriptions made with an API key count each event as
+//! a request against the key's quota, and end with an error once it is used up.
+
+use crate::auth::{ApiKey, Claims};
+use crate::config::EntityConfig;
+use crate::schema::access::authorize_query;
+use crate::schema::changes::{change_type, change_type_name, read_changes, ChangeRange, INSERT};
+use crate::schema::filter::filter_type_name;
+use crate::schema::registry::TableRegistry;
+use crate::schema::resolver::{into_field_value, requested_timezone, timezone_argument, ReadPoint};
+use crate::schema::row_filter::row_predicate;
+
+use async_graphql::dynamic::{FieldValue, InputValue, SubscriptionField, SubscriptionFieldFuture, TypeRef};
+use async_graphql::Value;
+use futures_util::stream::{self, BoxStream, StreamExt};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Name of a subscription field for an entity (e.g. `customerAdded`)
+pub fn subscription_field_name(graphql_name: &str, event: &str) -> String {
+    let mut chars = graphql_name.chars();
+    let first = chars.next().map(|c| c.to_lowercase().to_string()).unwrap_or_default();
+    format!("{}{}{}", first, chars.as_str(), event)
+}
+
+/// Create the `xAdded` subscription, pushing rows inserted into the table
+pub fn create_added_subscription(entity: &EntityConfig) -> SubscriptionField {
+    create_change_subscription(
+        entity,
+        "Added",
+        TypeRef::named_nn(&entity.graphql_name),
+        true,
+    )
+    .description(format!("New {} rows, as they are committed", entity.graphql_name))
+}
+
+/// Create the `xChanged` subscription, pushing every change to the table
+pub fn create_changed_subscription(entity: &EntityConfig) -> SubscriptionField {
+    create_change_subscription(
+        entity,
+        "Changed",
+        TypeRef::named_nn(change_type_name(&entity.graphql_name)),
+        false,
+    )
+    .description(format!(
+        "Inserted, updated and deleted {} rows, as they are committed",
+        entity.graphql_name
+    ))
+}
+
+fn create_change_subscription(
+    entity: &EntityConfig,
+    event: &str,
+    ty: TypeRef,
+    inserts_only: bool,
+) -> SubscriptionField {
+    let field_name = subscription_field_name(&entity.graphql_name, event);
+    let entity_arc = Arc::new(entity.clone());
+
+    SubscriptionField::new(field_name, ty, move |ctx| {
+        let entity = entity_arc.clone();
+        let table_name = entity.table.clone();
+
+        SubscriptionFieldFuture::new(async move {
+            authorize_query(&ctx, &entity)?;
+            let expires_at = ctx.data_opt::<Claims>().and_then(Claims::expires_at);
+            if expires_at.is_some_and(|expires_at| expires_at <= SystemTime::now()) {
+                return Err("Token has expired".into());
+            }
+
+            let registry = ctx
+                .data::<Arc<TableRegistry>>()
+                .map_err(|_e| "Failed to get table registry")?;
+            let table = registry.served_table(&table_name).ok_or_else(|| {
+                format!("Table '{}' is not a Delta table; subscriptions are unavailable", table_name)
+            })?;
+            let filter: Option<Value> = ctx
+                .args
+                .get("filter")
+                .map(|filter| filter.as_value().clone());
+            // Claims are bound once; they do not change for the life of the subscription
+            let row_filter = row_predicate(&ctx, &entity)?;
+            let timezone = requested_timezone(&ctx)?;
+            let read = ReadPoint::served(&ctx)?;
+            let primary_key = entity.primary_key.columns().to_vec();
+
+            let versions = table.subscribe();
+            let start = *versions.borrow();
+
+            // One item per new version: the (filtered) rows it changed
+            let changes = stream::unfold((versions, start), move |(mut versions, last)| {
+                let table = table.clone();
+                let filter = filter.clone();
+                let row_filter = row_filter.clone();
+                let primary_key = primary_key.clone();
+                async move {
+                    loop {
+                        // The sender lives as long as the table; end the stream if it is dropped
+                        versions.changed().await.ok()?;
+                        let version = *versions.borrow_and_update();
+                        if version <= last {
+                            continue;
+                        }
+
+                        let range = ChangeRange::versions(last + 1, version);
+                        let rows = read_changes(
+                            &table.snapshot(),
+                            &range,
+                            filter.as_ref(),
+                            row_filter.as_ref(),
+                            timezone.as_ref(),
+                            &primary_key,
+                            None,
+                        )
+                            .await
+                            .map_err(|e| async_graphql::Error::new(e.to_string()));
+                        return Some((rows, (versions, version)));
+                    }
+                }
+            });
+
+            let events = changes.flat_map(move |rows| {
+                let events: Vec<async_graphql::Result<FieldValue<'static>>> = match rows {
+                    Ok(rows) => rows
+                        .into_iter()
+                        .filter(|row| !inserts_only || change_type(row) == Some(INSERT))
+                        .map(|row| Ok(into_field_value(row, &read)))
+                        .collect(),
+                    Err(e) => vec![Err(e)],
+                };
+                stream::iter(events)
+            });
+
+            let events = charged_to(events.boxed(), ctx.data_opt::<ApiKey>().cloned());
+            Ok(until_expiry(events, expires_at))
+        })
+    })
+    .argument(InputValue::new(
+        "filter",
+        TypeRef::named(filter_type_name(&entity.graphql_name)),
+    ))
+    .argument(timezone_argument())
+}
+
+type Events<'a> = BoxStream<'a, async_graphql::Result<FieldValue<'a>>>;
+
+/// End `events` with a "Token has expired" error once `expires_at` passes
+fn until_expiry(events: Events<'_>, expires_at: Option<SystemTime>) -> Events<'_> {
+    let Some(expires_at) = expires_at else {
+        return events;
+    };
+    let remaining = expires_at.duration_since(SystemTime::now()).unwrap_or_default();
+
+    events
+        .take_until(tokio::time::sleep(remaining))
+        .chain(
+            stream::once(async move {
+                // Only report expiry if that is why the events ended
+                (SystemTime::now() >= expires_at).then(|| Err("Token has expired".into()))
+            })
+            .filter_map(|expired| async move { expired }),
+        )
+        .boxed()
+}
+
+/// Count each of `events` as a request of `api_key`, ending with an error once its quota is used up
+fn charged_to(events: Events<'_>, api_key: Option<ApiKey>) -> Events<'_> {
+    let Some(api_key) = api_key else {
+        return events;
+    };
+
+    events
+        .scan(false, move |exceeded, event| {
+            // The event after the quota error ends the stream
+            let event = if *exceeded {
+                None
+            } else if let Err(e) = api_key.admit() {
+                *exceeded = true;
+                Some(Err(e.to_string().into()))
+            } else {
+                Some(event)
+            };
+            async move { event }
+        })
+        .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_events_end_when_the_token_expires() {
+        let expires_at = SystemTime::now() + Duration::from_millis(50);
+        let events = until_expiry(stream::pending().boxed(), Some(expires_at));
+
+        let ended: Vec<_> = events.collect().await;
+        assert_eq!(ended.len(), 1);
+        let err = ended.into_iter().next().unwrap().err().unwrap();
+        assert_eq!(err.message, "Token has expired");
+
+        // Without an expiry the events end only when the table's do
+        let events = until_expiry(stream::empty().boxed(), None);
+        assert_eq!(events.count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_events_are_charged_to_the_api_key() {
+        use crate::config::ApiKeyConfig;
+
+        let keys = crate::auth::ApiKeys::new(vec![ApiKeyConfig {
+            name: "reporting".to_string(),
+            sha256: crate::auth::hash_api_key("secret"),
+            requests_per_minute: Some(3),
+            ..Default::default()
+        }])
+        .unwrap();
+        let api_key = keys.find("secret").unwrap().clone();
+        // The subscribing request itself
+        api_key.admit().unwrap();
+
+        let events = stream::iter((0..5).map(|n| Ok(FieldValue::value(n)))).boxed();
+        let events: Vec<_> = charged_to(events, Some(api_key.clone())).collect().await;
+        assert_eq!(events.len(), 3);
+        assert!(events[..2].iter().all(Result::is_ok));
+        let err = events[2].as_ref().err().unwrap();
+        assert!(err.message.contains("request rate"), "{}", err.message);
+        assert!(api_key.admit().is_err());
+    }
+
+    #[test]
+    fn test_subscription_field_name() {
+        assert_eq!(subscription_field_name("Customer", "Added"), "customerAdded");
+        assert_eq!(subscription_field_name("WordFrequency", "Changed"), "wordFrequencyChanged");
+    }
+}
@@ -0,0 +1,265 @@
+//! Rendering and parsing of time-of-day, local datetime and duration values
+//!
+//! Timestamps with a timezone (Delta `timestamp`) are instants and render as
+//! RFC 3339 `DateTime`s in the column's zone, or in the zone passed as the
+//! `timezone` argument of a query. Timestamps without one (Delta
+//! `timestamp_ntz`) are wall-clock values and render as
+//! `LocalDateTime`s with no offset; the `timezone` argument does not apply to
+//! them. Durations and intervals use ISO 8601 durations (`P1DT2H30M`).
+
+use crate::error::{NouninatorError, Result};
+
+use chrono::{DateTime, NaiveDateTime, NaiveTime, TimeZone};
+use datafusion::arrow::array::timezone::Tz;
+use std::str::FromStr;
+
+const NANOS_PER_SECOND: i64 = 1_000_000_000;
+const NANOS_PER_MINUTE: i64 = 60 * NANOS_PER_SECOND;
+const NANOS_PER_HOUR: i64 = 60 * NANOS_PER_MINUTE;
+const NANOS_PER_DAY: i64 = 24 * NANOS_PER_HOUR;
+
+/// Parse a timezone name: an IANA zone (`Europe/Paris`) or a UTC offset (`+02:00`)
+pub fn parse_timezone(name: &str) -> Result<Tz> {
+    Tz::from_str(name).map_err(|_| {
+        NouninatorError::SchemaGeneration(format!(
+            "Invalid timezone '{}': expected an IANA name (e.g. \"Europe/Paris\") or an offset (e.g. \"+02:00\")",
+            name
+        ))
+    })
+}
+
+/// Render an instant as RFC 3339 in the given zone
+pub fn format_instant<Z: TimeZone>(instant: DateTime<Z>, timezone: &Tz) -> String {
+    instant.with_timezone(timezone).to_rfc3339()
+}
+
+/// Render a wall-clock datetime without an offset (`2024-01-15T10:30:00`)
+pub fn format_local_datetime(datetime: NaiveDateTime) -> String {
+    datetime.format("%Y-%m-%dT%H:%M:%S%.f").to_string()
+}
+
+/// Render a time of day (`10:30:00`, `10:30:00.250`)
+pub fn format_time(time: NaiveTime) -> String {
+    time.format("%H:%M:%S%.f").to_string()
+}
+
+/// A calendar duration: months and days vary in length, so they are kept apart
+/// from the exact nanoseconds (the same split as Arrow's `MonthDayNano` intervals)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct IsoDuration {
+    pub months: i64,
+    pub days: i64,
+    pub nanos: i64,
+}
+
+impl IsoDuration {
+    /// An exact duration
+    pub fn from_nanos(nanos: i64) -> Self {
+        Self {
+            nanos,
+            ..Default::default()
+        }
+    }
+
+    /// Total nanoseconds, counting days as 24 hours
+    ///
+    /// Returns `None` if the duration has a month component or overflows.
+    pub fn total_nanos(&self) -> Option<i64> {
+        if self.months != 0 {
+            return None;
+        }
+        self.days.checked_mul(NANOS_PER_DAY)?.checked_add(self.nanos)
+    }
+}
+
+/// Render a duration in ISO 8601 (`P1Y2M3DT4H5M6.5S`)
+///
+/// Negative durations are prefixed with `-`; intervals mixing signs (which
+/// ISO 8601 cannot express) carry the sign on each component instead.
+pub fn format_duration(duration: IsoDuration) -> String {
+    let IsoDuration { months, days, nanos } = duration;
+    if months == 0 && days == 0 && nanos == 0 {
+        return "PT0S".to_string();
+    }
+
+    let negative = months <= 0 && days <= 0 && nanos <= 0;
+    let (months, days, nanos) = if negative {
+        (-(months as i128), -(days as i128), -(nanos as i128))
+    } else {
+        (months as i128, days as i128, nanos as i128)
+    };
+
+    let mut out = String::from(if negative { "-P" } else { "P" });
+    let years = months / 12;
+    let months = months % 12;
+    for (value, unit) in [(years, 'Y'), (months, 'M'), (days, 'D')] {
+        if value != 0 {
+            out.push_str(&format!("{}{}", value, unit));
+        }
+    }
+
+    if nanos != 0 {
+        out.push('T');
+        let hours = nanos / NANOS_PER_HOUR as i128;
+        let minutes = nanos % NANOS_PER_HOUR as i128 / NANOS_PER_MINUTE as i128;
+        let rest = nanos % NANOS_PER_MINUTE as i128;
+        for (value, unit) in [(hours, 'H'), (minutes, 'M')] {
+            if value != 0 {
+                out.push_str(&format!("{}{}", value, unit));
+            }
+        }
+        if rest != 0 {
+            let seconds = rest / NANOS_PER_SECOND as i128;
+            let fraction = (rest % NANOS_PER_SECOND as i128).abs();
+            if rest < 0 && seconds == 0 {
+                out.push('-');
+            }
+            out.push_str(&seconds.to_string());
+            if fraction != 0 {
+                let digits = format!("{:09}", fraction);
+                out.push('.');
+                out.push_str(digits.trim_end_matches('0'));
+            }
+            out.push('S');
+        }
+    }
+
+    out
+}
+
+/// Parse an ISO 8601 duration (`P1Y2M3W4DT5H6M7.5S`, optionally signed)
+///
+/// Weeks count as 7 days and years as 12 months. Returns `None` for
+/// malformed input.
+pub fn parse_duration(value: &str) -> Option<IsoDuration> {
+    let (sign, rest) = match value.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let rest = rest.strip_prefix('P')?;
+    let (date_part, time_part) = match rest.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (rest, None),
+    };
+    if date_part.is_empty() && time_part.is_none_or(str::is_empty) {
+        return None;
+    }
+
+    let mut duration = IsoDuration::default();
+    for (number, unit) in components(date_part)? {
+        let number: i64 = number.parse().ok()?;
+        match unit {
+            'Y' => duration.months = duration.months.checked_add(number.checked_mul(12)?)?,
+            'M' => duration.months = duration.months.checked_add(number)?,
+            'W' => duration.days = duration.days.checked_add(number.checked_mul(7)?)?,
+            'D' => duration.days = duration.days.checked_add(number)?,
+            _ => return None,
+        }
+    }
+
+    for (number, unit) in components(time_part.unwrap_or(""))? {
+        let nanos = match unit {
+            'H' => number.parse::<i64>().ok()?.checked_mul(NANOS_PER_HOUR)?,
+            'M' => number.parse::<i64>().ok()?.checked_mul(NANOS_PER_MINUTE)?,
+            'S' => parse_seconds(number)?,
+            _ => return None,
+        };
+        duration.nanos = duration.nanos.checked_add(nanos)?;
+    }
+
+    Some(IsoDuration {
+        months: duration.months * sign,
+        days: duration.days * sign,
+        nanos: duration.nanos * sign,
+    })
+}
+
+/// Split `1Y2M` into `[("1", 'Y'), ("2", 'M')]`
+fn components(part: &str) -> Option<Vec<(&str, char)>> {
+    let mut result = Vec::new();
+    let mut start = 0;
+    for (idx, c) in part.char_indices() {
+        if c.is_ascii_alphabetic() {
+            let number = &part[start..idx];
+            if number.is_empty() {
+                return None;
+            }
+            result.push((number, c));
+            start = idx + 1;
+        }
+    }
+    (start == part.len()).then_some(result)
+}
+
+/// Parse `7`, `-7` or `7.25` seconds into nanoseconds
+fn parse_seconds(number: &str) -> Option<i64> {
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if fraction.len() > 9 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let negative = whole.starts_with('-');
+    let seconds: i64 = whole.parse().ok()?;
+    let fraction: i64 = format!("{:0<9}", fraction).parse().ok()?;
+    let fraction = if negative { -fraction } else { fraction };
+    seconds.checked_mul(NANOS_PER_SECOND)?.checked_add(fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_format_instant_in_timezone() {
+        let instant = Utc.with_ymd_and_hms(2024, 1, 15, 10, 0, 0).unwrap();
+        assert_eq!(format_instant(instant, &parse_timezone("UTC").unwrap()), "2024-01-15T10:00:00+00:00");
+        assert_eq!(
+            format_instant(instant, &parse_timezone("America/New_York").unwrap()),
+            "2024-01-15T05:00:00-05:00"
+        );
+        assert_eq!(format_instant(instant, &parse_timezone("+05:30").unwrap()), "2024-01-15T15:30:00+05:30");
+        assert!(parse_timezone("Mars/Olympus_Mons").is_err());
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(IsoDuration::default()), "PT0S");
+        assert_eq!(format_duration(IsoDuration::from_nanos(5_400 * NANOS_PER_SECOND)), "PT1H30M");
+        assert_eq!(format_duration(IsoDuration::from_nanos(-1_500_000_000)), "-PT1.5S");
+        assert_eq!(
+            format_duration(IsoDuration {
+                months: 14,
+                days: 3,
+                nanos: 4 * NANOS_PER_HOUR + 250_000_000
+            }),
+            "P1Y2M3DT4H0.25S"
+        );
+        assert_eq!(
+            format_duration(IsoDuration {
+                months: 1,
+                days: -2,
+                nanos: 0
+            }),
+            "P1M-2D"
+        );
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("PT1H30M"), Some(IsoDuration::from_nanos(5_400 * NANOS_PER_SECOND)));
+        assert_eq!(parse_duration("-PT1.5S"), Some(IsoDuration::from_nanos(-1_500_000_000)));
+        assert_eq!(
+            parse_duration("P1Y2M1W3DT4H"),
+            Some(IsoDuration {
+                months: 14,
+                days: 10,
+                nanos: 4 * NANOS_PER_HOUR
+            })
+        );
+        assert_eq!(parse_duration("P2D").unwrap().total_nanos(), Some(2 * NANOS_PER_DAY));
+        assert_eq!(parse_duration("P1M").unwrap().total_nanos(), None);
+        for invalid in ["", "P", "PT", "1H", "PT1.5H", "PTS", "P1H", "PT0.1234567891S"] {
+            assert_eq!(parse_duration(invalid), None, "{}", invalid);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::config::EntityConfig;
+use crate::config::{EntityConfig, PrimaryKey};
 use crate::unity::{TableInfo, UnityClient};
 use crate::error::Result;
 
@@ -46,6 +46,7 @@ pub async fn discover_entities(
             primary_key,
             description: table.comment.or(metadata.comment),
             storage_location: table.storage_location.or(metadata.storage_location),
+            ..Default::default()
         };
         
         entities.push(entity);
@@ -58,27 +59,37 @@ pub async fn discover_entities(
 fn infer_primary_key(
     metadata: &crate::unity::types::TableMetadata,
     table: &TableInfo,
-) -> Result<String> {
-    // 1. Check table properties for explicit primary_key
+) -> Result<PrimaryKey> {
+    // 1. Check table properties for explicit primary_key ("id" or "date,region,sku")
     if let Some(pk) = table.properties.get("primary_key") {
-        return Ok(pk.clone());
+        return Ok(parse_primary_key_property(pk));
     }
     
     if let Some(pk) = metadata.properties.get("primary_key") {
-        return Ok(pk.clone());
+        return Ok(parse_primary_key_property(pk));
     }
     
-    // 2. Look for column named "id"
+    // 2. Use a declared PRIMARY KEY constraint (possibly spanning several columns)
+    if let Some(constraint) = metadata
+        .table_constraints
+        .iter()
+        .filter_map(|c| c.primary_key_constraint.as_ref())
+        .find(|c| !c.child_columns.is_empty())
+    {
+        return Ok(constraint.child_columns.clone().into());
+    }
+    
+    // 3. Look for column named "id"
     if let Some(col) = metadata.columns.iter().find(|c| c.name == "id") {
-        return Ok(col.name.clone());
+        return Ok(col.name.clone().into());
     }
     
-    // 3. Look for first column ending with "_id"
+    // 4. Look for first column ending with "_id"
     if let Some(col) = metadata.columns.iter().find(|c| c.name.ends_with("_id")) {
-        return Ok(col.name.clone());
+        return Ok(col.name.clone().into());
     }
     
-    // 4. Fall back to first column
+    // 5. Fall back to first column
     if let Some(first_col) = metadata.columns.first() {
         tracing::warn!(
             "No obvious primary key found for {}.{}.{}, using first column: {}",
@@ -87,7 +98,7 @@ fn infer_primary_key(
             metadata.name,
             first_col.name
         );
-        return Ok(first_col.name.clone());
+        return Ok(first_col.name.clone().into());
     }
     
     // This shouldn't happen for valid tables
@@ -100,6 +111,16 @@ fn infer_primary_key(
     ))
 }
 
+/// Parse a `primary_key` table property, splitting comma-separated columns
+fn parse_primary_key_property(value: &str) -> PrimaryKey {
+    value
+        .split(',')
+        .map(|column| column.trim().to_string())
+        .filter(|column| !column.is_empty())
+        .collect::<Vec<_>>()
+        .into()
+}
+
 /// Convert snake_case to PascalCase
 pub fn to_pascal_case(s: &str) -> String {
     s.split('_')
@@ -159,6 +180,7 @@ mod tests {
                 .into_iter()
                 .collect(),
             comment: None,
+            table_constraints: Vec::new(),
         };
         
         let table = TableInfo {
@@ -197,6 +219,7 @@ mod tests {
             storage_location: None,
             properties: std::collections::HashMap::new(),
             comment: None,
+            table_constraints: Vec::new(),
         };
         
         let table = TableInfo {
@@ -235,6 +258,7 @@ mod tests {
             storage_location: None,
             properties: std::collections::HashMap::new(),
             comment: None,
+            table_constraints: Vec::new(),
         };
         
         let table = TableInfo {
@@ -251,5 +275,54 @@ mod tests {
         let pk = infer_primary_key(&metadata, &table).unwrap();
         assert_eq!(pk, "customer_id");
     }
+
+    #[test]
+    fn test_infer_composite_primary_key_from_constraint() {
+        let column = |name: &str, position| crate::unity::types::ColumnInfo {
+            name: name.to_string(),
+            type_text: "string".to_string(),
+            type_name: "string".to_string(),
+            position,
+            nullable: false,
+            comment: None,
+        };
+        let mut metadata = crate::unity::types::TableMetadata {
+            name: "daily_sales".to_string(),
+            catalog_name: "main".to_string(),
+            schema_name: "sales".to_string(),
+            table_type: "MANAGED".to_string(),
+            data_source_format: "DELTA".to_string(),
+            columns: vec![column("sku_id", 0), column("date", 1), column("region", 2)],
+            storage_location: None,
+            properties: std::collections::HashMap::new(),
+            comment: None,
+            table_constraints: vec![crate::unity::types::TableConstraint {
+                primary_key_constraint: Some(crate::unity::types::PrimaryKeyConstraint {
+                    name: "daily_sales_pk".to_string(),
+                    child_columns: vec!["date".to_string(), "region".to_string(), "sku_id".to_string()],
+                }),
+            }],
+        };
+        
+        let table = TableInfo {
+            name: "daily_sales".to_string(),
+            catalog_name: "main".to_string(),
+            schema_name: "sales".to_string(),
+            table_type: "MANAGED".to_string(),
+            data_source_format: "DELTA".to_string(),
+            storage_location: None,
+            comment: None,
+            properties: std::collections::HashMap::new(),
+        };
+        
+        // The constraint wins over the "_id" heuristic
+        let pk = infer_primary_key(&metadata, &table).unwrap();
+        assert_eq!(pk.columns(), ["date", "region", "sku_id"]);
+
+        // An explicit property still overrides the catalog
+        metadata.properties.insert("primary_key".to_string(), "date, region".to_string());
+        let pk = infer_primary_key(&metadata, &table).unwrap();
+        assert_eq!(pk, PrimaryKey::Columns(vec!["date".to_string(), "region".to_string()]));
+    }
 }
 
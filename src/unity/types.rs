@@ -38,6 +38,24 @@ pub struct TableMetadata {
     pub properties: HashMap<String, String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub comment: Option<String>,
+    /// Informational constraints (`PRIMARY KEY`, `FOREIGN KEY`) declared on the table
+    #[serde(default)]
+    pub table_constraints: Vec<TableConstraint>,
+}
+
+/// A table constraint; only primary keys are read, other kinds are ignored
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TableConstraint {
+    #[serde(default)]
+    pub primary_key_constraint: Option<PrimaryKeyConstraint>,
+}
+
+/// A `PRIMARY KEY (col, ...)` constraint
+#[derive(Debug, Clone, Deserialize)]
+pub struct PrimaryKeyConstraint {
+    pub name: String,
+    /// Key columns, in declaration order
+    pub child_columns: Vec<String>,
 }
 
 /// Column information
@@ -95,5 +113,39 @@ mod tests {
         assert_eq!(column_info.position, 0);
         assert!(!column_info.nullable);
     }
+
+    #[test]
+    fn test_deserialize_table_constraints() {
+        let json = r#"{
+            "name": "daily_sales",
+            "catalog_name": "main",
+            "schema_name": "sales",
+            "table_type": "MANAGED",
+            "data_source_format": "DELTA",
+            "columns": [],
+            "table_constraints": [
+                {
+                    "primary_key_constraint": {
+                        "name": "daily_sales_pk",
+                        "child_columns": ["date", "region", "sku"]
+                    }
+                },
+                {
+                    "foreign_key_constraint": {
+                        "name": "daily_sales_sku_fk",
+                        "child_columns": ["sku"],
+                        "parent_table": "main.sales.products",
+                        "parent_columns": ["sku"]
+                    }
+                }
+            ]
+        }"#;
+
+        let metadata: TableMetadata = serde_json::from_str(json).unwrap();
+        assert_eq!(metadata.table_constraints.len(), 2);
+        let primary_key = metadata.table_constraints[0].primary_key_constraint.as_ref().unwrap();
+        assert_eq!(primary_key.child_columns, ["date", "region", "sku"]);
+        assert!(metadata.table_constraints[1].primary_key_constraint.is_none());
+    }
 }
 
@@ -1,9 +1,9 @@
-/// End-to-end test for the full Delta table workflow
-///
-/// This test verifies:
-/// 1. Delta tables can be loaded from disk
-/// 2. GraphQL schema can be built from Delta tables
-/// 3. Queries work against Delta table data
+//! End-to-end test for the full Delta table workflow
+//!
+//! This test verifies:
+//! 1. Delta tables can be loaded from disk
+//! 2. GraphQL schema can be built from Delta tables
+//! 3. Queries work against Delta table data
 
 mod delta_tests {
     use nouninator::config::EntityConfig;
@@ -15,6 +15,13 @@ mod delta_tests {
         // Verify Delta tables were created
         let delta_dir = "examples/delta";
         
+        // Skip test if the example Delta tables haven't been generated yet
+        if !Path::new(delta_dir).exists() {
+            eprintln!("Skipping test: Delta tables not found at {}", delta_dir);
+            eprintln!("Run: cargo run -- init --example");
+            return;
+        }
+        
         let tables = vec!["nouns", "verbs", "adjectives", "sentences", "synonyms", "word_frequency"];
         
         for table in tables {
@@ -55,9 +62,10 @@ mod delta_tests {
         let entity = EntityConfig {
             table: "nouns".to_string(),
             graphql_name: "Noun".to_string(),
-            primary_key: "noun_id".to_string(),
+            primary_key: "noun_id".into(),
             description: Some("Nouns from Delta table".to_string()),
             storage_location: None,
+            ..Default::default()
         };
 
         // Build schema
@@ -65,9 +73,6 @@ mod delta_tests {
             .build_schema(vec![entity])
             .await
             .expect("Failed to build schema from Delta table");
-
-        // Verify schema was built successfully
-        assert!(true, "Schema built successfully from Delta table");
     }
 
     #[tokio::test]
@@ -94,9 +99,10 @@ mod delta_tests {
         let entity = EntityConfig {
             table: "word_frequency".to_string(),
             graphql_name: "WordFrequency".to_string(),
-            primary_key: "word_id".to_string(),
+            primary_key: "word_id".into(),
             description: None,
             storage_location: None,
+            ..Default::default()
         };
 
         let schema = builder.build_schema(vec![entity]).await.expect("Failed to build schema");
@@ -146,16 +152,18 @@ mod delta_tests {
             EntityConfig {
                 table: "nouns".to_string(),
                 graphql_name: "Noun".to_string(),
-                primary_key: "noun_id".to_string(),
+                primary_key: "noun_id".into(),
                 description: None,
                 storage_location: None,
+                ..Default::default()
             },
             EntityConfig {
                 table: "verbs".to_string(),
                 graphql_name: "Verb".to_string(),
-                primary_key: "verb_id".to_string(),
+                primary_key: "verb_id".into(),
                 description: None,
                 storage_location: None,
+                ..Default::default()
             },
         ];
 
@@ -186,5 +194,1344 @@ mod delta_tests {
 
         println!("Multi-table query result: {}", serde_json::to_string_pretty(&data).unwrap());
     }
-}
 
+    /// Write a two-version Delta table of `(id, word)` rows
+    async fn two_version_table(path: &str) {
+        use deltalake::arrow::array::{Int64Array, StringArray};
+        use deltalake::arrow::datatypes::{DataType, Field, Schema};
+        use deltalake::arrow::record_batch::RecordBatch;
+        use deltalake::protocol::SaveMode;
+        use deltalake::DeltaOps;
+        use std::sync::Arc;
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("word", DataType::Utf8, false),
+        ]));
+
+        for (id, word) in [(1, "cat"), (2, "dog")] {
+            let batch = RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(Int64Array::from(vec![id])),
+                    Arc::new(StringArray::from(vec![word])),
+                ],
+            )
+            .unwrap();
+
+            DeltaOps::try_from_uri(path)
+                .await
+                .unwrap()
+                .write(vec![batch])
+                .with_save_mode(SaveMode::Append)
+                .await
+                .expect("Failed to write Delta table");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_time_travel() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        two_version_table(path).await;
+
+        let mut builder = SchemaBuilder::new();
+        builder
+            .register_table_from_path("words", path)
+            .await
+            .expect("Failed to register Delta table");
+
+        let entity = EntityConfig {
+            table: "words".to_string(),
+            graphql_name: "Word".to_string(),
+            primary_key: "id".into(),
+            ..Default::default()
+        };
+        let schema = builder.build_schema(vec![entity]).await.unwrap();
+
+        let query = r#"
+            query {
+                latest: list_word { id }
+                first: list_word(asOfVersion: 0) { id }
+                missing: word(id: "2", asOfVersion: 0) { id }
+                present: word(id: "2", asOfVersion: "1") { word }
+                count: word_aggregate(asOfVersion: 0) { count }
+                future: list_word(asOfTimestamp: "2999-01-01T00:00:00Z") { id }
+            }
+        "#;
+        let response = schema.execute(query).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+        let data = response.data.into_json().unwrap();
+        assert_eq!(data["latest"].as_array().unwrap().len(), 2);
+        assert_eq!(data["first"], serde_json::json!([{ "id": "1" }]));
+        assert!(data["missing"].is_null());
+        assert_eq!(data["present"]["word"], "dog");
+        assert_eq!(data["count"], serde_json::json!([{ "count": 1 }]));
+        assert_eq!(data["future"].as_array().unwrap().len(), 2);
+
+        let response = schema
+            .execute(r#"{ list_word(asOfVersion: 0, asOfTimestamp: "2999-01-01T00:00:00Z") { id } }"#)
+            .await;
+        assert!(!response.errors.is_empty(), "Combining version and timestamp should fail");
+
+        let response = schema.execute(r#"{ list_word(asOfVersion: 7) { id } }"#).await;
+        assert!(!response.errors.is_empty(), "Unknown versions should fail");
+    }
+
+    #[tokio::test]
+    async fn test_relations_read_as_of_their_parent() {
+        use deltalake::arrow::array::{ArrayRef, Int64Array, StringArray};
+        use deltalake::arrow::record_batch::RecordBatch;
+        use deltalake::protocol::SaveMode;
+        use deltalake::DeltaOps;
+        use nouninator::config::{RelationConfig, RelationKind};
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        let dir = tempfile::tempdir().unwrap();
+        let customers_path = dir.path().join("customers");
+        let orders_path = dir.path().join("orders");
+        std::fs::create_dir_all(&customers_path).unwrap();
+        std::fs::create_dir_all(&orders_path).unwrap();
+        let customers_path = customers_path.to_str().unwrap();
+        let orders_path = orders_path.to_str().unwrap();
+
+        let write = |path: &str, batch: RecordBatch, mode: SaveMode| {
+            let path = path.to_string();
+            async move {
+                DeltaOps::try_from_uri(&path)
+                    .await
+                    .unwrap()
+                    .write(vec![batch])
+                    .with_save_mode(mode)
+                    .await
+                    .expect("Failed to write Delta table");
+                // Commit times have millisecond precision
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        };
+        let customer = |name: &str| {
+            RecordBatch::try_from_iter([
+                ("customer_id", Arc::new(Int64Array::from(vec![1])) as ArrayRef),
+                ("name", Arc::new(StringArray::from(vec![name])) as ArrayRef),
+            ])
+            .unwrap()
+        };
+        let order = |order_id: i64| {
+            RecordBatch::try_from_iter([
+                ("order_id", Arc::new(Int64Array::from(vec![order_id])) as ArrayRef),
+                ("customer_id", Arc::new(Int64Array::from(vec![1])) as ArrayRef),
+            ])
+            .unwrap()
+        };
+
+        // customers v0, orders v0, customers v1 (renamed), orders v1
+        write(customers_path, customer("Ann"), SaveMode::Append).await;
+        write(orders_path, order(10), SaveMode::Append).await;
+        write(customers_path, customer("Anne"), SaveMode::Overwrite).await;
+        write(orders_path, order(11), SaveMode::Append).await;
+
+        let mut builder = SchemaBuilder::new();
+        builder.register_table_from_path("customers", customers_path).await.unwrap();
+        builder.register_table_from_path("orders", orders_path).await.unwrap();
+        let entities = vec![
+            EntityConfig {
+                table: "orders".to_string(),
+                graphql_name: "Order".to_string(),
+                primary_key: "order_id".into(),
+                relation: vec![RelationConfig {
+                    name: "customer".to_string(),
+                    target: "Customer".to_string(),
+                    local_column: "customer_id".to_string(),
+                    remote_column: "customer_id".to_string(),
+                    kind: RelationKind::One,
+                }],
+                ..Default::default()
+            },
+            EntityConfig {
+                table: "customers".to_string(),
+                graphql_name: "Customer".to_string(),
+                primary_key: "customer_id".into(),
+                relation: vec![RelationConfig {
+                    name: "orders".to_string(),
+                    target: "Order".to_string(),
+                    local_column: "customer_id".to_string(),
+                    remote_column: "customer_id".to_string(),
+                    kind: RelationKind::Many,
+                }],
+                ..Default::default()
+            },
+        ];
+        let schema = builder.build_schema(entities).await.unwrap();
+
+        let query = r#"
+            query {
+                latest: order(order_id: "10") { customer { name orders { order_id } } }
+                old: order(order_id: "10", asOfVersion: 0) { customer { name orders { order_id } } }
+                listed: list_order(asOfVersion: 0) { customer { name } }
+                paged: order_connection(asOfVersion: 0) { edges { node { customer { name } } } }
+                byIds: order_by_ids(ids: ["10"], asOfVersion: 0) { node { customer { name } } }
+                renamed: customer(customer_id: "1", asOfVersion: 1) { orders(filter: { order_id: { in: ["10", "11"] } }) { order_id } }
+            }
+        "#;
+        let response = schema.execute(query).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+        let data = response.data.into_json().unwrap();
+        assert_eq!(
+            data["latest"]["customer"],
+            serde_json::json!({ "name": "Anne", "orders": [{ "order_id": "10" }, { "order_id": "11" }] })
+        );
+        assert_eq!(
+            data["old"]["customer"],
+            serde_json::json!({ "name": "Ann", "orders": [{ "order_id": "10" }] })
+        );
+        assert_eq!(data["listed"], serde_json::json!([{ "customer": { "name": "Ann" } }]));
+        assert_eq!(data["paged"]["edges"][0]["node"]["customer"]["name"], "Ann");
+        assert_eq!(data["byIds"][0]["node"]["customer"]["name"], "Ann");
+        // Orders as of the rename, before order 11 was written
+        assert_eq!(data["renamed"]["orders"], serde_json::json!([{ "order_id": "10" }]));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_serves_new_commits() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        two_version_table(path).await;
+
+        let mut builder = SchemaBuilder::new();
+        builder.register_table_from_path("words", path).await.unwrap();
+        let registry = builder.registry();
+
+        let entity = EntityConfig {
+            table: "words".to_string(),
+            graphql_name: "Word".to_string(),
+            primary_key: "id".into(),
+            ..Default::default()
+        };
+        let schema = builder.build_schema(vec![entity]).await.unwrap();
+        assert_eq!(registry.served_versions()["words"], 1);
+
+        // Two more commits land while serving
+        two_version_table(path).await;
+        let count = |data: serde_json::Value| data["list_word"].as_array().unwrap().len();
+
+        let stale = schema.execute("{ list_word { id } }").await;
+        assert_eq!(count(stale.data.into_json().unwrap()), 2);
+
+        let refreshed = registry.served_table("words").unwrap().refresh().await.unwrap();
+        assert_eq!(refreshed, Some(3));
+        assert_eq!(registry.served_versions()["words"], 3);
+
+        let fresh = schema.execute("{ list_word { id } }").await;
+        assert_eq!(count(fresh.data.into_json().unwrap()), 4);
+    }
+
+    #[tokio::test]
+    async fn test_subscriptions_push_new_commits() {
+        use futures_util::StreamExt;
+        use std::time::Duration;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        two_version_table(path).await;
+
+        let mut builder = SchemaBuilder::new();
+        builder.register_table_from_path("words", path).await.unwrap();
+        let registry = builder.registry();
+
+        let entity = EntityConfig {
+            table: "words".to_string(),
+            graphql_name: "Word".to_string(),
+            primary_key: "id".into(),
+            ..Default::default()
+        };
+        let schema = builder.build_schema(vec![entity]).await.unwrap();
+
+        let mut added = schema.execute_stream("subscription { wordAdded { id word } }");
+        let mut changed = schema.execute_stream(
+            r#"subscription { wordChanged(filter: { word: { eq: "dog" } }) { id _change_type _commit_version } }"#,
+        );
+
+        // Nothing is pushed until the served table moves to a new version
+        // (polling also starts the subscriptions)
+        let pending = tokio::time::timeout(Duration::from_millis(100), added.next()).await;
+        assert!(pending.is_err(), "No rows should be pushed before a refresh");
+        let pending = tokio::time::timeout(Duration::from_millis(100), changed.next()).await;
+        assert!(pending.is_err(), "No changes should be pushed before a refresh");
+
+        two_version_table(path).await;
+        registry.served_table("words").unwrap().refresh().await.unwrap();
+
+        let mut words = Vec::new();
+        for _ in 0..2 {
+            let response = tokio::time::timeout(Duration::from_secs(10), added.next())
+                .await
+                .expect("Timed out waiting for wordAdded")
+                .unwrap();
+            assert!(response.errors.is_empty(), "{:?}", response.errors);
+            let data = response.data.into_json().unwrap();
+            words.push(data["wordAdded"]["word"].as_str().unwrap().to_string());
+        }
+        assert_eq!(words, vec!["cat", "dog"]);
+
+        let response = tokio::time::timeout(Duration::from_secs(10), changed.next())
+            .await
+            .expect("Timed out waiting for wordChanged")
+            .unwrap();
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.data.into_json().unwrap()["wordChanged"],
+            serde_json::json!({ "id": "2", "_change_type": "insert", "_commit_version": 3 })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_change_data_feed() {
+        use deltalake::arrow::array::{Int64Array, StringArray};
+        use deltalake::arrow::datatypes::{DataType, Field, Schema};
+        use deltalake::arrow::record_batch::RecordBatch;
+        use deltalake::kernel::{DataType as DeltaType, PrimitiveType, StructField};
+        use deltalake::protocol::SaveMode;
+        use deltalake::table::config::DeltaConfigKey;
+        use deltalake::DeltaOps;
+        use std::sync::Arc;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+
+        // v0: create with CDF enabled
+        let table = DeltaOps::try_from_uri(path)
+            .await
+            .unwrap()
+            .create()
+            .with_columns(vec![
+                StructField::new("id", DeltaType::Primitive(PrimitiveType::Long), false),
+                StructField::new("word", DeltaType::Primitive(PrimitiveType::String), false),
+            ])
+            .with_configuration_property(DeltaConfigKey::EnableChangeDataFeed, Some("true"))
+            .await
+            .unwrap();
+
+        // v1: insert cat and dog, v2: delete cat
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("word", DataType::Utf8, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2])),
+                Arc::new(StringArray::from(vec!["cat", "dog"])),
+            ],
+        )
+        .unwrap();
+        let table = DeltaOps(table)
+            .write(vec![batch])
+            .with_save_mode(SaveMode::Append)
+            .await
+            .unwrap();
+        DeltaOps(table).delete().with_predicate("id = 1").await.unwrap();
+
+        let mut builder = SchemaBuilder::new();
+        builder.register_table_from_path("words", path).await.unwrap();
+        let entity = EntityConfig {
+            table: "words".to_string(),
+            graphql_name: "Word".to_string(),
+            primary_key: "id".into(),
+            ..Default::default()
+        };
+        let schema = builder.build_schema(vec![entity]).await.unwrap();
+
+        let query = r#"
+            query {
+                all: word_changes(fromVersion: 0) {
+                    changes { id word _change_type _commit_version _commit_timestamp }
+                    hasMore
+                    nextVersion
+                }
+                deletes: word_changes(fromVersion: "2", toVersion: "2") { changes { id _change_type } }
+                dogs: word_changes(fromVersion: 0, filter: { word: { eq: "dog" } }) { changes { id _change_type } }
+                none: word_changes(fromVersion: 0, toTimestamp: "2000-01-01T00:00:00Z") { changes { id } }
+            }
+        "#;
+        let response = schema.execute(query).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+        let data = response.data.into_json().unwrap();
+        let all = data["all"]["changes"].as_array().unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0]["_commit_version"], 1);
+        assert_eq!(all[0]["_change_type"], "insert");
+        assert!(all[0]["_commit_timestamp"].is_string());
+        assert_eq!(all[2]["_change_type"], "delete");
+        assert_eq!(all[2]["word"], "cat");
+        assert_eq!(data["all"]["hasMore"], false);
+        assert_eq!(data["all"]["nextVersion"], 3);
+        assert_eq!(data["deletes"]["changes"], serde_json::json!([{ "id": "1", "_change_type": "delete" }]));
+        assert_eq!(data["dogs"]["changes"], serde_json::json!([{ "id": "2", "_change_type": "insert" }]));
+        assert_eq!(data["none"]["changes"], serde_json::json!([]));
+
+        // Pages continue within a commit, then across commits
+        let mut pages = Vec::new();
+        let (mut version, mut offset) = (0, 0);
+        loop {
+            let query = format!(
+                "{{ word_changes(fromVersion: {}, offset: {}, limit: 1) {{ changes {{ id _change_type }} hasMore nextVersion nextOffset }} }}",
+                version, offset
+            );
+            let response = schema.execute(query).await;
+            assert!(response.errors.is_empty(), "{:?}", response.errors);
+            let page = response.data.into_json().unwrap()["word_changes"].clone();
+            pages.extend(page["changes"].as_array().unwrap().clone());
+            (version, offset) = (page["nextVersion"].as_i64().unwrap(), page["nextOffset"].as_u64().unwrap());
+            if page["hasMore"] == false {
+                break;
+            }
+        }
+        assert_eq!((version, offset), (3, 0));
+        assert_eq!(
+            pages,
+            vec![
+                serde_json::json!({ "id": "1", "_change_type": "insert" }),
+                serde_json::json!({ "id": "2", "_change_type": "insert" }),
+                serde_json::json!({ "id": "1", "_change_type": "delete" }),
+            ]
+        );
+
+        let response = schema.execute("{ word_changes(fromVersion: 2, toVersion: 1) { changes { id } } }").await;
+        assert!(!response.errors.is_empty());
+
+        // The range must start somewhere
+        let response = schema.execute("{ word_changes { changes { id } } }").await;
+        assert!(!response.errors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_changes_require_change_data_feed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        two_version_table(path).await;
+
+        let mut builder = SchemaBuilder::new();
+        builder.register_table_from_path("words", path).await.unwrap();
+        let entity = EntityConfig {
+            table: "words".to_string(),
+            graphql_name: "Word".to_string(),
+            primary_key: "id".into(),
+            ..Default::default()
+        };
+        let schema = builder.build_schema(vec![entity]).await.unwrap();
+
+        let response = schema.execute("{ word_changes(fromVersion: 0) { changes { id } } }").await;
+        assert_eq!(response.errors.len(), 1);
+        assert!(response.errors[0].message.contains("Change Data Feed is not enabled"));
+    }
+
+    #[tokio::test]
+    async fn test_mutations() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        two_version_table(path).await;
+
+        let mut builder = SchemaBuilder::new();
+        builder.register_table_from_path("words", path).await.unwrap();
+        let entity = EntityConfig {
+            table: "words".to_string(),
+            graphql_name: "Word".to_string(),
+            primary_key: "id".into(),
+            mutations: true,
+            ..Default::default()
+        };
+        let schema = builder.build_schema(vec![entity]).await.unwrap();
+
+        let execute = |query: &'static str| {
+            let schema = schema.clone();
+            async move {
+                let response = schema.execute(query).await;
+                assert!(response.errors.is_empty(), "{}: {:?}", query, response.errors);
+                response.data.into_json().unwrap()
+            }
+        };
+
+        let data = execute(r#"mutation { createWord(input: { id: "3", word: "owl" }) { id word } }"#).await;
+        assert_eq!(data["createWord"], serde_json::json!({ "id": "3", "word": "owl" }));
+
+        let data = execute(r#"mutation { updateWord(id: "1", patch: { word: "lion" }) { word } }"#).await;
+        assert_eq!(data["updateWord"]["word"], "lion");
+        let data = execute(r#"mutation { updateWord(id: "99", patch: { word: "yak" }) { word } }"#).await;
+        assert!(data["updateWord"].is_null());
+
+        let data = execute(
+            r#"mutation {
+                a: upsertWord(input: { id: "2", word: "wolf" }) { word }
+                b: upsertWord(input: { id: "4", word: "bee" }) { word }
+            }"#,
+        )
+        .await;
+        assert_eq!(data["a"]["word"], "wolf");
+        assert_eq!(data["b"]["word"], "bee");
+
+        let data = execute(r#"mutation { yes: deleteWord(id: "3") no: deleteWord(id: "3") }"#).await;
+        assert_eq!(data["yes"], true);
+        assert_eq!(data["no"], false);
+
+        let data = execute("{ list_word { id word } }").await;
+        assert_eq!(
+            data["list_word"],
+            serde_json::json!([
+                { "id": "1", "word": "lion" },
+                { "id": "2", "word": "wolf" },
+                { "id": "4", "word": "bee" },
+            ])
+        );
+
+        // Creating an existing key fails without writing
+        let response = schema
+            .execute(r#"mutation { createWord(input: { id: "1", word: "dup" }) { id } }"#)
+            .await;
+        assert_eq!(response.errors.len(), 1);
+        assert!(response.errors[0].message.contains("already exists"));
+
+        // One commit per effective write on top of the two initial versions;
+        // writes that match nothing do not commit
+        let version = deltalake::open_table(path).await.unwrap().version();
+        assert_eq!(version, 6);
+    }
+
+    #[tokio::test]
+    async fn test_row_filter_limits_writes() {
+        use async_graphql::Request;
+        use nouninator::auth::Claims;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        two_version_table(path).await;
+
+        let mut builder = SchemaBuilder::new();
+        builder.register_table_from_path("words", path).await.unwrap();
+        let entity = EntityConfig {
+            table: "words".to_string(),
+            graphql_name: "Word".to_string(),
+            primary_key: "id".into(),
+            mutations: true,
+            row_filter: Some("word <> {claims.blocked}".to_string()),
+            ..Default::default()
+        };
+        let schema = builder.build_schema(vec![entity]).await.unwrap();
+
+        let claims = |blocked: &str| {
+            let claims = serde_json::json!({ "sub": "user-1", "blocked": blocked });
+            Claims::new(claims.as_object().unwrap().clone())
+        };
+        let execute = |query: &'static str| {
+            let schema = schema.clone();
+            let claims = claims("dog");
+            async move { schema.execute(Request::new(query).data(claims)).await }
+        };
+
+        let data = execute("{ list_word { id word } word(id: \"2\") { id } }").await.data.into_json().unwrap();
+        assert_eq!(data["list_word"], serde_json::json!([{ "id": "1", "word": "cat" }]));
+        assert!(data["word"].is_null());
+
+        // Rows outside the filter can be neither changed nor written
+        let data = execute(r#"mutation { updateWord(id: "2", patch: { word: "wolf" }) { word } deleteWord(id: "2") }"#)
+            .await
+            .data
+            .into_json()
+            .unwrap();
+        assert!(data["updateWord"].is_null());
+        assert_eq!(data["deleteWord"], false);
+        // Keys held by rows outside the filter fail just like rows outside it,
+        // so they don't give away that the rows exist
+        for mutation in [
+            r#"mutation { createWord(input: { id: "3", word: "dog" }) { id } }"#,
+            r#"mutation { createWord(input: { id: "2", word: "owl" }) { id } }"#,
+            r#"mutation { upsertWord(input: { id: "2", word: "wolf" }) { id } }"#,
+        ] {
+            let errors = execute(mutation).await.errors;
+            assert_eq!(errors.len(), 1, "{}", mutation);
+            assert_eq!(errors[0].message, "Rows written to Word must match its row filter", "{}", mutation);
+        }
+        let errors = execute(r#"mutation { createWord(input: { id: "1", word: "owl" }) { id } }"#).await.errors;
+        assert!(errors[0].message.contains("already exists"), "{:?}", errors);
+
+        // Without the claim nothing can be read
+        let response = schema.execute("{ list_word { id } }").await;
+        assert!(response.errors[0].message.contains("no 'blocked' claim"), "{:?}", response.errors);
+        for query in [r#"{ word(id: "1") { id } }"#, r#"{ word_by_ids(ids: ["1"]) { node { id } } }"#] {
+            let response = schema.execute(query).await;
+            assert_eq!(response.errors.len(), 1);
+            assert!(response.errors[0].message.starts_with("Not authorized to query Word"), "{:?}", response.errors);
+        }
+
+        let response = schema
+            .execute(Request::new("{ list_word { word } }").data(claims("none")))
+            .await;
+        assert_eq!(
+            response.data.into_json().unwrap()["list_word"],
+            serde_json::json!([{ "word": "cat" }, { "word": "dog" }])
+        );
+        // Nothing was committed on top of the two initial versions
+        assert_eq!(deltalake::open_table(path).await.unwrap().version(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_row_filter_limits_patches() {
+        use async_graphql::Request;
+        use nouninator::auth::Claims;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        two_version_table(path).await;
+
+        // Each word is its own tenant
+        let mut builder = SchemaBuilder::new();
+        builder.register_table_from_path("words", path).await.unwrap();
+        let entity = EntityConfig {
+            table: "words".to_string(),
+            graphql_name: "Word".to_string(),
+            primary_key: "id".into(),
+            mutations: true,
+            row_filter: Some("word = {claims.tenant}".to_string()),
+            ..Default::default()
+        };
+        let schema = builder.build_schema(vec![entity]).await.unwrap();
+        let claims = serde_json::json!({ "sub": "user-1", "tenant": "cat" });
+        let claims = Claims::new(claims.as_object().unwrap().clone());
+
+        // A tenant cannot move its row to another tenant
+        let response = schema
+            .execute(Request::new(r#"mutation { updateWord(id: "1", patch: { word: "dog" }) { id } }"#).data(claims.clone()))
+            .await;
+        assert_eq!(response.errors.len(), 1);
+        assert!(response.errors[0].message.contains("must match its row filter"), "{:?}", response.errors);
+        assert_eq!(deltalake::open_table(path).await.unwrap().version(), 1);
+
+        // Patches that keep the row inside the filter are applied
+        let response = schema
+            .execute(Request::new(r#"mutation { updateWord(id: "1", patch: { word: "cat" }) { word } }"#).data(claims))
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(response.data.into_json().unwrap()["updateWord"]["word"], "cat");
+        assert_eq!(deltalake::open_table(path).await.unwrap().version(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_column_rules_limit_writes() {
+        use async_graphql::Request;
+        use nouninator::auth::Principal;
+        use nouninator::config::ColumnConfig;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        two_version_table(path).await;
+
+        let mut builder = SchemaBuilder::new();
+        builder.register_table_from_path("words", path).await.unwrap();
+        let entity = EntityConfig {
+            table: "words".to_string(),
+            graphql_name: "Word".to_string(),
+            primary_key: "id".into(),
+            mutations: true,
+            columns: [(
+                "word".to_string(),
+                ColumnConfig {
+                    allow: Some(vec!["editor".to_string()]),
+                    ..Default::default()
+                },
+            )]
+            .into(),
+            ..Default::default()
+        };
+        let schema = builder.build_schema(vec![entity]).await.unwrap();
+        let principal = |roles: &[&str]| Principal {
+            roles: roles.iter().map(|role| role.to_string()).collect(),
+            ..Default::default()
+        };
+
+        // A caller who may not read `word` may not set it either
+        for mutation in [
+            r#"mutation { createWord(input: { id: "3", word: "owl" }) { id } }"#,
+            r#"mutation { upsertWord(input: { id: "1", word: "owl" }) { id } }"#,
+            r#"mutation { updateWord(id: "1", patch: { word: "owl" }) { id } }"#,
+            r#"mutation { bulkInsertWord(rows: [{ id: "3", word: "owl" }]) { rowCount } }"#,
+        ] {
+            let response = schema.execute(Request::new(mutation).data(principal(&[]))).await;
+            assert_eq!(response.errors.len(), 1, "{}", mutation);
+            assert!(response.errors[0].message.contains("Not authorized to write Word.word"), "{:?}", response.errors);
+        }
+        assert_eq!(deltalake::open_table(path).await.unwrap().version(), 1);
+
+        let response = schema
+            .execute(Request::new(r#"mutation { updateWord(id: "1", patch: { word: "owl" }) { word } }"#).data(principal(&["editor"])))
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(response.data.into_json().unwrap()["updateWord"]["word"], "owl");
+    }
+
+    #[tokio::test]
+    async fn test_mutations_are_opt_in() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        two_version_table(path).await;
+
+        let mut builder = SchemaBuilder::new();
+        builder.register_table_from_path("words", path).await.unwrap();
+        let entity = EntityConfig {
+            table: "words".to_string(),
+            graphql_name: "Word".to_string(),
+            primary_key: "id".into(),
+            ..Default::default()
+        };
+        let schema = builder.build_schema(vec![entity]).await.unwrap();
+
+        let response = schema
+            .execute(r#"mutation { createWord(input: { id: "3", word: "owl" }) { id } }"#)
+            .await;
+        assert!(!response.errors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mutations_need_settable_columns() {
+        use deltalake::arrow::array::{Array, Int64Array, ListArray};
+        use deltalake::arrow::datatypes::{DataType, Field, Int64Type, Schema};
+        use deltalake::arrow::record_batch::RecordBatch;
+        use deltalake::DeltaOps;
+        use std::sync::Arc;
+
+        // Mutation inputs cannot set list columns, so a non-nullable one rules mutations out
+        for nullable in [false, true] {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().to_str().unwrap();
+            let tags = ListArray::from_iter_primitive::<Int64Type, _, _>(vec![Some(vec![Some(1)])]);
+            let schema = Arc::new(Schema::new(vec![
+                Field::new("id", DataType::Int64, false),
+                Field::new("tags", tags.data_type().clone(), nullable),
+            ]));
+            let batch =
+                RecordBatch::try_new(schema, vec![Arc::new(Int64Array::from(vec![1])), Arc::new(tags)]).unwrap();
+            DeltaOps::try_from_uri(path).await.unwrap().write(vec![batch]).await.unwrap();
+
+            let mut builder = SchemaBuilder::new();
+            builder.register_table_from_path("posts", path).await.unwrap();
+            let entity = EntityConfig {
+                table: "posts".to_string(),
+                graphql_name: "Post".to_string(),
+                primary_key: "id".into(),
+                mutations: true,
+                ..Default::default()
+            };
+            let result = builder.build_schema(vec![entity]).await;
+            if nullable {
+                assert!(result.is_ok(), "{:?}", result.err());
+            } else {
+                let error = result.expect_err("Mutations on a non-nullable list column should be rejected");
+                assert!(error.to_string().contains("'tags' cannot be set"), "{}", error);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bulk_insert() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        two_version_table(path).await;
+
+        let mut builder = SchemaBuilder::new();
+        builder.register_table_from_path("words", path).await.unwrap();
+        let entity = EntityConfig {
+            table: "words".to_string(),
+            graphql_name: "Word".to_string(),
+            primary_key: "id".into(),
+            mutations: true,
+            max_bulk_insert_rows: Some(3),
+            ..Default::default()
+        };
+        let schema = builder.build_schema(vec![entity]).await.unwrap();
+
+        let response = schema
+            .execute(
+                r#"mutation {
+                    bulkInsertWord(rows: [
+                        { id: "3", word: "owl" }
+                        { id: "4", word: "bee" }
+                        { id: "5", word: "yak" }
+                    ]) { version rowCount }
+                }"#,
+            )
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let data = response.data.into_json().unwrap();
+        assert_eq!(data["bulkInsertWord"], serde_json::json!({ "version": 2, "rowCount": 3 }));
+
+        let response = schema.execute("{ list_word { id } }").await;
+        let data = response.data.into_json().unwrap();
+        assert_eq!(data["list_word"].as_array().unwrap().len(), 5);
+
+        // Over the configured limit nothing is written
+        let response = schema
+            .execute(
+                r#"mutation {
+                    bulkInsertWord(rows: [
+                        { id: "6", word: "a" }
+                        { id: "7", word: "b" }
+                        { id: "8", word: "c" }
+                        { id: "9", word: "d" }
+                    ]) { version }
+                }"#,
+            )
+            .await;
+        assert_eq!(response.errors.len(), 1);
+        assert!(response.errors[0].message.contains("max_bulk_insert_rows"));
+        assert_eq!(deltalake::open_table(path).await.unwrap().version(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_lookup_by_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        two_version_table(path).await;
+
+        let mut builder = SchemaBuilder::new();
+        builder.register_table_from_path("words", path).await.unwrap();
+        let entity = EntityConfig {
+            table: "words".to_string(),
+            graphql_name: "Word".to_string(),
+            primary_key: "id".into(),
+            max_lookup_ids: Some(4),
+            ..Default::default()
+        };
+        let schema = builder.build_schema(vec![entity]).await.unwrap();
+
+        let execute = |query: &'static str| {
+            let schema = schema.clone();
+            async move {
+                let response = schema.execute(query).await;
+                assert!(response.errors.is_empty(), "{}: {:?}", query, response.errors);
+                response.data.into_json().unwrap()
+            }
+        };
+
+        // Input order is kept, misses are null and repeated ids repeat the row
+        let data = execute(r#"{ word_by_ids(ids: ["2", "99", "1", "2"]) { node { id word } } }"#).await;
+        assert_eq!(
+            data["word_by_ids"],
+            serde_json::json!([
+                { "node": { "id": "2", "word": "dog" } },
+                { "node": null },
+                { "node": { "id": "1", "word": "cat" } },
+                { "node": { "id": "2", "word": "dog" } },
+            ])
+        );
+
+        // Keys outside every file's statistics prune the whole scan
+        let data = execute(r#"{ word_by_ids(ids: ["98", "99"]) { node { id } } }"#).await;
+        assert_eq!(data["word_by_ids"], serde_json::json!([{ "node": null }, { "node": null }]));
+        let data = execute(r#"{ word_by_ids(ids: []) { node { id } } }"#).await;
+        assert_eq!(data["word_by_ids"], serde_json::json!([]));
+        let data = execute(r#"{ list_word(filter: { id: { in: ["98", "99"] } }) { id } }"#).await;
+        assert_eq!(data["list_word"], serde_json::json!([]));
+
+        let data = execute(r#"{ word_by_ids(ids: ["1", "2"], asOfVersion: 0) { node { id } } }"#).await;
+        assert_eq!(data["word_by_ids"], serde_json::json!([{ "node": { "id": "1" } }, { "node": null }]));
+
+        let response = schema.execute(r#"{ word_by_ids(ids: ["1", "2", "3", "4", "5"]) { node { id } } }"#).await;
+        assert_eq!(response.errors.len(), 1);
+        assert!(response.errors[0].message.contains("max_lookup_ids is 4"));
+
+        let response = schema.execute(r#"{ word_by_ids(ids: ["one"]) { node { id } } }"#).await;
+        assert!(response.errors[0].message.contains("Invalid primary key"), "{:?}", response.errors);
+    }
+
+    #[tokio::test]
+    async fn test_struct_columns() {
+        use deltalake::arrow::array::{Float64Array, Int64Array, StringArray, StructArray};
+        use deltalake::arrow::buffer::NullBuffer;
+        use deltalake::arrow::datatypes::{DataType, Field, Fields, Schema};
+        use deltalake::arrow::record_batch::RecordBatch;
+        use deltalake::DeltaOps;
+        use std::sync::Arc;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+
+        let geo_fields = Fields::from(vec![Field::new("lat", DataType::Float64, true)]);
+        let address_fields = Fields::from(vec![
+            Field::new("city", DataType::Utf8, false),
+            Field::new("geo", DataType::Struct(geo_fields.clone()), true),
+        ]);
+        let geo = StructArray::new(
+            geo_fields,
+            vec![Arc::new(Float64Array::from(vec![Some(59.9), None]))],
+            Some(NullBuffer::from(vec![true, false])),
+        );
+        let address = StructArray::new(
+            address_fields.clone(),
+            vec![Arc::new(StringArray::from(vec!["Oslo", "Bergen"])), Arc::new(geo)],
+            None,
+        );
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("address", DataType::Struct(address_fields), true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![Arc::new(Int64Array::from(vec![1, 2])), Arc::new(address)],
+        )
+        .unwrap();
+        DeltaOps::try_from_uri(path).await.unwrap().write(vec![batch]).await.unwrap();
+
+        let mut builder = SchemaBuilder::new();
+        builder.register_table_from_path("customers", path).await.unwrap();
+        let entity = EntityConfig {
+            table: "customers".to_string(),
+            graphql_name: "Customer".to_string(),
+            primary_key: "id".into(),
+            ..Default::default()
+        };
+        let schema = builder.build_schema(vec![entity]).await.unwrap();
+
+        let response = schema
+            .execute("{ list_customer { id address { __typename city geo { __typename lat } } } }")
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.data.into_json().unwrap()["list_customer"],
+            serde_json::json!([
+                {
+                    "id": "1",
+                    "address": {
+                        "__typename": "CustomerAddress",
+                        "city": "Oslo",
+                        "geo": { "__typename": "CustomerAddressGeo", "lat": 59.9 }
+                    }
+                },
+                {
+                    "id": "2",
+                    "address": { "__typename": "CustomerAddress", "city": "Bergen", "geo": null }
+                },
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_and_map_columns() {
+        use deltalake::arrow::array::{
+            Array, Int64Array, Int64Builder, ListArray, ListBuilder, MapBuilder, MapFieldNames, StringArray, StringBuilder,
+            StructArray,
+        };
+        use deltalake::arrow::buffer::{NullBuffer, OffsetBuffer};
+        use deltalake::arrow::datatypes::{DataType, Field, Fields, Schema};
+        use deltalake::arrow::record_batch::RecordBatch;
+        use deltalake::DeltaOps;
+        use std::sync::Arc;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+
+        // tags: ["a", null] and []
+        let mut tags = ListBuilder::new(StringBuilder::new());
+        tags.values().append_value("a");
+        tags.values().append_null();
+        tags.append(true);
+        tags.append(true);
+        let tags = tags.finish();
+
+        // line_items: [{sku: "x"}, null, {sku: "y"}] and null
+        let item_fields = Fields::from(vec![Field::new("sku", DataType::Utf8, true)]);
+        let items = StructArray::new(
+            item_fields.clone(),
+            vec![Arc::new(StringArray::from(vec![Some("x"), None, Some("y")]))],
+            Some(NullBuffer::from(vec![true, false, true])),
+        );
+        let item_field = Arc::new(Field::new("item", DataType::Struct(item_fields), true));
+        let line_items = ListArray::new(
+            item_field,
+            OffsetBuffer::new(vec![0, 3, 3].into()),
+            Arc::new(items),
+            Some(NullBuffer::from(vec![true, false])),
+        );
+
+        // attributes: {"size": 3} and {}
+        let names = MapFieldNames {
+            entry: "key_value".to_string(),
+            key: "key".to_string(),
+            value: "value".to_string(),
+        };
+        let mut attributes = MapBuilder::new(Some(names), StringBuilder::new(), Int64Builder::new());
+        attributes.keys().append_value("size");
+        attributes.values().append_value(3);
+        attributes.append(true).unwrap();
+        attributes.append(true).unwrap();
+        let attributes = attributes.finish();
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("tags", tags.data_type().clone(), true),
+            Field::new("line_items", line_items.data_type().clone(), true),
+            Field::new("attributes", attributes.data_type().clone(), true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2])),
+                Arc::new(tags),
+                Arc::new(line_items),
+                Arc::new(attributes),
+            ],
+        )
+        .unwrap();
+        DeltaOps::try_from_uri(path).await.unwrap().write(vec![batch]).await.unwrap();
+
+        let mut builder = SchemaBuilder::new();
+        builder.register_table_from_path("orders", path).await.unwrap();
+        let entity = EntityConfig {
+            table: "orders".to_string(),
+            graphql_name: "Order".to_string(),
+            primary_key: "id".into(),
+            ..Default::default()
+        };
+        let schema = builder.build_schema(vec![entity]).await.unwrap();
+
+        let response = schema
+            .execute("{ list_order { id tags line_items { sku } attributes { key value } } }")
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.data.into_json().unwrap()["list_order"],
+            serde_json::json!([
+                {
+                    "id": "1",
+                    "tags": ["a", null],
+                    "line_items": [{ "sku": "x" }, { "sku": "y" }],
+                    "attributes": [{ "key": "size", "value": 3 }]
+                },
+                { "id": "2", "tags": [], "line_items": null, "attributes": [] },
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_decimal_big_int_and_bytes_columns() {
+        use deltalake::arrow::array::{BinaryArray, Decimal128Array, Int64Array};
+        use deltalake::arrow::datatypes::{DataType, Field, Schema};
+        use deltalake::arrow::record_batch::RecordBatch;
+        use deltalake::DeltaOps;
+        use std::sync::Arc;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("price", DataType::Decimal128(10, 2), true),
+            Field::new("views", DataType::Int64, true),
+            Field::new("digest", DataType::Binary, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2, 3])),
+                Arc::new(
+                    Decimal128Array::from(vec![1250, 310, 10000])
+                        .with_precision_and_scale(10, 2)
+                        .unwrap(),
+                ),
+                Arc::new(Int64Array::from(vec![9_007_199_254_740_993, 1, 2])),
+                Arc::new(BinaryArray::from_vec(vec![b"\x00\x01", b"abc", b"\xff"])),
+            ],
+        )
+        .unwrap();
+        DeltaOps::try_from_uri(path).await.unwrap().write(vec![batch]).await.unwrap();
+
+        let mut builder = SchemaBuilder::new();
+        builder.register_table_from_path("products", path).await.unwrap();
+        let entity = EntityConfig {
+            table: "products".to_string(),
+            graphql_name: "Product".to_string(),
+            primary_key: "id".into(),
+            ..Default::default()
+        };
+        let schema = builder.build_schema(vec![entity]).await.unwrap();
+
+        let execute = |query: &'static str| {
+            let schema = schema.clone();
+            async move {
+                let response = schema.execute(query).await;
+                assert!(response.errors.is_empty(), "{}: {:?}", query, response.errors);
+                response.data.into_json().unwrap()
+            }
+        };
+
+        let data = execute(
+            r#"{
+                list_product(filter: { price: { gte: "3.10", lt: 100 } }, orderBy: [{ field: price, direction: DESC }]) {
+                    id price views digest
+                }
+            }"#,
+        )
+        .await;
+        assert_eq!(
+            data["list_product"],
+            serde_json::json!([
+                { "id": "1", "price": "12.50", "views": "9007199254740993", "digest": "AAE=" },
+                { "id": "2", "price": "3.10", "views": 1, "digest": "YWJj" },
+            ])
+        );
+
+        let data = execute(
+            r#"{
+                a: list_product(filter: { digest: { eq: "/w==" } }) { id }
+                b: list_product(filter: { views: { gt: "9007199254740992" } }) { id }
+                c: product_aggregate { sum { price views } max { price } }
+            }"#,
+        )
+        .await;
+        assert_eq!(data["a"], serde_json::json!([{ "id": "3" }]));
+        assert_eq!(data["b"], serde_json::json!([{ "id": "1" }]));
+        assert_eq!(data["c"][0]["sum"]["price"], "115.60");
+        assert_eq!(data["c"][0]["sum"]["views"], "9007199254740996");
+        assert_eq!(data["c"][0]["max"]["price"], "100.00");
+    }
+
+    #[tokio::test]
+    async fn test_timestamp_columns_and_timezone_argument() {
+        use deltalake::arrow::array::{Int64Array, TimestampMicrosecondArray};
+        use deltalake::arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+        use deltalake::arrow::record_batch::RecordBatch;
+        use deltalake::DeltaOps;
+        use std::sync::Arc;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+
+        // 2024-01-15T10:00:00Z and 2024-07-01T22:30:00.5Z
+        let micros = vec![1_705_312_800_000_000, 1_719_873_000_500_000];
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("created_at", DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())), true),
+            Field::new("opens_at", DataType::Timestamp(TimeUnit::Microsecond, None), true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2])),
+                Arc::new(TimestampMicrosecondArray::from(micros.clone()).with_timezone("UTC")),
+                Arc::new(TimestampMicrosecondArray::from(micros)),
+            ],
+        )
+        .unwrap();
+        DeltaOps::try_from_uri(path).await.unwrap().write(vec![batch]).await.unwrap();
+
+        let mut builder = SchemaBuilder::new();
+        builder.register_table_from_path("events", path).await.unwrap();
+        let entity = EntityConfig {
+            table: "events".to_string(),
+            graphql_name: "Event".to_string(),
+            primary_key: "id".into(),
+            ..Default::default()
+        };
+        let schema = builder.build_schema(vec![entity]).await.unwrap();
+
+        let response = schema
+            .execute(
+                r#"{
+                    utc: list_event { id created_at opens_at }
+                    paris: list_event(timezone: "Europe/Paris") { created_at opens_at }
+                    offset: event(id: "1", timezone: "-03:30") { created_at }
+                    local: list_event(filter: { opens_at: { gt: "2024-07-01T22:00:00" } }) { id }
+                    instant: list_event(filter: { created_at: { lt: "2024-01-15T12:00:01+02:00" } }) { id }
+                    latest: event_aggregate { max { created_at opens_at } }
+                }"#,
+            )
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let data = response.data.into_json().unwrap();
+
+        assert_eq!(
+            data["utc"],
+            serde_json::json!([
+                { "id": "1", "created_at": "2024-01-15T10:00:00+00:00", "opens_at": "2024-01-15T10:00:00" },
+                { "id": "2", "created_at": "2024-07-01T22:30:00.500+00:00", "opens_at": "2024-07-01T22:30:00.500" },
+            ])
+        );
+        // Daylight saving time is applied per instant; wall-clock values are left alone
+        assert_eq!(
+            data["paris"],
+            serde_json::json!([
+                { "created_at": "2024-01-15T11:00:00+01:00", "opens_at": "2024-01-15T10:00:00" },
+                { "created_at": "2024-07-02T00:30:00.500+02:00", "opens_at": "2024-07-01T22:30:00.500" },
+            ])
+        );
+        assert_eq!(data["offset"]["created_at"], "2024-01-15T06:30:00-03:30");
+        assert_eq!(data["local"], serde_json::json!([{ "id": "2" }]));
+        assert_eq!(data["instant"], serde_json::json!([{ "id": "1" }]));
+        assert_eq!(data["latest"][0]["max"]["opens_at"], "2024-07-01T22:30:00.500");
+
+        let response = schema.execute(r#"{ list_event(timezone: "Mars/Olympus_Mons") { id } }"#).await;
+        assert!(response.errors[0].message.contains("Invalid timezone 'Mars/Olympus_Mons'"));
+    }
+
+    #[tokio::test]
+    async fn test_composite_primary_key() {
+        use deltalake::arrow::array::{Date32Array, Int64Array, StringArray};
+        use deltalake::arrow::datatypes::{DataType, Field, Schema};
+        use deltalake::arrow::record_batch::RecordBatch;
+        use deltalake::DeltaOps;
+        use std::sync::Arc;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+
+        // 19723 is 2024-01-01
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("date", DataType::Date32, false),
+            Field::new("region", DataType::Utf8, false),
+            Field::new("sku", DataType::Utf8, false),
+            Field::new("units", DataType::Int64, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Date32Array::from(vec![19724, 19723, 19723, 19723])),
+                Arc::new(StringArray::from(vec!["EU", "US", "EU", "EU"])),
+                Arc::new(StringArray::from(vec!["A1", "A1", "B2", "A1"])),
+                Arc::new(Int64Array::from(vec![4, 3, 7, 5])),
+            ],
+        )
+        .unwrap();
+        DeltaOps::try_from_uri(path).await.unwrap().write(vec![batch]).await.unwrap();
+
+        let mut builder = SchemaBuilder::new();
+        builder.register_table_from_path("daily_sales", path).await.unwrap();
+        let entity = EntityConfig {
+            table: "daily_sales".to_string(),
+            graphql_name: "DailySale".to_string(),
+            primary_key: vec!["date".to_string(), "region".to_string(), "sku".to_string()].into(),
+            mutations: true,
+            ..Default::default()
+        };
+        let schema = builder.build_schema(vec![entity]).await.unwrap();
+
+        let execute = |query: &'static str| {
+            let schema = schema.clone();
+            async move {
+                let response = schema.execute(query).await;
+                assert!(response.errors.is_empty(), "{}: {:?}", query, response.errors);
+                response.data.into_json().unwrap()
+            }
+        };
+
+        // One typed argument per key column; lookups are batched into one query
+        let data = execute(
+            r#"{
+                a: daily_sale(date: "2024-01-01", region: "EU", sku: "B2") { units }
+                b: daily_sale(date: "2024-01-01", region: "US", sku: "A1") { units }
+                c: daily_sale(date: "2024-01-02", region: "US", sku: "A1") { units }
+            }"#,
+        )
+        .await;
+        assert_eq!(data["a"]["units"], 7);
+        assert_eq!(data["b"]["units"], 3);
+        assert!(data["c"].is_null());
+
+        let data = execute(
+            r#"{
+                daily_sale_by_ids(ids: [
+                    { date: "2024-01-02", region: "EU", sku: "A1" }
+                    { date: "2024-01-02", region: "US", sku: "A1" }
+                    { date: "2024-01-01", region: "EU", sku: "B2" }
+                ]) { node { units } }
+            }"#,
+        )
+        .await;
+        assert_eq!(
+            data["daily_sale_by_ids"],
+            serde_json::json!([{ "node": { "units": 4 } }, { "node": null }, { "node": { "units": 7 } }])
+        );
+
+        let response = schema.execute(r#"{ daily_sale(date: "2024-01-01", region: "EU") { units } }"#).await;
+        assert!(response.errors[0].message.contains("sku"), "{:?}", response.errors);
+
+        // Lists are ordered by every key column
+        let data = execute("{ list_daily_sale { date region sku } }").await;
+        assert_eq!(
+            data["list_daily_sale"],
+            serde_json::json!([
+                { "date": "2024-01-01", "region": "EU", "sku": "A1" },
+                { "date": "2024-01-01", "region": "EU", "sku": "B2" },
+                { "date": "2024-01-01", "region": "US", "sku": "A1" },
+                { "date": "2024-01-02", "region": "EU", "sku": "A1" },
+            ])
+        );
+
+        // Cursors carry the whole key
+        let data = execute("{ daily_sale_connection(first: 2) { pageInfo { endCursor } } }").await;
+        let cursor = data["daily_sale_connection"]["pageInfo"]["endCursor"].as_str().unwrap().to_string();
+        let response = schema
+            .execute(format!(
+                r#"{{ daily_sale_connection(first: 5, after: "{}") {{ edges {{ node {{ region sku }} }} }} }}"#,
+                cursor
+            ))
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let data = response.data.into_json().unwrap();
+        assert_eq!(
+            data["daily_sale_connection"]["edges"],
+            serde_json::json!([
+                { "node": { "region": "US", "sku": "A1" } },
+                { "node": { "region": "EU", "sku": "A1" } },
+            ])
+        );
+
+        // Mutations match on all key columns
+        let data = execute(
+            r#"mutation {
+                createDailySale(input: { date: "2024-01-02", region: "US", sku: "A1", units: 1 }) { units }
+            }"#,
+        )
+        .await;
+        assert_eq!(data["createDailySale"]["units"], 1);
+
+        let response = schema
+            .execute(r#"mutation { createDailySale(input: { date: "2024-01-02", region: "US", sku: "A1" }) { units } }"#)
+            .await;
+        assert!(
+            response.errors[0].message.contains(r#"date "2024-01-02", region "US", sku "A1" already exists"#),
+            "{:?}",
+            response.errors
+        );
+
+        let data = execute(
+            r#"mutation {
+                updateDailySale(date: "2024-01-01", region: "EU", sku: "A1", patch: { units: 6 }) { units }
+            }"#,
+        )
+        .await;
+        assert_eq!(data["updateDailySale"]["units"], 6);
+
+        let data = execute(
+            r#"mutation {
+                upsertDailySale(input: { date: "2024-01-01", region: "EU", sku: "B2", units: 8 }) { units }
+            }"#,
+        )
+        .await;
+        assert_eq!(data["upsertDailySale"]["units"], 8);
+
+        let data = execute(r#"mutation { deleteDailySale(date: "2024-01-01", region: "US", sku: "A1") }"#).await;
+        assert_eq!(data["deleteDailySale"], true);
+
+        let data = execute("{ list_daily_sale { date region sku units } }").await;
+        assert_eq!(
+            data["list_daily_sale"],
+            serde_json::json!([
+                { "date": "2024-01-01", "region": "EU", "sku": "A1", "units": 6 },
+                { "date": "2024-01-01", "region": "EU", "sku": "B2", "units": 8 },
+                { "date": "2024-01-02", "region": "EU", "sku": "A1", "units": 4 },
+                { "date": "2024-01-02", "region": "US", "sku": "A1", "units": 1 },
+            ])
+        );
+    }
+}